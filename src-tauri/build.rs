@@ -1,6 +1,11 @@
 use std::env;
 use std::path::PathBuf;
 
+/// Tools every build ships
+const REQUIRED_BINARIES: &[&str] = &["yt-dlp", "aria2c", "ffmpeg"];
+/// Tools shipped when the release workflow built them for this platform (whisper.cpp for transcription)
+const OPTIONAL_BINARIES: &[&str] = &["whisper-cli"];
+
 fn main() {
     // Get the target directory where Rust builds the binary
     let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
@@ -30,9 +35,8 @@ fn main() {
         eprintln!("Warning: Failed to create target binaries directory: {}", e);
     } else {
         // Copy each binary
-        for binary in &["yt-dlp", "aria2c", "ffmpeg"] {
-            let ext = if cfg!(target_os = "windows") { ".exe" } else { "" };
-            let binary_name = format!("{}{}", binary, ext);
+        for binary in REQUIRED_BINARIES.iter().chain(OPTIONAL_BINARIES) {
+            let binary_name = format!("{}{}", binary, exe_ext());
             
             let src = binaries_src.join(&binary_name);
             let dst = target_binaries.join(&binary_name);
@@ -54,7 +58,7 @@ fn main() {
                         }
                     }
                 }
-            } else {
+            } else if REQUIRED_BINARIES.contains(binary) {
                 eprintln!("Warning: Binary not found: {}", src.display());
             }
         }
@@ -62,48 +66,57 @@ fn main() {
     
     // Tell Cargo to rerun this build script if the binaries change
     println!("cargo:rerun-if-changed=binaries");
+
+    write_checksums(&binaries_src, &out_dir);
+
     
     tauri_build::build()
 }
 
+/// Record the SHA-256 of each bundled binary so the app can detect modified or truncated copies
+fn write_checksums(binaries_src: &std::path::Path, out_dir: &str) {
+    use sha2::{Digest, Sha256};
+
+    let mut entries = String::new();
+    for binary in REQUIRED_BINARIES.iter().chain(OPTIONAL_BINARIES) {
+        let path = binaries_src.join(format!("{}{}", binary, exe_ext()));
+        match std::fs::read(&path) {
+            Ok(bytes) => entries.push_str(&format!("    (\"{}\", \"{:x}\"),\n", binary, Sha256::digest(&bytes))),
+            Err(e) if REQUIRED_BINARIES.contains(binary) => {
+                eprintln!("Warning: No checksum for {}: {}", path.display(), e)
+            }
+            Err(_) => {}
+        }
+    }
+    let source = format!("pub const BINARY_CHECKSUMS: &[(&str, &str)] = &[\n{}];\n", entries);
+    let dest = PathBuf::from(out_dir).join("binary_checksums.rs");
+    std::fs::write(&dest, source).expect("Failed to write binary checksums");
+}
+
+/// Binary directory for the target being built. A build script runs on the host, so the target
+/// comes from Cargo's `CARGO_CFG_*` variables rather than `cfg!`.
 fn get_platform_dir() -> &'static str {
-    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
-    return "windows-x64";
-    
-    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-    return "linux-x64";
-    
-    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
-    return "linux-arm64";
-    
-    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
-    return "macos-x64";
-    
-    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-    return "macos-arm64";
-    
-    #[cfg(all(target_os = "android", target_arch = "aarch64"))]
-    return "android-arm64";
-    
-    #[cfg(all(target_os = "android", target_arch = "arm"))]
-    return "android-arm";
-    
-    #[cfg(all(target_os = "android", target_arch = "x86"))]
-    return "android-x86";
-    
-    #[cfg(all(target_os = "android", target_arch = "x86_64"))]
-    return "android-x64";
-    
-    #[cfg(not(any(
-        all(target_os = "windows", target_arch = "x86_64"),
-        all(target_os = "linux", target_arch = "x86_64"),
-        all(target_os = "linux", target_arch = "aarch64"),
-        all(target_os = "macos", target_arch = "x86_64"),
-        all(target_os = "macos", target_arch = "aarch64"),
-        all(target_os = "android", target_arch = "aarch64"),
-        all(target_os = "android", target_arch = "arm"),
-        all(target_os = "android", target_arch = "x86"),
-        all(target_os = "android", target_arch = "x86_64"),
-    )))]
-    return "unknown";
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    match (os.as_str(), arch.as_str()) {
+        ("windows", "x86_64") => "windows-x64",
+        ("linux", "x86_64") => "linux-x64",
+        ("linux", "aarch64") => "linux-arm64",
+        ("macos", "x86_64") => "macos-x64",
+        ("macos", "aarch64") => "macos-arm64",
+        ("android", "aarch64") => "android-arm64",
+        ("android", "arm") => "android-arm",
+        ("android", "x86") => "android-x86",
+        ("android", "x86_64") => "android-x64",
+        _ => "unknown",
+    }
+}
+
+/// Executable extension on the target
+fn exe_ext() -> &'static str {
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        ".exe"
+    } else {
+        ""
+    }
 }
@@ -0,0 +1,256 @@
+// Persisted state for in-flight Android downloads, so a download interrupted by
+// process death can continue from its partial file with an HTTP Range request.
+#![cfg_attr(not(target_os = "android"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::io::AsyncWriteExt;
+
+use crate::{calculate_eta, format_speed, ProgressState};
+
+const STATE_FILE: &str = "inflight_downloads.json";
+const USER_AGENT: &str = "Mozilla/5.0 (Linux; Android 10; SM-G975F) AppleWebKit/537.36";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InflightDownload {
+    pub id: String,
+    pub url: String,
+    pub stream_url: String,
+    pub download_type: String,
+    pub quality: String,
+    pub title: String,
+    pub output_folder: String,
+    pub temp_path: String,
+    pub final_path: String,
+    pub bytes_written: u64,
+    pub total_bytes: u64,
+    /// Stream format the partial file holds (YouTube's `itag`); a re-extracted URL for another
+    /// format cannot continue it
+    #[serde(default)]
+    pub format_id: Option<String>,
+}
+
+fn state_path() -> Result<PathBuf, String> {
+    let base = std::env::var("UDL_FILES_DIR").unwrap_or_default();
+    if base.is_empty() {
+        return Err("not-android".into());
+    }
+    Ok(PathBuf::from(base).join(STATE_FILE))
+}
+
+pub fn load_all() -> Vec<InflightDownload> {
+    state_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(records: &[InflightDownload]) -> Result<(), String> {
+    let path = state_path()?;
+    let json = serde_json::to_string_pretty(records)
+        .map_err(|e| format!("Failed to serialize download state: {}", e))?;
+    // Write-then-rename so a kill during the write never leaves a truncated state file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write download state: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to save download state: {}", e))
+}
+
+pub fn upsert(record: &InflightDownload) -> Result<(), String> {
+    let mut records = load_all();
+    records.retain(|r| r.id != record.id);
+    records.push(record.clone());
+    save_all(&records)
+}
+
+pub fn remove(id: &str) -> Result<(), String> {
+    let mut records = load_all();
+    records.retain(|r| r.id != id);
+    save_all(&records)
+}
+
+/// Records whose partial file still exists, with `bytes_written` refreshed from disk
+pub fn interrupted() -> Vec<InflightDownload> {
+    load_all()
+        .into_iter()
+        .filter_map(|mut record| {
+            let len = std::fs::metadata(&record.temp_path).ok()?.len();
+            record.bytes_written = len;
+            Some(record)
+        })
+        .collect()
+}
+
+pub fn find(id: &str) -> Option<InflightDownload> {
+    interrupted().into_iter().find(|r| r.id == id)
+}
+
+pub fn find_matching(url: &str, download_type: &str, output_folder: &str) -> Option<InflightDownload> {
+    interrupted().into_iter().find(|r| {
+        r.url == url && r.download_type == download_type && r.output_folder == output_folder
+    })
+}
+
+pub fn new_id() -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!("android-{}", millis)
+}
+
+fn content_range_total(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    // Content-Range: bytes 1000-4999/5000
+    headers
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// The `itag` of a YouTube stream URL
+pub fn format_id(stream_url: &str) -> Option<String> {
+    url::Url::parse(stream_url)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "itag")
+        .map(|(_, value)| value.to_string())
+}
+
+/// GET the stream from where the partial file ends
+async fn request_from(client: &reqwest::Client, record: &InflightDownload) -> Result<reqwest::Response, String> {
+    let mut request = client.get(&record.stream_url);
+    if record.bytes_written > 0 {
+        eprintln!("Resuming {} from byte {}", record.title, record.bytes_written);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", record.bytes_written));
+    }
+    request.send().await.map_err(|e| format!("Failed to download content: {}", e))
+}
+
+/// Whether the server's answer to a Range request shows the partial file belongs to another
+/// stream: the range is past its end, or its length differs from the one the file was started with
+fn is_other_stream(response: &reqwest::Response, record: &InflightDownload) -> bool {
+    match response.status() {
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE => true,
+        reqwest::StatusCode::PARTIAL_CONTENT => {
+            record.total_bytes > 0
+                && content_range_total(response.headers()).is_some_and(|total| total != record.total_bytes)
+        }
+        _ => false,
+    }
+}
+
+/// Stream `record.stream_url` into its partial file, resuming from whatever is already on disk,
+/// then move the finished file into place. A partial file from another format or length is
+/// started over rather than appended to. Returns the final size in bytes.
+pub async fn stream_to_file<R: Runtime>(
+    app: &AppHandle<R>,
+    progress_state: &ProgressState,
+    mut record: InflightDownload,
+) -> Result<u64, String> {
+    let temp_path = PathBuf::from(&record.temp_path);
+
+    // Trust the partial file over the persisted counter; the last state flush may lag behind it
+    record.bytes_written = tokio::fs::metadata(&temp_path).await.map(|m| m.len()).unwrap_or(0);
+
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create download client: {}", e))?;
+
+    let stream_format = format_id(&record.stream_url);
+    if record.bytes_written > 0 && record.format_id != stream_format {
+        eprintln!("Stream format changed for {}, restarting from zero", record.title);
+        record.bytes_written = 0;
+        record.total_bytes = 0;
+    }
+    record.format_id = stream_format;
+
+    let mut response = request_from(&client, &record).await?;
+    if record.bytes_written > 0 && is_other_stream(&response, &record) {
+        eprintln!("Partial file of {} does not match the stream, restarting from zero", record.title);
+        record.bytes_written = 0;
+        record.total_bytes = 0;
+        response = request_from(&client, &record).await?;
+    }
+
+    if !response.status().is_success() {
+        return Err(format!("Download failed with status: {}", response.status()));
+    }
+
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if record.bytes_written > 0 && !resumed {
+        eprintln!("Server ignored Range request, restarting {} from zero", record.title);
+        record.bytes_written = 0;
+    }
+
+    record.total_bytes = if resumed {
+        content_range_total(response.headers())
+            .unwrap_or(record.bytes_written + response.content_length().unwrap_or(0))
+    } else {
+        response.content_length().unwrap_or(0)
+    };
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&temp_path)
+        .await
+        .map_err(|e| format!("Failed to open partial file {}: {}", temp_path.display(), e))?;
+
+    upsert(&record)?;
+
+    let started = Instant::now();
+    let resumed_from = record.bytes_written;
+    let mut last_flush = Instant::now();
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read download content: {}", e))?
+    {
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write partial file: {}", e))?;
+        record.bytes_written += chunk.len() as u64;
+
+        if last_flush.elapsed() >= Duration::from_millis(500) {
+            let _ = file.flush().await;
+            let _ = upsert(&record);
+            last_flush = Instant::now();
+
+            let elapsed = started.elapsed().as_secs_f64().max(0.1);
+            let speed = ((record.bytes_written - resumed_from) as f64 / elapsed) as u64;
+            let mut p = progress_state.lock().unwrap();
+            p.status = "downloading".into();
+            p.bytes_downloaded = record.bytes_written;
+            p.total_bytes = record.total_bytes;
+            if record.total_bytes > 0 {
+                p.percentage = (record.bytes_written as f64 / record.total_bytes as f64 * 100.0).min(100.0);
+            }
+            p.speed_bytes_per_sec = speed;
+            p.speed = format_speed(speed);
+            p.eta = calculate_eta(record.bytes_written, record.total_bytes, speed);
+            let _ = app.emit("download-progress", p.clone());
+        }
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write partial file: {}", e))?;
+    drop(file);
+
+    tokio::fs::rename(&temp_path, &record.final_path)
+        .await
+        .map_err(|e| format!("Failed to move {} into place: {}", record.final_path, e))?;
+    remove(&record.id)?;
+
+    Ok(record.bytes_written)
+}
@@ -0,0 +1,410 @@
+// Optional remote-control server: a token-authenticated HTTP + WebSocket API on localhost
+// (or the LAN) for queueing downloads, streaming progress and reading history.
+//
+//   GET  /api/v1/queue      queue summary and every job
+//   POST /api/v1/downloads  {url, download_type?, quality?, output_folder?, preset?}
+//   GET  /api/v1/history    ?limit=&status=
+//   POST /api/v1/ws-ticket  one-time ticket for opening the WebSocket from a browser
+//   GET  /api/v1/ws         queue snapshots once per second
+//
+// Clients authenticate with `Authorization: Bearer <token>`. Tokens in the query string end up
+// in proxy logs and browser history, so they are not accepted. Browsers cannot set headers on a
+// WebSocket, so `/ws` also takes `?ticket=` with a ticket that works once, within 30 seconds.
+use serde::Serialize;
+use tauri::{AppHandle, Runtime};
+
+use crate::settings::{self, ApiSettings};
+
+#[cfg(not(target_os = "android"))]
+const TOKEN_SECRET: &str = "api-token";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ApiStatus {
+    pub settings: ApiSettings,
+    pub running: bool,
+    pub address: Option<String>,
+    pub token: Option<String>,
+}
+
+#[cfg(not(target_os = "android"))]
+mod server {
+    use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+    use axum::extract::{Query, Request, State};
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::middleware::{self, Next};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use tauri::{AppHandle, Manager, Runtime};
+    use tokio::sync::oneshot;
+
+    use crate::download_manager::{self, DownloadManagerState, DownloadRequest, JobStatus, JobSummary, QueueProgress};
+    use crate::{history, presets, settings};
+
+    #[derive(Default)]
+    pub struct ApiServer {
+        pub shutdown: Option<oneshot::Sender<()>>,
+        pub address: Option<SocketAddr>,
+        pub token: Option<String>,
+    }
+
+    pub type ApiState = Arc<Mutex<ApiServer>>;
+
+    /// How long a WebSocket ticket can be redeemed
+    const TICKET_TTL: Duration = Duration::from_secs(30);
+
+    struct Context<R: Runtime> {
+        app: AppHandle<R>,
+        token: Arc<String>,
+        /// Unredeemed WebSocket tickets and when they expire
+        tickets: Arc<Mutex<HashMap<String, Instant>>>,
+    }
+
+    // Derived Clone would require R: Clone
+    impl<R: Runtime> Clone for Context<R> {
+        fn clone(&self) -> Self {
+            Self { app: self.app.clone(), token: self.token.clone(), tickets: self.tickets.clone() }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct QueueSnapshot {
+        summary: QueueProgress,
+        jobs: Vec<JobSummary>,
+    }
+
+    #[derive(Deserialize)]
+    struct SubmitBody {
+        url: String,
+        download_type: Option<String>,
+        quality: Option<String>,
+        output_folder: Option<String>,
+        preset: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct WsQuery {
+        ticket: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct HistoryQuery {
+        limit: Option<usize>,
+        status: Option<JobStatus>,
+    }
+
+    fn error(status: StatusCode, message: impl Into<String>) -> Response {
+        (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+    }
+
+    /// Compare without short-circuiting so response timing does not leak the token
+    fn token_matches(given: &str, expected: &str) -> bool {
+        given.len() == expected.len()
+            && given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+
+    fn bearer_matches(headers: &HeaderMap, expected: &str) -> bool {
+        let given = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        token_matches(given, expected)
+    }
+
+    async fn authenticate<R: Runtime>(
+        State(ctx): State<Context<R>>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if bearer_matches(request.headers(), &ctx.token) {
+            next.run(request).await
+        } else {
+            error(StatusCode::UNAUTHORIZED, "Missing or invalid API token")
+        }
+    }
+
+    fn snapshot<R: Runtime>(app: &AppHandle<R>) -> QueueSnapshot {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        QueueSnapshot { summary: manager.queue_progress(), jobs: manager.summaries() }
+    }
+
+    async fn queue<R: Runtime>(State(ctx): State<Context<R>>) -> Response {
+        Json(snapshot(&ctx.app)).into_response()
+    }
+
+    async fn submit<R: Runtime>(State(ctx): State<Context<R>>, Json(body): Json<SubmitBody>) -> Response {
+        match url::Url::parse(&body.url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => return error(StatusCode::BAD_REQUEST, format!("Invalid URL: {}", body.url)),
+        }
+
+        let current = settings::current(&ctx.app);
+        let (request, category) = match body.preset {
+            Some(name) => match presets::find(&ctx.app, &name) {
+                Ok(preset) => (
+                    DownloadRequest {
+                        url: body.url,
+                        download_type: preset.download_type,
+                        quality: preset.quality,
+                        output_folder: body.output_folder.or(preset.output_folder).unwrap_or_default(),
+                        start_time: None,
+                        end_time: None,
+                        filename: None,
+                        options: preset.options,
+                    },
+                    preset.category,
+                ),
+                Err(e) => return error(StatusCode::NOT_FOUND, e),
+            },
+            None => {
+                let (download_type, quality) = current.resolve_format(&body.url, body.download_type, body.quality);
+                let request = DownloadRequest {
+                    url: body.url,
+                    download_type,
+                    quality,
+                    output_folder: body.output_folder.unwrap_or_default(),
+                    start_time: None,
+                    end_time: None,
+                    filename: None,
+                    options: Default::default(),
+                };
+                (request, None)
+            }
+        };
+
+        let output_folder =
+            match current
+                .resolve_output_folder(Some(request.output_folder.clone()), &request.download_type, category)
+                .and_then(|folder| crate::storage::validate_output_folder(&ctx.app, &folder))
+            {
+                Ok(folder) => folder,
+                Err(e) => return error(StatusCode::BAD_REQUEST, e),
+            };
+        let request = DownloadRequest { output_folder, ..request };
+
+        eprintln!("🌐 API queued {}", request.url);
+        let id = download_manager::submit(&ctx.app, request);
+        (StatusCode::CREATED, Json(serde_json::json!({ "id": id }))).into_response()
+    }
+
+    async fn history_list<R: Runtime>(State(ctx): State<Context<R>>, Query(query): Query<HistoryQuery>) -> Response {
+        Json(history::recent(&ctx.app, query.limit.unwrap_or(100), query.status)).into_response()
+    }
+
+    async fn ws_ticket<R: Runtime>(State(ctx): State<Context<R>>) -> Response {
+        use rand::Rng;
+        let ticket: String = rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        let now = Instant::now();
+        let mut tickets = ctx.tickets.lock().unwrap();
+        tickets.retain(|_, expires| *expires > now);
+        tickets.insert(ticket.clone(), now + TICKET_TTL);
+        Json(serde_json::json!({ "ticket": ticket, "expires_in": TICKET_TTL.as_secs() })).into_response()
+    }
+
+    /// Redeem a ticket; each one opens a single connection
+    fn redeem_ticket<R: Runtime>(ctx: &Context<R>, ticket: &str) -> bool {
+        ctx.tickets.lock().unwrap().remove(ticket).is_some_and(|expires| expires > Instant::now())
+    }
+
+    /// Outside the bearer middleware: takes the header or a ticket
+    async fn ws<R: Runtime>(
+        State(ctx): State<Context<R>>,
+        headers: HeaderMap,
+        Query(query): Query<WsQuery>,
+        upgrade: WebSocketUpgrade,
+    ) -> Response {
+        let authorized = bearer_matches(&headers, &ctx.token)
+            || query.ticket.is_some_and(|ticket| redeem_ticket(&ctx, &ticket));
+        if !authorized {
+            return error(StatusCode::UNAUTHORIZED, "Missing or invalid API token or ticket");
+        }
+        upgrade.on_upgrade(move |socket| stream_progress(ctx.app, socket))
+    }
+
+    async fn stream_progress<R: Runtime>(app: AppHandle<R>, mut socket: WebSocket) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let Ok(json) = serde_json::to_string(&snapshot(&app)) else { continue };
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                incoming = socket.recv() => match incoming {
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    pub async fn start<R: Runtime>(app: &AppHandle<R>, api: &settings::ApiSettings, token: String) -> Result<SocketAddr, String> {
+        let host = if api.allow_lan { [0, 0, 0, 0] } else { [127, 0, 0, 1] };
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((host, api.port)))
+            .await
+            .map_err(|e| format!("Failed to listen on port {}: {}", api.port, e))?;
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+
+        if api.allow_lan {
+            eprintln!("⚠️  API is reachable from the LAN over plain HTTP; anyone on the network can read its token");
+        }
+
+        let ctx = Context { app: app.clone(), token: Arc::new(token.clone()), tickets: Arc::default() };
+        let router = Router::new()
+            .route("/api/v1/queue", get(queue::<R>))
+            .route("/api/v1/downloads", post(submit::<R>))
+            .route("/api/v1/history", get(history_list::<R>))
+            .route("/api/v1/ws-ticket", post(ws_ticket::<R>))
+            .layer(middleware::from_fn_with_state(ctx.clone(), authenticate::<R>))
+            .route("/api/v1/ws", get(ws::<R>))
+            .with_state(ctx);
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        tauri::async_runtime::spawn(async move {
+            let server = axum::serve(listener, router).with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+            if let Err(e) = server.await {
+                eprintln!("❌ API server stopped: {}", e);
+            }
+        });
+
+        let state = app.state::<ApiState>();
+        let mut server = state.lock().unwrap();
+        server.shutdown = Some(shutdown_tx);
+        server.address = Some(address);
+        server.token = Some(token);
+        Ok(address)
+    }
+
+    pub fn stop<R: Runtime>(app: &AppHandle<R>) {
+        let state = app.state::<ApiState>();
+        let mut server = state.lock().unwrap();
+        if let Some(shutdown) = server.shutdown.take() {
+            let _ = shutdown.send(());
+            eprintln!("🌐 API server stopped");
+        }
+        server.address = None;
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+pub use server::ApiState;
+
+/// The API token from the keychain, creating one on first use
+#[cfg(not(target_os = "android"))]
+fn token(regenerate: bool) -> Result<String, String> {
+    if !regenerate {
+        if let Some(token) = crate::secrets::get(TOKEN_SECRET)? {
+            return Ok(token);
+        }
+    }
+    use rand::Rng;
+    let token: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    crate::secrets::set(TOKEN_SECRET, &token)?;
+    Ok(token)
+}
+
+/// Stop any running server and start a new one if the settings enable it
+pub async fn restart<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = app;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        server::stop(app);
+        let api = settings::current(app).api;
+        if !api.enabled {
+            return Ok(());
+        }
+        let address = server::start(app, &api, token(false)?).await?;
+        eprintln!("🌐 API server listening on http://{}", address);
+        Ok(())
+    }
+}
+
+pub fn spawn_startup<R: Runtime>(app: AppHandle<R>) {
+    #[cfg(target_os = "android")]
+    let _ = app;
+    #[cfg(not(target_os = "android"))]
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = restart(&app).await {
+            eprintln!("⚠️ {}", e);
+        }
+    });
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> ApiStatus {
+    let settings = settings::current(app).api;
+    #[cfg(target_os = "android")]
+    {
+        let _ = app;
+        ApiStatus { settings, running: false, address: None, token: None }
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        use tauri::Manager;
+        let state = app.state::<ApiState>();
+        let server = state.lock().unwrap();
+        ApiStatus {
+            settings,
+            running: server.shutdown.is_some(),
+            address: server.address.map(|a| a.to_string()),
+            token: server.token.clone(),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_api_status<R: Runtime>(app_handle: AppHandle<R>) -> Result<ApiStatus, String> {
+    Ok(status(&app_handle))
+}
+
+#[tauri::command]
+pub async fn set_api_settings<R: Runtime>(app_handle: AppHandle<R>, api: ApiSettings) -> Result<ApiStatus, String> {
+    api.validate()?;
+    let updated = {
+        use tauri::Manager;
+        let state = app_handle.state::<settings::SettingsState>();
+        let mut settings = state.lock().unwrap();
+        settings.api = api;
+        settings.clone()
+    };
+    settings::save(&app_handle, &updated)?;
+    restart(&app_handle).await?;
+    Ok(status(&app_handle))
+}
+
+/// Invalidate the current token; connected clients must re-authenticate
+#[tauri::command]
+pub async fn regenerate_api_token<R: Runtime>(app_handle: AppHandle<R>) -> Result<ApiStatus, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = app_handle;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        token(true)?;
+        restart(&app_handle).await?;
+        Ok(status(&app_handle))
+    }
+}
@@ -0,0 +1,412 @@
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{binary_manager, calculate_eta, format_speed, settings, ProgressState};
+
+/// aria2 error code for a failed checksum verification
+const ERROR_CHECKSUM_MISMATCH: &str = "32";
+
+const STATUS_KEYS: &[&str] = &[
+    "gid",
+    "status",
+    "totalLength",
+    "completedLength",
+    "downloadSpeed",
+    "connections",
+    "errorCode",
+    "errorMessage",
+    "files",
+    "followedBy",
+];
+
+/// Handle for talking to the aria2c JSON-RPC endpoint
+#[derive(Debug, Clone)]
+pub struct Aria2Rpc {
+    endpoint: String,
+    secret: String,
+    client: reqwest::Client,
+}
+
+/// The long-running `aria2c --enable-rpc` process shared by all direct downloads
+#[derive(Default)]
+pub struct Aria2Daemon {
+    rpc: Option<Aria2Rpc>,
+    child: Option<Child>,
+}
+
+pub type Aria2State = Arc<Mutex<Aria2Daemon>>;
+
+impl Aria2Rpc {
+    async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value, String> {
+        let mut full_params = vec![json!(format!("token:{}", self.secret))];
+        full_params.extend(params);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "u-download",
+            "method": method,
+            "params": full_params,
+        });
+
+        let response: Value = self
+            .client
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("aria2 RPC {} request failed: {}", method, e))?
+            .json()
+            .await
+            .map_err(|e| format!("aria2 RPC {} returned invalid JSON: {}", method, e))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!(
+                "aria2 RPC {} failed: {}",
+                method,
+                error["message"].as_str().unwrap_or("unknown error")
+            ));
+        }
+        Ok(response["result"].clone())
+    }
+
+    pub async fn add_uri(&self, uri: &str, options: Value) -> Result<String, String> {
+        let gid = self.call("aria2.addUri", vec![json!([uri]), options]).await?;
+        gid.as_str()
+            .map(|g| g.to_string())
+            .ok_or_else(|| "aria2 RPC addUri returned no gid".to_string())
+    }
+
+    pub async fn add_metalink(&self, document: &[u8], options: Value) -> Result<Vec<String>, String> {
+        use base64::Engine;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(document);
+        let gids = self
+            .call("aria2.addMetalink", vec![json!(encoded), options])
+            .await?;
+        Ok(gids
+            .as_array()
+            .map(|list| list.iter().filter_map(|g| g.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default())
+    }
+
+    pub async fn tell_status(&self, gid: &str) -> Result<Value, String> {
+        self.call("aria2.tellStatus", vec![json!(gid), json!(STATUS_KEYS)]).await
+    }
+
+    pub async fn pause(&self, gid: &str) -> Result<(), String> {
+        self.call("aria2.pause", vec![json!(gid)]).await.map(|_| ())
+    }
+
+    pub async fn unpause(&self, gid: &str) -> Result<(), String> {
+        self.call("aria2.unpause", vec![json!(gid)]).await.map(|_| ())
+    }
+
+    /// Change options of a transfer in flight, e.g. `max-download-limit`
+    pub async fn change_option(&self, gid: &str, options: Value) -> Result<(), String> {
+        self.call("aria2.changeOption", vec![json!(gid), options]).await.map(|_| ())
+    }
+
+    pub async fn remove(&self, gid: &str) -> Result<(), String> {
+        self.call("aria2.remove", vec![json!(gid)]).await.map(|_| ())
+    }
+}
+
+fn random_secret() -> String {
+    use rand::Rng;
+    let value: u128 = rand::thread_rng().gen();
+    format!("{:032x}", value)
+}
+
+fn free_local_port() -> Result<u16, String> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Failed to reserve a port for aria2c RPC: {}", e))?;
+    listener
+        .local_addr()
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to reserve a port for aria2c RPC: {}", e))
+}
+
+/// Serializes spawning the daemon and waiting for its RPC port, so concurrent first callers
+/// don't each start an aria2c or get a handle before the port accepts connections
+static INIT: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The published handle, if the daemon is running and has answered RPC calls
+fn ready_rpc(state: &Aria2State) -> Option<Aria2Rpc> {
+    let mut daemon = state.lock().unwrap();
+    let alive = daemon
+        .child
+        .as_mut()
+        .map(|child| matches!(child.try_wait(), Ok(None)))
+        .unwrap_or(false);
+    if alive {
+        daemon.rpc.clone()
+    } else {
+        None
+    }
+}
+
+/// Return an RPC handle, spawning the aria2c daemon on first use (or if it has died)
+pub async fn rpc<R: Runtime>(app: &AppHandle<R>) -> Result<Aria2Rpc, String> {
+    let state = app.state::<Aria2State>().inner().clone();
+    if let Some(rpc) = ready_rpc(&state) {
+        return Ok(rpc);
+    }
+
+    let _init = INIT.lock().await;
+    // Another caller may have started the daemon while we waited for the lock
+    if let Some(rpc) = ready_rpc(&state) {
+        return Ok(rpc);
+    }
+
+    let rpc = {
+        let mut daemon = state.lock().unwrap();
+        // A daemon that never answered, or has exited, is replaced
+        if let Some(mut child) = daemon.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        daemon.rpc = None;
+
+        let paths = binary_manager::resolve_paths(app)?;
+        binary_manager::ensure_executable(&paths)?;
+
+        let port = free_local_port()?;
+        let secret = random_secret();
+        let aria2_settings = settings::current(app).aria2;
+
+        let mut cmd = binary_manager::tool_command(&paths.aria2c);
+        cmd.arg("--enable-rpc")
+            .arg("--rpc-listen-all=false")
+            .arg(format!("--rpc-listen-port={}", port))
+            .arg(format!("--rpc-secret={}", secret))
+            // Exit on our own if the app dies without cleaning up
+            .arg(format!("--stop-with-process={}", std::process::id()))
+            .arg(format!("--max-connection-per-server={}", aria2_settings.connections))
+            .arg(format!("--split={}", aria2_settings.split))
+            .arg(format!("--min-split-size={}M", aria2_settings.chunk_size_mb))
+            .arg("--continue=true")
+            .arg("--auto-file-renaming=true")
+            .arg("--console-log-level=warn")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        eprintln!("Starting aria2c RPC daemon on port {}", port);
+        let child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start bundled aria2c: {}", e))?;
+        daemon.child = Some(child);
+
+        Aria2Rpc {
+            endpoint: format!("http://127.0.0.1:{}/jsonrpc", port),
+            secret,
+            client: reqwest::Client::new(),
+        }
+    };
+
+    // Give aria2c a moment to bind its RPC port; the handle is published only once it answers
+    let mut last_error = String::new();
+    for _ in 0..20 {
+        match rpc.call("aria2.getVersion", vec![]).await {
+            Ok(version) => {
+                eprintln!("✅ aria2c RPC ready (version {})", version["version"].as_str().unwrap_or("unknown"));
+                state.lock().unwrap().rpc = Some(rpc.clone());
+                return Ok(rpc);
+            }
+            Err(e) => last_error = e,
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+    }
+    shutdown(&state);
+    Err(format!("aria2c RPC did not become ready: {}", last_error))
+}
+
+/// Stop the RPC daemon if it is running
+pub fn shutdown(state: &Aria2State) {
+    let mut daemon = state.lock().unwrap();
+    if let Some(mut child) = daemon.child.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    daemon.rpc = None;
+}
+
+pub fn is_metalink_source(source: &str) -> bool {
+    let lower = source.to_lowercase();
+    let path = lower.split(['?', '#']).next().unwrap_or("");
+    path.ends_with(".meta4") || path.ends_with(".metalink")
+}
+
+/// Queue a plain HTTP(S)/FTP file. Returns the aria2 gid.
+pub async fn add_direct<R: Runtime>(
+    app: &AppHandle<R>,
+    url: &str,
+    output_folder: &str,
+    filename: Option<&str>,
+) -> Result<String, String> {
+    let mut options = settings::current(app).aria2.rpc_options();
+    options["dir"] = json!(output_folder);
+    // Pick up a partial file left by an interrupted session instead of starting over
+    options["continue"] = json!("true");
+    if let Some(name) = filename.filter(|n| !n.trim().is_empty()) {
+        let name = crate::storage::validate_file_name(name)?;
+        let name = if cfg!(windows) { crate::windows_paths::safe_name(&name) } else { name };
+        options["out"] = json!(name);
+    }
+    rpc(app).await?.add_uri(url, options).await
+}
+
+/// Queue every file described by a metalink document (local path or URL). aria2c spreads
+/// segments across the listed mirrors and verifies the declared piece/file hashes.
+pub async fn add_metalink<R: Runtime>(
+    app: &AppHandle<R>,
+    source: &str,
+    output_folder: &str,
+) -> Result<Vec<String>, String> {
+    // Segmentation is re-sent per download so settings changes apply without restarting the daemon
+    let mut options = settings::current(app).aria2.rpc_options();
+    options["dir"] = json!(output_folder);
+    options["check-integrity"] = json!("true");
+    options["metalink-enable-unique-protocol"] = json!("true");
+    let rpc = rpc(app).await?;
+
+    if Path::new(source).is_file() {
+        let document = std::fs::read(source)
+            .map_err(|e| format!("Failed to read metalink file {}: {}", source, e))?;
+        rpc.add_metalink(&document, options).await
+    } else {
+        url::Url::parse(source).map_err(|e| format!("Invalid metalink source: {}", e))?;
+        // Fetch the document in memory and follow it instead of saving the .meta4 itself
+        options["follow-metalink"] = json!("mem");
+        Ok(vec![rpc.add_uri(source, options).await?])
+    }
+}
+
+fn number_field(status: &Value, key: &str) -> u64 {
+    status[key].as_str().and_then(|s| s.parse().ok()).unwrap_or(0)
+}
+
+/// Poll aria2c for the given gids until all of them finish, translating `tellStatus` into
+/// progress events. Returns the paths of the downloaded files.
+pub async fn track<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    progress_state: &ProgressState,
+    gids: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let rpc = rpc(app).await?;
+
+    {
+        let mut progress = progress_state.lock().unwrap();
+        progress.download_start_time = std::time::SystemTime::now();
+        progress.status = "downloading".to_string();
+        progress.percentage = 0.0;
+        progress.bytes_downloaded = 0;
+        progress.total_bytes = 0;
+        progress.speed_bytes_per_sec = 0;
+        progress.speed = format_speed(0);
+        progress.eta = String::new();
+        progress.connections = 0;
+    }
+
+    let mut pending = gids;
+    let mut saved_paths = Vec::new();
+    let mut finished_bytes = 0u64;
+
+    while !pending.is_empty() {
+        let mut next = Vec::new();
+        let mut followed_now = Vec::new();
+        let mut completed = 0u64;
+        let mut total = 0u64;
+        let mut speed = 0u64;
+        let mut connections = 0u32;
+        let mut all_paused = true;
+
+        for gid in &pending {
+            let status = rpc.tell_status(gid).await?;
+            let length = number_field(&status, "totalLength");
+
+            match status["status"].as_str().unwrap_or("") {
+                "complete" => {
+                    // A followed metalink hands its work over to new gids
+                    let followed: Vec<String> = status["followedBy"]
+                        .as_array()
+                        .map(|list| list.iter().filter_map(|g| g.as_str().map(|s| s.to_string())).collect())
+                        .unwrap_or_default();
+                    if !followed.is_empty() {
+                        followed_now.extend(followed);
+                        continue;
+                    }
+
+                    finished_bytes += length;
+                    if let Some(files) = status["files"].as_array() {
+                        saved_paths.extend(
+                            files
+                                .iter()
+                                .filter_map(|f| f["path"].as_str())
+                                .filter(|p| !p.is_empty())
+                                .map(|p| p.to_string()),
+                        );
+                    }
+                }
+                "error" => {
+                    let code = status["errorCode"].as_str().unwrap_or("");
+                    let message = status["errorMessage"].as_str().unwrap_or("unknown error");
+                    return Err(if code == ERROR_CHECKSUM_MISMATCH {
+                        "aria2c checksum verification failed: the downloaded data does not match the metalink hashes".to_string()
+                    } else {
+                        format!("aria2c failed (error {}): {}", code, message)
+                    });
+                }
+                "removed" => return Err("Download was cancelled".to_string()),
+                state => {
+                    all_paused &= state == "paused";
+                    completed += number_field(&status, "completedLength");
+                    total += length;
+                    speed += number_field(&status, "downloadSpeed");
+                    connections += number_field(&status, "connections") as u32;
+                    next.push(gid.clone());
+                }
+            }
+        }
+
+        // The job pauses, cancels and limits the transfers doing the work, not the finished parent
+        if !followed_now.is_empty() {
+            next.extend(followed_now.iter().cloned());
+            if let Some(limit) = crate::download_manager::adopt_gids(app, id, &next) {
+                for gid in &followed_now {
+                    rpc.change_option(gid, json!({ "max-download-limit": limit.to_string() })).await?;
+                }
+            }
+        }
+
+        pending = next;
+        if pending.is_empty() {
+            break;
+        }
+
+        let completed = completed + finished_bytes;
+        let total = total + finished_bytes;
+        let progress_copy = {
+            let mut progress = progress_state.lock().unwrap();
+            progress.status = if all_paused { "paused" } else { "downloading" }.to_string();
+            progress.bytes_downloaded = completed;
+            progress.total_bytes = total;
+            if total > 0 {
+                progress.percentage = (completed as f64 / total as f64 * 100.0).min(100.0);
+            }
+            progress.speed_bytes_per_sec = speed;
+            progress.speed = format_speed(speed);
+            progress.eta = calculate_eta(completed, total, speed);
+            progress.connections = connections;
+            progress.clone()
+        };
+        let _ = app.emit("download-progress", progress_copy);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    Ok(saved_paths)
+}
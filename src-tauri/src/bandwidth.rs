@@ -0,0 +1,183 @@
+// Connection speed probe and the quality suggestions built on it: the highest quality tier
+// whose estimated size downloads fast enough on the measured connection
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+
+use crate::download_manager::now_millis;
+use crate::formats::{self, SizeEstimate};
+
+/// Served by Cloudflare's speed test for exactly the requested number of bytes
+const PROBE_URL: &str = "https://speed.cloudflare.com/__down?bytes=25000000";
+const PROBE_MAX_BYTES: u64 = 25_000_000;
+const PROBE_MAX_TIME: Duration = Duration::from_secs(8);
+/// Suggestions reuse a measurement this recent instead of probing again
+const MEASUREMENT_TTL_MS: u64 = 10 * 60 * 1000;
+/// Share of the measured speed a download is expected to get
+const HEADROOM: f64 = 0.8;
+/// Longest acceptable download when the video's duration is unknown
+const DEFAULT_BUDGET_SECS: f64 = 10.0 * 60.0;
+/// Quality tiers from lowest to highest, without "best"
+const TIERS: &[&str] = &["360", "480", "720", "1080", "1440", "2160", "4320"];
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct BandwidthMeasurement {
+    pub bytes_per_sec: u64,
+    pub bytes: u64,
+    pub elapsed_ms: u64,
+    pub measured_at_ms: u64,
+}
+
+static LAST_MEASUREMENT: Mutex<Option<BandwidthMeasurement>> = Mutex::new(None);
+
+/// Download from the probe URL for a few seconds and time it
+async fn probe(url: &str) -> Result<BandwidthMeasurement, String> {
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_MAX_TIME + Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Failed to start the speed test: {}", e))?;
+    let started = Instant::now();
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Speed test failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Speed test failed with status: {}", response.status()));
+    }
+
+    let mut bytes = 0u64;
+    while bytes < PROBE_MAX_BYTES && started.elapsed() < PROBE_MAX_TIME {
+        match response.chunk().await {
+            Ok(Some(chunk)) => bytes += chunk.len() as u64,
+            Ok(None) => break,
+            Err(e) if bytes == 0 => return Err(format!("Speed test failed: {}", e)),
+            // Cut off by the client timeout; what arrived so far is still a measurement
+            Err(_) => break,
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64().max(0.001);
+    if bytes == 0 {
+        return Err("The speed test received no data".to_string());
+    }
+    Ok(BandwidthMeasurement {
+        bytes_per_sec: (bytes as f64 / elapsed) as u64,
+        bytes,
+        elapsed_ms: (elapsed * 1000.0) as u64,
+        measured_at_ms: now_millis(),
+    })
+}
+
+/// Time a short download to estimate the connection's speed. `url` replaces the default probe,
+/// e.g. with a file on the server the downloads will come from.
+#[tauri::command]
+pub async fn measure_bandwidth(url: Option<String>) -> Result<BandwidthMeasurement, String> {
+    let url = url.filter(|url| !url.trim().is_empty()).unwrap_or_else(|| PROBE_URL.to_string());
+    let measurement = probe(&url).await?;
+    eprintln!(
+        "📶 Measured {:.1} Mbit/s ({} bytes in {} ms)",
+        measurement.bytes_per_sec as f64 * 8.0 / 1_000_000.0,
+        measurement.bytes,
+        measurement.elapsed_ms
+    );
+    *LAST_MEASUREMENT.lock().unwrap() = Some(measurement);
+    Ok(measurement)
+}
+
+/// The last measurement if it is recent, else a new one
+async fn current_bandwidth() -> Result<BandwidthMeasurement, String> {
+    let cached = *LAST_MEASUREMENT.lock().unwrap();
+    match cached {
+        Some(measurement) if now_millis().saturating_sub(measurement.measured_at_ms) < MEASUREMENT_TTL_MS => {
+            Ok(measurement)
+        }
+        _ => measure_bandwidth(None).await,
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QualitySuggestion {
+    pub quality: String,
+    pub estimate: Option<SizeEstimate>,
+    /// Expected download time at the measured speed
+    pub seconds: Option<u64>,
+    pub bandwidth: BandwidthMeasurement,
+    /// False when not even the lowest tier downloads in time; `quality` is then the lowest tier
+    pub fits: bool,
+}
+
+/// Highest tier that downloads within the budget: `max_minutes` when given, else as fast as
+/// the video plays (or within ten minutes when its length is unknown)
+pub fn pick_quality(
+    metadata: &serde_json::Value,
+    download_type: &str,
+    bandwidth: BandwidthMeasurement,
+    max_minutes: Option<u32>,
+) -> QualitySuggestion {
+    let speed = bandwidth.bytes_per_sec as f64 * HEADROOM;
+    let duration = metadata["duration"].as_f64().filter(|d| *d > 0.0);
+    let budget_secs = match (max_minutes, duration) {
+        (Some(minutes), _) => f64::from(minutes) * 60.0,
+        (None, Some(duration)) => duration,
+        (None, None) => DEFAULT_BUDGET_SECS,
+    };
+    let seconds_for = |estimate: &SizeEstimate| estimate.bytes as f64 / speed.max(1.0);
+    let suggestion = |quality: &str, estimate: Option<SizeEstimate>, fits: bool| QualitySuggestion {
+        quality: quality.to_string(),
+        estimate,
+        seconds: estimate.as_ref().map(|e| seconds_for(e).ceil() as u64),
+        bandwidth,
+        fits,
+    };
+
+    if formats::is_audio(download_type) {
+        let estimate = formats::estimate_size(metadata, download_type, "best");
+        let fits = estimate.as_ref().is_none_or(|e| seconds_for(e) <= budget_secs);
+        return suggestion("best", estimate, fits);
+    }
+
+    // A tier above the video's own resolution selects the same streams as the tier below it;
+    // keep only the lowest label for each distinct size
+    let mut tiers: Vec<(&str, SizeEstimate)> = Vec::new();
+    for tier in TIERS {
+        if let Some(estimate) = formats::estimate_size(metadata, download_type, tier) {
+            if tiers.last().is_none_or(|(_, last)| last.bytes != estimate.bytes) {
+                tiers.push((tier, estimate));
+            }
+        }
+    }
+    match tiers.iter().rev().find(|(_, estimate)| seconds_for(estimate) <= budget_secs) {
+        Some((tier, estimate)) => suggestion(tier, Some(*estimate), true),
+        None => match tiers.first() {
+            Some((tier, estimate)) => suggestion(tier, Some(*estimate), false),
+            // The site reports no sizes or bitrates; nothing to go on
+            None => suggestion("best", None, true),
+        },
+    }
+}
+
+/// Suggested quality for a URL on the current connection, measuring it first if needed
+pub async fn suggest<R: Runtime>(
+    app: &AppHandle<R>,
+    url: &str,
+    download_type: &str,
+    max_minutes: Option<u32>,
+) -> Result<QualitySuggestion, String> {
+    let metadata = crate::fetch_metadata_json(app, url).await?;
+    let bandwidth = current_bandwidth().await?;
+    let suggestion = pick_quality(&metadata, download_type, bandwidth, max_minutes);
+    eprintln!("📶 Suggesting {} for {} (fits: {})", suggestion.quality, url, suggestion.fits);
+    Ok(suggestion)
+}
+
+#[tauri::command]
+pub async fn suggest_quality<R: Runtime>(
+    app_handle: AppHandle<R>,
+    url: String,
+    downloadType: Option<String>,
+    maxMinutes: Option<u32>,
+) -> Result<QualitySuggestion, String> {
+    let (download_type, _) = crate::settings::current(&app_handle).resolve_format(&url, downloadType, None);
+    suggest(&app_handle, &url, &download_type, maxMinutes).await
+}
@@ -0,0 +1,162 @@
+// Operations over many URLs at once: concurrent metadata lookups and batch downloads
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Semaphore;
+
+use crate::download_manager::{self, now_millis, DownloadManagerState, DownloadRequest};
+
+/// yt-dlp processes running at once for a batch lookup
+const METADATA_WORKERS: usize = 4;
+const MAX_BATCH_URLS: usize = 500;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MetadataBatchItem {
+    pub batch_id: String,
+    /// Position of the URL in the submitted list
+    pub index: usize,
+    pub url: String,
+    pub metadata: Option<crate::VideoMetadata>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MetadataBatchComplete {
+    pub batch_id: String,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Unique, trimmed, non-empty URLs in their original order
+fn clean_urls(urls: Vec<String>) -> Result<Vec<String>, String> {
+    let mut seen = std::collections::HashSet::new();
+    let urls: Vec<String> = urls
+        .into_iter()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty() && seen.insert(url.clone()))
+        .collect();
+    if urls.is_empty() {
+        return Err("No URLs given".to_string());
+    }
+    if urls.len() > MAX_BATCH_URLS {
+        return Err(format!("At most {} URLs can be processed at once", MAX_BATCH_URLS));
+    }
+    Ok(urls)
+}
+
+/// Look up metadata for many URLs with a bounded pool of yt-dlp workers. Returns a batch id at
+/// once; each result arrives as a `metadata-batch-item` event, then `metadata-batch-complete`.
+#[tauri::command]
+pub async fn get_metadata_batch<R: Runtime>(app_handle: AppHandle<R>, urls: Vec<String>) -> Result<String, String> {
+    let urls = clean_urls(urls)?;
+    let batch_id = format!("batch-{}", now_millis());
+    eprintln!("📋 Fetching metadata for {} URLs ({})", urls.len(), batch_id);
+
+    let semaphore = Arc::new(Semaphore::new(METADATA_WORKERS));
+    let mut tasks = Vec::with_capacity(urls.len());
+    for (index, url) in urls.into_iter().enumerate() {
+        let app = app_handle.clone();
+        let semaphore = semaphore.clone();
+        let batch_id = batch_id.clone();
+        tasks.push(tauri::async_runtime::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            let result = crate::fetch_metadata_json(&app, &url).await;
+            let item = MetadataBatchItem {
+                batch_id,
+                index,
+                url,
+                metadata: result.as_ref().ok().map(crate::parse_video_metadata),
+                error: result.err(),
+            };
+            let _ = app.emit("metadata-batch-item", item.clone());
+            Some(item.error.is_none())
+        }));
+    }
+
+    let app = app_handle.clone();
+    let id = batch_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut summary = MetadataBatchComplete { batch_id: id, succeeded: 0, failed: 0 };
+        for task in tasks {
+            match task.await {
+                Ok(Some(true)) => summary.succeeded += 1,
+                _ => summary.failed += 1,
+            }
+        }
+        eprintln!("📋 {}: {} resolved, {} failed", summary.batch_id, summary.succeeded, summary.failed);
+        let _ = app.emit("metadata-batch-complete", summary);
+    });
+
+    Ok(batch_id)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BatchStarted {
+    pub batch_id: String,
+    /// Download ids in the order of the submitted URLs
+    pub ids: Vec<String>,
+}
+
+/// Queue many URLs with one preset. Every URL is checked before anything is queued, so a typo
+/// does not leave half a batch behind. `batch-complete` is emitted when the last item finishes.
+pub fn queue_batch<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    urls: Vec<String>,
+    preset: &str,
+    output_folder: Option<String>,
+) -> Result<BatchStarted, String> {
+    let urls = clean_urls(urls)?;
+    for url in &urls {
+        match url::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => return Err(format!("Not a valid http(s) URL: {}", url)),
+        }
+    }
+
+    let preset = crate::presets::find(app_handle, preset)?;
+    preset.options.validate()?;
+    let output_folder = crate::settings::current(app_handle).resolve_output_folder(
+        output_folder.filter(|folder| !folder.trim().is_empty()).or(preset.output_folder.clone()),
+        &preset.download_type,
+        preset.category,
+    )?;
+    let output_folder = crate::storage::validate_output_folder(app_handle, &output_folder)?;
+
+    let batch_id = format!("batch-{}", now_millis());
+    let ids = {
+        let state = app_handle.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        urls.into_iter()
+            .map(|url| {
+                let id = manager.enqueue(DownloadRequest {
+                    url,
+                    download_type: preset.download_type.clone(),
+                    quality: preset.quality.clone(),
+                    output_folder: output_folder.clone(),
+                    start_time: None,
+                    end_time: None,
+                    filename: None,
+                    options: preset.options.clone(),
+                });
+                if let Some(job) = manager.job_mut(&id) {
+                    job.batch_id = Some(batch_id.clone());
+                }
+                id
+            })
+            .collect::<Vec<_>>()
+    };
+
+    eprintln!("📦 Queued batch {} with {} downloads using preset \"{}\"", batch_id, ids.len(), preset.name);
+    download_manager::schedule(app_handle);
+    Ok(BatchStarted { batch_id, ids })
+}
+
+#[tauri::command]
+pub async fn start_batch_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    urls: Vec<String>,
+    preset: String,
+    outputFolder: Option<String>,
+) -> Result<BatchStarted, String> {
+    queue_batch(&app_handle, urls, &preset, outputFolder)
+}
@@ -1,5 +1,12 @@
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tauri::{path::BaseDirectory, AppHandle, Manager, Runtime};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+use tauri::{path::BaseDirectory, AppHandle, Emitter, Manager, Runtime};
+
+// SHA-256 of the binaries this build bundles, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/binary_checksums.rs"));
 
 #[derive(Debug, Clone)]
 pub struct BinaryPaths {
@@ -51,7 +58,7 @@ fn platform_dir() -> &'static str {
     { return "unknown"; }
 }
 
-fn exe_name(base: &str) -> String {
+pub(crate) fn exe_name(base: &str) -> String {
     #[cfg(target_os = "windows")]
     { format!("{}.exe", base) }
     #[cfg(not(target_os = "windows"))]
@@ -276,8 +283,28 @@ fn try_resolve_dev_paths(
     None
 }
 
-/// Enhanced binary resolution with comprehensive fallback system
+/// Where the in-app updater installs yt-dlp; that copy takes precedence over the bundled one
+pub fn managed_yt_dlp<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(dir.join("yt-dlp").join(exe_name("yt-dlp")))
+}
+
+/// Bundled binaries, with yt-dlp replaced by the updated copy when there is one
 pub fn resolve_paths<R: Runtime>(app: &AppHandle<R>) -> Result<BinaryPaths, String> {
+    let mut paths = resolve_bundled_paths(app)?;
+    if let Ok(managed) = managed_yt_dlp(app) {
+        if managed.is_file() {
+            paths.yt_dlp = managed;
+        }
+    }
+    Ok(paths)
+}
+
+/// Enhanced binary resolution with comprehensive fallback system
+fn resolve_bundled_paths<R: Runtime>(app: &AppHandle<R>) -> Result<BinaryPaths, String> {
     let plat = platform_dir();
     let y_name = exe_name("yt-dlp");
     let a_name = exe_name("aria2c");
@@ -388,8 +415,131 @@ pub fn resolve_paths<R: Runtime>(app: &AppHandle<R>) -> Result<BinaryPaths, Stri
     ))
 }
 
-/// Ensure binaries have executable permissions on Unix systems
+/// An optional tool shipped next to the bundled binaries (e.g. whisper-cli), if this build has it
+pub fn optional_binary(paths: &BinaryPaths, name: &str) -> Option<PathBuf> {
+    let path = paths.dir.join(exe_name(name));
+    path.is_file().then_some(path)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityStatus {
+    Ok,
+    Missing,
+    /// Contents differ from what this build shipped: modified, truncated or replaced
+    Corrupted,
+    /// No expected hash, e.g. yt-dlp installed by the in-app updater (checked when installed)
+    Unverified,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BinaryIntegrity {
+    pub name: String,
+    pub path: String,
+    pub status: IntegrityStatus,
+}
+
+/// Integrity of each checked file by path, with the size and modification time it was hashed at
+type VerifiedCache = HashMap<PathBuf, (u64, SystemTime, IntegrityStatus)>;
+
+/// Verified hashes by path, size and modification time, so each file is hashed once per change
+static VERIFIED: Mutex<Option<VerifiedCache>> = Mutex::new(None);
+
+fn check_integrity(name: &str, path: &Path, bundled_dir: &Path) -> IntegrityStatus {
+    let Ok(meta) = std::fs::metadata(path) else { return IntegrityStatus::Missing };
+    let expected = BINARY_CHECKSUMS.iter().find(|(binary, _)| *binary == name).map(|(_, hash)| *hash);
+    // Paths may or may not have been canonicalized while resolving
+    let bundled = path.parent().and_then(|dir| dir.canonicalize().ok()) == bundled_dir.canonicalize().ok();
+    let Some(expected) = expected.filter(|_| bundled) else {
+        return IntegrityStatus::Unverified;
+    };
+
+    let stamp = (meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+    let mut cache = VERIFIED.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if let Some((len, modified, status)) = cache.get(path) {
+        if (*len, *modified) == stamp {
+            return *status;
+        }
+    }
+    let status = match crate::checksum::sha256_file(path) {
+        Ok(hash) if hash == expected => IntegrityStatus::Ok,
+        Ok(_) => IntegrityStatus::Corrupted,
+        Err(_) => IntegrityStatus::Missing,
+    };
+    cache.insert(path.to_path_buf(), (stamp.0, stamp.1, status));
+    status
+}
+
+/// Compare each binary with the hash recorded at build time
+pub fn verify_integrity(paths: &BinaryPaths) -> Vec<BinaryIntegrity> {
+    [("yt-dlp", &paths.yt_dlp), ("aria2c", &paths.aria2c), ("ffmpeg", &paths.ffmpeg)]
+        .into_iter()
+        .map(|(name, path)| BinaryIntegrity {
+            name: name.to_string(),
+            path: path.to_string_lossy().to_string(),
+            status: check_integrity(name, path, &paths.dir),
+        })
+        .collect()
+}
+
+/// Hash the binaries in the background at startup and tell the UI about any that are damaged
+pub fn spawn_integrity_check<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(paths) = resolve_paths(&app) else { return };
+        let Ok(report) = tokio::task::spawn_blocking(move || verify_integrity(&paths)).await else { return };
+        let damaged: Vec<_> = report
+            .into_iter()
+            .filter(|binary| matches!(binary.status, IntegrityStatus::Corrupted | IntegrityStatus::Missing))
+            .collect();
+        if damaged.is_empty() {
+            eprintln!("✅ Bundled binaries match their checksums");
+            return;
+        }
+        for binary in &damaged {
+            eprintln!("❌ {} is {:?}: {}", binary.name, binary.status, binary.path);
+        }
+        let _ = app.emit("binary-integrity", damaged);
+    });
+}
+
+/// Integrity of each bundled binary, for the dependencies screen
+#[tauri::command]
+pub async fn get_binary_integrity<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<BinaryIntegrity>, String> {
+    let paths = resolve_paths(&app_handle)?;
+    tokio::task::spawn_blocking(move || verify_integrity(&paths))
+        .await
+        .map_err(|e| format!("Integrity check failed: {}", e))
+}
+
+/// Replace damaged binaries. yt-dlp is re-downloaded from its release; ffmpeg and aria2c come only
+/// with the app, so those need a reinstall.
+#[tauri::command]
+pub async fn repair_binaries<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<BinaryIntegrity>, String> {
+    let report = get_binary_integrity(app_handle.clone()).await?;
+    let damaged = |binary: &BinaryIntegrity| matches!(binary.status, IntegrityStatus::Corrupted | IntegrityStatus::Missing);
+    if report.iter().any(|binary| binary.name == "yt-dlp" && damaged(binary)) {
+        crate::binary_updates::update_yt_dlp(app_handle.clone()).await?;
+    }
+    let report = get_binary_integrity(app_handle).await?;
+    let remaining: Vec<&str> = report.iter().filter(|binary| damaged(binary)).map(|binary| binary.name.as_str()).collect();
+    if !remaining.is_empty() {
+        return Err(format!("{} cannot be repaired in place; please reinstall U-Download", remaining.join(", ")));
+    }
+    Ok(report)
+}
+
+/// Ensure binaries have executable permissions on Unix systems, and refuse to run damaged ones
 pub fn ensure_executable(paths: &BinaryPaths) -> Result<(), String> {
+    if let Some(binary) = verify_integrity(paths)
+        .into_iter()
+        .find(|binary| binary.status == IntegrityStatus::Corrupted)
+    {
+        return Err(format!(
+            "{} at {} is corrupted (its checksum does not match this release). Repair it from the dependencies screen or reinstall U-Download.",
+            binary.name, binary.path
+        ));
+    }
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
@@ -413,18 +563,83 @@ pub fn ensure_executable(paths: &BinaryPaths) -> Result<(), String> {
     Ok(())
 }
 
-/// Add the binary directory to PATH environment variable for a command
-pub fn augment_path_env(cmd: &mut std::process::Command, dir: &Path) {
-    if let Ok(cur) = std::env::var("PATH") {
-        #[cfg(target_os = "windows")]
-        let sep = ";";
-        #[cfg(not(target_os = "windows"))]
-        let sep = ":";
-        let new_path = format!("{}{}{}", dir.display(), sep, cur);
-        cmd.env("PATH", new_path);
-        eprintln!("🔧 Added {} to PATH", dir.display());
-    } else {
-        cmd.env("PATH", dir);
-        eprintln!("🔧 Set PATH to {}", dir.display());
+/// Variables bundled tools may see; anything else in the app's environment (tokens, library
+/// paths, `PYTHON*`) is dropped
+const INHERITED_ENV: &[&str] = &[
+    "HOME", "USER", "USERNAME", "USERPROFILE", "APPDATA", "LOCALAPPDATA", "PROGRAMDATA",
+    "SYSTEMROOT", "SYSTEMDRIVE", "WINDIR", "COMSPEC", "PATHEXT", "TEMP", "TMP", "TMPDIR",
+    "LANG", "LC_ALL", "LC_CTYPE", "TZ", "XDG_CACHE_HOME", "XDG_CONFIG_HOME", "XDG_DATA_HOME",
+    "XDG_RUNTIME_DIR",
+    // --cookies-from-browser reads the Linux keyring over D-Bus
+    "DBUS_SESSION_BUS_ADDRESS",
+    "SSL_CERT_FILE", "SSL_CERT_DIR",
+];
+
+const PROXY_ENV: &[&str] = &[
+    "HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "NO_PROXY", "http_proxy", "https_proxy", "all_proxy", "no_proxy",
+];
+
+struct ChildEnv {
+    work_dir: Option<PathBuf>,
+    keep_proxy: bool,
+}
+
+static CHILD_ENV: RwLock<ChildEnv> = RwLock::new(ChildEnv { work_dir: None, keep_proxy: false });
+
+/// Set up the working directory for tools and apply the proxy setting; run at startup and
+/// whenever settings change
+pub fn configure_child_env<R: Runtime>(app: &AppHandle<R>) {
+    let work_dir = crate::storage::app_data_file(app, "work").and_then(|dir| {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(dir)
+    });
+    if let Err(e) = &work_dir {
+        eprintln!("⚠️  Tools will run in the app's working directory: {}", e);
     }
+    let mut env = CHILD_ENV.write().unwrap();
+    env.work_dir = work_dir.ok();
+    env.keep_proxy = crate::settings::current(app).advanced.use_system_proxy;
+}
+
+/// Whether the tools are handed a system proxy to connect through
+pub fn system_proxy_in_use() -> bool {
+    CHILD_ENV.read().unwrap().keep_proxy
+        && PROXY_ENV
+            .iter()
+            .filter(|name| !name.eq_ignore_ascii_case("NO_PROXY"))
+            .any(|name| std::env::var(name).is_ok_and(|value| !value.trim().is_empty()))
+}
+
+/// A command for a bundled tool (yt-dlp, aria2c, ffmpeg, whisper) with a cleared environment,
+/// a minimal PATH that finds the other bundled tools, and a working directory under app data
+pub fn tool_command(binary: &Path) -> std::process::Command {
+    let mut cmd = std::process::Command::new(binary);
+    cmd.env_clear();
+    let env = CHILD_ENV.read().unwrap();
+    let proxy: &[&str] = if env.keep_proxy { PROXY_ENV } else { &[] };
+    for name in INHERITED_ENV.iter().chain(proxy) {
+        if let Some(value) = std::env::var_os(name) {
+            cmd.env(name, value);
+        }
+    }
+    if let Some(dir) = binary.parent() {
+        augment_path_env(&mut cmd, dir);
+    }
+    if let Some(work_dir) = &env.work_dir {
+        cmd.current_dir(work_dir);
+    }
+    cmd
+}
+
+/// Set PATH to the binary directory followed by the system directories only, so a tool never
+/// picks up a same-named program from the user's PATH
+pub fn augment_path_env(cmd: &mut std::process::Command, dir: &Path) {
+    #[cfg(target_os = "windows")]
+    let path = {
+        let root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+        format!("{};{}\\System32;{}", dir.display(), root, root)
+    };
+    #[cfg(not(target_os = "windows"))]
+    let path = format!("{}:/usr/bin:/bin", dir.display());
+    cmd.env("PATH", path);
 }
@@ -0,0 +1,349 @@
+// Compares the bundled yt-dlp, ffmpeg and aria2c against their latest upstream releases for the
+// dependencies screen, and updates yt-dlp in place on the chosen release channel
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+
+use crate::binary_manager;
+use crate::settings::{self, YtDlpChannel, YtDlpSettings};
+
+const GITHUB_API: &str = "https://api.github.com/repos";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BinaryUpdate {
+    pub name: String,
+    pub installed: Option<String>,
+    pub latest: Option<String>,
+    /// `None` when either version is unknown or not comparable, e.g. ffmpeg git snapshots
+    pub update_available: Option<bool>,
+    pub release_url: Option<String>,
+    /// Why the check could not finish
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GithubRelease {
+    pub tag_name: String,
+    pub html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubTag {
+    name: String,
+}
+
+pub(crate) fn github_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        // GitHub's API rejects requests without a user agent
+        .user_agent(concat!("U-Download/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn github_json<T: serde::de::DeserializeOwned>(client: &reqwest::Client, path: &str) -> Result<T, String> {
+    let response = client
+        .get(format!("{}/{}", GITHUB_API, path))
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach GitHub: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub returned status {}", response.status()));
+    }
+    response.json().await.map_err(|e| format!("Unexpected GitHub response: {}", e))
+}
+
+pub(crate) async fn latest_release(client: &reqwest::Client, repo: &str) -> Result<GithubRelease, String> {
+    github_json(client, &format!("{}/releases/latest", repo)).await
+}
+
+/// Numeric parts of a version such as `2024.08.06`, `n7.0.1` or `release-1.37.0`. ffmpeg git
+/// snapshots (`N-116543-g…`) have no release number to compare.
+fn version_parts(version: &str) -> Option<Vec<u64>> {
+    if version.starts_with("N-") {
+        return None;
+    }
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let numeric: String = version[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let parts: Vec<u64> = numeric.split('.').filter_map(|part| part.parse().ok()).collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+fn is_newer(latest: &str, installed: &str) -> Option<bool> {
+    Some(version_parts(latest)? > version_parts(installed)?)
+}
+
+/// First line of `binary <flag>`, e.g. `ffmpeg version 7.0.1-static https://…`
+fn version_line(binary: &Path, flag: &str) -> Result<String, String> {
+    let output = binary_manager::tool_command(binary)
+        .arg(flag)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", binary.display(), e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .ok_or_else(|| format!("{} printed no version", binary.display()))
+}
+
+pub(crate) fn installed_yt_dlp(binary: &Path) -> Result<String, String> {
+    version_line(binary, "--version")
+}
+
+fn installed_ffmpeg(binary: &Path) -> Result<String, String> {
+    let line = version_line(binary, "-version")?;
+    let version = line.strip_prefix("ffmpeg version ").unwrap_or(&line);
+    Ok(version.split_whitespace().next().unwrap_or(version).to_string())
+}
+
+fn installed_aria2(binary: &Path) -> Result<String, String> {
+    let line = version_line(binary, "--version")?;
+    Ok(line.strip_prefix("aria2 version ").unwrap_or(&line).to_string())
+}
+
+/// ffmpeg publishes releases as `nX.Y[.Z]` tags only, so take the highest of those
+async fn latest_ffmpeg(client: &reqwest::Client) -> Result<GithubRelease, String> {
+    let tags: Vec<GithubTag> = github_json(client, "FFmpeg/FFmpeg/tags?per_page=100").await?;
+    let release = regex::Regex::new(r"^n\d+(\.\d+)+$").unwrap();
+    let tag = tags
+        .into_iter()
+        .filter(|tag| release.is_match(&tag.name))
+        .max_by_key(|tag| version_parts(&tag.name))
+        .ok_or("No ffmpeg release tags found")?;
+    Ok(GithubRelease { html_url: format!("https://ffmpeg.org/download.html#release_{}", &tag.name[1..]), tag_name: tag.name })
+}
+
+fn compare(name: &str, installed: Result<String, String>, latest: Result<GithubRelease, String>) -> BinaryUpdate {
+    let error = match (&installed, &latest) {
+        (Err(e), _) | (_, Err(e)) => Some(e.clone()),
+        _ => None,
+    };
+    let installed = installed.ok();
+    let (latest, release_url) = match latest {
+        Ok(release) => (Some(release.tag_name), Some(release.html_url)),
+        Err(_) => (None, None),
+    };
+    let update_available = match (&latest, &installed) {
+        (Some(latest), Some(installed)) => is_newer(latest, installed),
+        _ => None,
+    };
+    BinaryUpdate { name: name.to_string(), installed, latest, update_available, release_url, error }
+}
+
+/// Installed and latest upstream versions of each bundled binary
+#[tauri::command]
+pub async fn check_binary_updates<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<BinaryUpdate>, String> {
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+    let (yt_dlp, ffmpeg, aria2) = tokio::task::spawn_blocking(move || {
+        (installed_yt_dlp(&paths.yt_dlp), installed_ffmpeg(&paths.ffmpeg), installed_aria2(&paths.aria2c))
+    })
+    .await
+    .map_err(|e| format!("Version check failed: {}", e))?;
+
+    let yt_dlp_settings = settings::current(&app_handle).yt_dlp;
+    let client = github_client()?;
+    let (upstream_yt_dlp, upstream_ffmpeg, upstream_aria2) = tokio::join!(
+        target_yt_dlp_release(&client, &yt_dlp_settings),
+        latest_ffmpeg(&client),
+        latest_release(&client, "aria2/aria2"),
+    );
+
+    let mut yt_dlp = compare("yt-dlp", yt_dlp, upstream_yt_dlp);
+    // A pin is followed both ways, so a newer install than the pin also counts as an update
+    if let (Some(_), Some(installed), Some(latest)) = (&yt_dlp_settings.pinned_version, &yt_dlp.installed, &yt_dlp.latest) {
+        yt_dlp.update_available = Some(installed != latest);
+    }
+    let updates = vec![
+        yt_dlp,
+        compare("ffmpeg", ffmpeg, upstream_ffmpeg),
+        compare("aria2c", aria2, upstream_aria2),
+    ];
+    for update in &updates {
+        if update.update_available == Some(true) {
+            eprintln!(
+                "⬆️  {} {} is available (bundled {})",
+                update.name,
+                update.latest.as_deref().unwrap_or("?"),
+                update.installed.as_deref().unwrap_or("?")
+            );
+        }
+    }
+    Ok(updates)
+}
+
+fn channel_repo(channel: YtDlpChannel) -> &'static str {
+    match channel {
+        YtDlpChannel::Stable => "yt-dlp/yt-dlp",
+        YtDlpChannel::Nightly => "yt-dlp/yt-dlp-nightly-builds",
+    }
+}
+
+/// yt-dlp's standalone build for this platform, as named in its releases
+fn yt_dlp_asset() -> Option<&'static str> {
+    if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some("yt-dlp.exe")
+    } else if cfg!(target_os = "macos") {
+        Some("yt-dlp_macos")
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("yt-dlp_linux")
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Some("yt-dlp_linux_aarch64")
+    } else {
+        None
+    }
+}
+
+/// The pinned release if there is one, else the newest on the channel
+async fn target_yt_dlp_release(client: &reqwest::Client, settings: &YtDlpSettings) -> Result<GithubRelease, String> {
+    let repo = channel_repo(settings.channel);
+    match &settings.pinned_version {
+        Some(version) => github_json(client, &format!("{}/releases/tags/{}", repo, version.trim())).await,
+        None => latest_release(client, repo).await,
+    }
+}
+
+async fn download_bytes(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .timeout(Duration::from_secs(300))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download of {} failed with status {}", url, response.status()));
+    }
+    Ok(response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?
+        .to_vec())
+}
+
+/// Check a download against the release's `SHA2-256SUMS` file
+fn verify_sha256(bytes: &[u8], sums: &str, asset: &str) -> Result<(), String> {
+    let expected = sums
+        .lines()
+        .find_map(|line| {
+            let (hash, name) = line.split_once(char::is_whitespace)?;
+            (name.trim().trim_start_matches('*') == asset).then(|| hash.to_lowercase())
+        })
+        .ok_or_else(|| format!("The release lists no checksum for {}", asset))?;
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("Checksum mismatch for {}: expected {}, got {}", asset, expected, actual))
+    }
+}
+
+fn previous_path(managed: &Path) -> PathBuf {
+    managed.with_file_name(format!("{}.previous", binary_manager::exe_name("yt-dlp")))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct YtDlpUpdate {
+    pub channel: YtDlpChannel,
+    pub version: String,
+    /// Version it replaced, which `rollback_yt_dlp` restores; `None` if nothing changed
+    pub previous: Option<String>,
+}
+
+/// Install the newest yt-dlp on the configured channel, or the pinned version. The binary in use
+/// is kept for `rollback_yt_dlp`.
+#[tauri::command]
+pub async fn update_yt_dlp<R: Runtime>(app_handle: AppHandle<R>) -> Result<YtDlpUpdate, String> {
+    let asset = yt_dlp_asset().ok_or("yt-dlp updates are not available on this platform")?;
+    let settings = settings::current(&app_handle).yt_dlp;
+    let current = binary_manager::resolve_paths(&app_handle)?.yt_dlp;
+    let installed = tokio::task::spawn_blocking(move || installed_yt_dlp(&current))
+        .await
+        .map_err(|e| format!("Version check failed: {}", e))?
+        .ok();
+
+    let client = github_client()?;
+    let release = target_yt_dlp_release(&client, &settings).await?;
+    if installed.as_deref() == Some(release.tag_name.as_str()) {
+        return Ok(YtDlpUpdate { channel: settings.channel, version: release.tag_name, previous: None });
+    }
+
+    let base = format!("https://github.com/{}/releases/download/{}", channel_repo(settings.channel), release.tag_name);
+    eprintln!("⬇️  Downloading yt-dlp {} ({:?})", release.tag_name, settings.channel);
+    let binary = download_bytes(&client, &format!("{}/{}", base, asset)).await?;
+    let sums = download_bytes(&client, &format!("{}/SHA2-256SUMS", base)).await?;
+    verify_sha256(&binary, &String::from_utf8_lossy(&sums), asset)?;
+
+    let managed = binary_manager::managed_yt_dlp(&app_handle)?;
+    let version = tokio::task::spawn_blocking(move || install(&managed, &binary))
+        .await
+        .map_err(|e| format!("yt-dlp install failed: {}", e))??;
+    eprintln!("✅ yt-dlp updated to {} (was {})", version, installed.as_deref().unwrap_or("unknown"));
+    Ok(YtDlpUpdate { channel: settings.channel, version, previous: installed })
+}
+
+/// Write the new binary next to the managed one, check it runs, then swap it in
+fn install(managed: &Path, binary: &[u8]) -> Result<String, String> {
+    let dir = managed.parent().ok_or("Invalid yt-dlp install path")?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let staged = managed.with_file_name(format!("{}.part", binary_manager::exe_name("yt-dlp")));
+    std::fs::write(&staged, binary).map_err(|e| format!("Failed to save yt-dlp: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make yt-dlp executable: {}", e))?;
+    }
+    let version = match installed_yt_dlp(&staged) {
+        Ok(version) => version,
+        Err(e) => {
+            let _ = std::fs::remove_file(&staged);
+            return Err(format!("The downloaded yt-dlp does not run: {}", e));
+        }
+    };
+
+    let previous = previous_path(managed);
+    if managed.is_file() {
+        let _ = std::fs::remove_file(&previous);
+        std::fs::rename(managed, &previous).map_err(|e| format!("Failed to keep the current yt-dlp: {}", e))?;
+    }
+    std::fs::rename(&staged, managed).map_err(|e| format!("Failed to install yt-dlp: {}", e))?;
+    Ok(version)
+}
+
+/// Swap back to the yt-dlp that the last update replaced; running it again undoes the rollback.
+/// Returns the version now in use.
+#[tauri::command]
+pub async fn rollback_yt_dlp<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, String> {
+    let managed = binary_manager::managed_yt_dlp(&app_handle)?;
+    let previous = previous_path(&managed);
+    let rename = |from: &Path, to: &Path| {
+        std::fs::rename(from, to).map_err(|e| format!("Failed to roll back yt-dlp: {}", e))
+    };
+    // No managed copy means the bundled yt-dlp is in use
+    match (managed.is_file(), previous.is_file()) {
+        (true, true) => {
+            let swap = managed.with_file_name(format!("{}.swap", binary_manager::exe_name("yt-dlp")));
+            rename(&managed, &swap)?;
+            rename(&previous, &managed)?;
+            rename(&swap, &previous)?;
+        }
+        (false, true) => rename(&previous, &managed)?,
+        (true, false) => rename(&managed, &previous)?,
+        (false, false) => return Err("There is no earlier yt-dlp to roll back to".to_string()),
+    }
+
+    let current = binary_manager::resolve_paths(&app_handle)?.yt_dlp;
+    let version = tokio::task::spawn_blocking(move || installed_yt_dlp(&current))
+        .await
+        .map_err(|e| format!("Version check failed: {}", e))??;
+    eprintln!("↩️  yt-dlp rolled back to {}", version);
+    Ok(version)
+}
@@ -0,0 +1,146 @@
+// Watch-later list: URLs saved with their metadata and a cached thumbnail, to be downloaded
+// later one by one or all at once as a batch
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::batch::{self, BatchStarted};
+use crate::download_manager::now_millis;
+use crate::storage;
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+const MAX_BOOKMARKS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub id: String,
+    pub url: String,
+    /// Missing when the lookup failed, e.g. while offline
+    pub metadata: Option<crate::VideoMetadata>,
+    /// Local copy of the thumbnail from the thumbnail cache; the UI shows it via `fetch_thumbnail`
+    pub thumbnail_path: Option<String>,
+    pub added_at_ms: u64,
+}
+
+/// Oldest first; loaded once at startup
+pub type BookmarksState = Arc<Mutex<Vec<Bookmark>>>;
+
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Vec<Bookmark> {
+    match storage::app_data_file(app, BOOKMARKS_FILE).and_then(|path| storage::read_json(&path)) {
+        Ok(bookmarks) => bookmarks.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("⚠️  Failed to load bookmarks: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Apply `change` to the list and save it
+fn modify<R: Runtime, T>(app: &AppHandle<R>, change: impl FnOnce(&mut Vec<Bookmark>) -> T) -> Result<T, String> {
+    let (result, snapshot) = {
+        let state = app.state::<BookmarksState>();
+        let mut bookmarks = state.lock().unwrap();
+        let result = change(&mut bookmarks);
+        (result, bookmarks.clone())
+    };
+    let path = storage::app_data_file(app, BOOKMARKS_FILE)?;
+    storage::write_json(&path, &snapshot)?;
+    Ok(result)
+}
+
+/// Save a URL for later. Bookmarking the same URL twice returns the existing bookmark.
+#[tauri::command]
+pub async fn add_bookmark<R: Runtime>(app_handle: AppHandle<R>, url: String) -> Result<Bookmark, String> {
+    let url = url.trim().to_string();
+    match url::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err(format!("Not a valid http(s) URL: {}", url)),
+    }
+    {
+        let state = app_handle.state::<BookmarksState>();
+        let bookmarks = state.lock().unwrap();
+        if let Some(existing) = bookmarks.iter().find(|bookmark| bookmark.url == url) {
+            return Ok(existing.clone());
+        }
+        if bookmarks.len() >= MAX_BOOKMARKS {
+            return Err(format!("You can keep at most {} bookmarks", MAX_BOOKMARKS));
+        }
+    }
+
+    let metadata = match crate::fetch_metadata_json(&app_handle, &url).await {
+        Ok(metadata) => Some(crate::parse_video_metadata(&metadata)),
+        Err(e) => {
+            eprintln!("⚠️  Bookmarking {} without metadata: {}", url, e);
+            None
+        }
+    };
+    let thumbnail_path = match metadata {
+        Some(_) => crate::thumbnails::cached_path(&app_handle, &url, None)
+            .await
+            .ok()
+            .map(|path| path.to_string_lossy().to_string()),
+        None => None,
+    };
+
+    let bookmark = Bookmark {
+        id: format!("bookmark-{}", now_millis()),
+        url,
+        metadata,
+        thumbnail_path,
+        added_at_ms: now_millis(),
+    };
+    let added = bookmark.clone();
+    modify(&app_handle, |bookmarks| {
+        if !bookmarks.iter().any(|existing| existing.url == added.url) {
+            bookmarks.push(added);
+        }
+    })?;
+    eprintln!("🔖 Bookmarked {}", bookmark.url);
+    Ok(bookmark)
+}
+
+#[tauri::command]
+pub async fn get_bookmarks<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<Bookmark>, String> {
+    Ok(app_handle.state::<BookmarksState>().lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn remove_bookmark<R: Runtime>(app_handle: AppHandle<R>, id: String) -> Result<(), String> {
+    let removed = modify(&app_handle, |bookmarks| {
+        let before = bookmarks.len();
+        bookmarks.retain(|bookmark| bookmark.id != id);
+        before != bookmarks.len()
+    })?;
+    if removed {
+        Ok(())
+    } else {
+        Err(format!("Unknown bookmark id: {}", id))
+    }
+}
+
+/// Queue every bookmark as one batch with the given preset. The queued bookmarks are removed
+/// from the list unless `keep` is set.
+#[tauri::command]
+pub async fn download_bookmarks<R: Runtime>(
+    app_handle: AppHandle<R>,
+    preset: String,
+    outputFolder: Option<String>,
+    keep: Option<bool>,
+) -> Result<BatchStarted, String> {
+    let urls: Vec<String> = app_handle
+        .state::<BookmarksState>()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|bookmark| bookmark.url.clone())
+        .collect();
+    if urls.is_empty() {
+        return Err("There are no bookmarks to download".to_string());
+    }
+
+    let started = batch::queue_batch(&app_handle, urls.clone(), &preset, outputFolder)?;
+    if !keep.unwrap_or(false) {
+        modify(&app_handle, |bookmarks| bookmarks.retain(|bookmark| !urls.contains(&bookmark.url)))?;
+    }
+    Ok(started)
+}
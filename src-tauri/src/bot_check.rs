@@ -0,0 +1,230 @@
+// Guided fix for "Sign in to confirm you're not a bot": failed downloads are remembered and the
+// UI is asked to offer a cookie import from a browser or a sign-in window. Once new cookies are
+// in the keychain, every download that hit the check is retried.
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Runtime};
+
+#[cfg(not(target_os = "android"))]
+use crate::secrets;
+
+/// Browsers yt-dlp's `--cookies-from-browser` can read
+pub const BROWSERS: &[&str] = &["brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale"];
+#[cfg(not(target_os = "android"))]
+const LOGIN_LABEL: &str = "login";
+
+/// Downloads that failed the bot check and are retried once cookies arrive
+static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Emitted as `bot-check-required`
+#[derive(Debug, Serialize, Clone)]
+pub struct BotCheckRequired {
+    pub id: String,
+    pub url: String,
+    pub site: String,
+    pub browsers: &'static [&'static str],
+}
+
+/// Remember a download that failed the bot check and start the guided flow
+pub fn on_bot_check<R: Runtime>(app: &AppHandle<R>, id: &str, url: &str) {
+    {
+        let mut pending = PENDING.lock().unwrap();
+        if !pending.iter().any(|pending| pending == id) {
+            pending.push(id.to_string());
+        }
+    }
+    eprintln!("🤖 {} hit a bot check; waiting for cookies", url);
+    let _ = app.emit(
+        "bot-check-required",
+        BotCheckRequired { id: id.to_string(), url: url.to_string(), site: crate::history::site_of(url), browsers: BROWSERS },
+    );
+}
+
+/// Retry every download waiting on the bot check; returns the new download ids
+#[cfg(not(target_os = "android"))]
+async fn retry_pending<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap());
+    let mut retried = Vec::new();
+    for id in pending {
+        match crate::download_manager::retry_download(app.clone(), id.clone()).await {
+            Ok(new_id) => retried.push(new_id),
+            Err(e) => eprintln!("⚠️  Could not retry {} after the bot check: {}", id, e),
+        }
+    }
+    retried
+}
+
+/// Netscape cookie-file lines, keyed by domain and name
+#[cfg(not(target_os = "android"))]
+fn cookie_key(line: &str) -> Option<(String, String)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    (fields.len() >= 7 && !line.starts_with('#')).then(|| (fields[0].trim_start_matches('.').to_string(), fields[5].to_string()))
+}
+
+/// Store `new` in the keychain on top of the saved cookies, replacing cookies with the same
+/// domain and name
+#[cfg(not(target_os = "android"))]
+fn store_cookies(new: &str) -> Result<(), String> {
+    let new_keys: Vec<(String, String)> = new.lines().filter_map(cookie_key).collect();
+    if new_keys.is_empty() {
+        return Err("No cookies were found".to_string());
+    }
+    let existing = secrets::get(secrets::COOKIES)?.unwrap_or_default();
+    let kept = existing
+        .lines()
+        .filter(|line| cookie_key(line).is_some_and(|key| !new_keys.contains(&key)));
+    let fresh = new.lines().filter(|line| cookie_key(line).is_some());
+    let mut merged = String::from("# Netscape HTTP Cookie File\n");
+    for line in kept.chain(fresh) {
+        merged.push_str(line);
+        merged.push('\n');
+    }
+    secrets::set(secrets::COOKIES, &merged)?;
+    eprintln!("🔐 Stored {} cookies in the OS keychain", new_keys.len());
+    Ok(())
+}
+
+/// Copy cookies out of a browser profile with yt-dlp, store them and retry the downloads that
+/// hit the bot check. The browser may need to be closed first (Chrome locks its cookie store).
+#[tauri::command]
+pub async fn import_browser_cookies<R: Runtime>(
+    app_handle: AppHandle<R>,
+    browser: String,
+    profile: Option<String>,
+) -> Result<Vec<String>, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, browser, profile);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let browser = browser.trim().to_lowercase();
+        if !BROWSERS.contains(&browser.as_str()) {
+            return Err(format!("Unsupported browser: {} (expected one of {})", browser, BROWSERS.join(", ")));
+        }
+        let spec = match profile.filter(|profile| !profile.trim().is_empty()) {
+            Some(profile) => format!("{}:{}", browser, profile.trim()),
+            None => browser,
+        };
+
+        let paths = crate::binary_manager::resolve_paths(&app_handle)?;
+        crate::binary_manager::ensure_executable(&paths)?;
+        let export = secrets::TempSecretFile::write("browser-cookies", "")?;
+        // Without a URL yt-dlp exits with a usage error, but only after writing the cookie file
+        let output = tokio::process::Command::from(crate::binary_manager::tool_command(&paths.yt_dlp))
+            .arg("--ignore-config")
+            .arg("--cookies-from-browser")
+            .arg(&spec)
+            .arg("--cookies")
+            .arg(&export.path)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run yt-dlp: {}", e))?;
+        let cookies = std::fs::read_to_string(&export.path).unwrap_or_default();
+        if cookies.lines().filter_map(cookie_key).next().is_none() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .rev()
+                .find_map(|line| line.trim().strip_prefix("ERROR:"))
+                .unwrap_or("no cookies found");
+            return Err(format!("Could not read cookies from {}: {}", spec, reason.trim()));
+        }
+        store_cookies(&cookies)?;
+        Ok(retry_pending(&app_handle).await)
+    }
+}
+
+/// Page to sign in on, and the sites whose cookies to keep afterwards
+#[cfg(not(target_os = "android"))]
+fn login_pages(site: &str) -> (String, Vec<String>) {
+    let site = site.trim().trim_start_matches("www.").to_lowercase();
+    if site == "youtube.com" || site == "youtu.be" || site.ends_with(".youtube.com") {
+        return (
+            "https://accounts.google.com/ServiceLogin?service=youtube&continue=https%3A%2F%2Fwww.youtube.com%2F".to_string(),
+            vec!["https://www.youtube.com/".to_string(), "https://accounts.google.com/".to_string()],
+        );
+    }
+    let home = format!("https://{}/", site);
+    (home.clone(), vec![home])
+}
+
+/// Open a window on the site's sign-in page. It loads remote content, so it gets no
+/// capabilities and cannot call the app.
+#[tauri::command]
+pub async fn open_login_window<R: Runtime>(app_handle: AppHandle<R>, site: String) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, site);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        use tauri::Manager;
+        let (page, _) = login_pages(&site);
+        let url = url::Url::parse(&page).map_err(|e| format!("Invalid sign-in page: {}", e))?;
+        if let Some(window) = app_handle.get_webview_window(LOGIN_LABEL) {
+            window.navigate(url).map_err(|e| format!("Failed to open the sign-in page: {}", e))?;
+            let _ = window.set_focus();
+            return Ok(());
+        }
+        tauri::WebviewWindowBuilder::new(&app_handle, LOGIN_LABEL, tauri::WebviewUrl::External(url))
+            .title(crate::i18n::t("login-window-title"))
+            .inner_size(480.0, 720.0)
+            .build()
+            .map_err(|e| format!("Failed to open the sign-in window: {}", e))?;
+        Ok(())
+    }
+}
+
+/// One Netscape cookie-file line for a cookie from the sign-in window
+#[cfg(not(target_os = "android"))]
+fn netscape_line(cookie: &tauri::webview::Cookie<'_>, host: &str) -> String {
+    let (domain, subdomains) = match cookie.domain() {
+        Some(domain) => (format!(".{}", domain.trim_start_matches('.')), "TRUE"),
+        None => (host.to_string(), "FALSE"),
+    };
+    let expires = cookie.expires_datetime().map_or(0, |at| at.unix_timestamp().max(0));
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        domain,
+        subdomains,
+        cookie.path().unwrap_or("/"),
+        if cookie.secure().unwrap_or(false) { "TRUE" } else { "FALSE" },
+        expires,
+        cookie.name(),
+        cookie.value()
+    )
+}
+
+/// Take the cookies from the sign-in window once the user has signed in, close it and retry
+/// the downloads that hit the bot check
+#[tauri::command]
+pub async fn finish_login<R: Runtime>(app_handle: AppHandle<R>, site: String) -> Result<Vec<String>, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, site);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        use tauri::Manager;
+        let window = app_handle
+            .get_webview_window(LOGIN_LABEL)
+            .ok_or("The sign-in window is not open")?;
+        let (_, cookie_pages) = login_pages(&site);
+        let mut lines = Vec::new();
+        for page in cookie_pages {
+            let url = url::Url::parse(&page).map_err(|e| format!("Invalid page {}: {}", page, e))?;
+            let host = url.host_str().unwrap_or_default().to_string();
+            let cookies = window
+                .cookies_for_url(url)
+                .map_err(|e| format!("Failed to read cookies from the sign-in window: {}", e))?;
+            lines.extend(cookies.iter().map(|cookie| netscape_line(cookie, &host)));
+        }
+        store_cookies(&lines.join("\n"))?;
+        let _ = window.close();
+        Ok(retry_pending(&app_handle).await)
+    }
+}
@@ -0,0 +1,77 @@
+// Music detection: downloads queued without a chosen format switch to audio extraction, and to
+// the music folder, when yt-dlp's metadata says the video is a song
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::{DownloadManagerState, DownloadOptions, DownloadRequest};
+use crate::settings::{self, MediaCategory};
+use crate::{formats, storage};
+
+/// Sites that only host music
+const MUSIC_EXTRACTORS: &[&str] = &["Bandcamp", "Soundcloud", "Mixcloud"];
+
+/// Opt a download whose format the user did not pick into detection, unless its options
+/// already decide
+pub fn mark_unchosen<R: Runtime>(app: &AppHandle<R>, options: &mut DownloadOptions) {
+    if options.classify.is_none() && settings::current(app).classify.enabled {
+        options.classify = Some(true);
+    }
+}
+
+/// Songs have track/artist/album fields, sit in the Music category or come from a music site
+pub fn is_music(metadata: &serde_json::Value) -> bool {
+    let has = |key: &str| metadata[key].as_str().is_some_and(|value| !value.trim().is_empty());
+    if has("track") || has("album") || has("artist") {
+        return true;
+    }
+    let in_music_category = metadata["categories"]
+        .as_array()
+        .is_some_and(|categories| categories.iter().any(|c| c.as_str() == Some("Music")));
+    let extractor = metadata["extractor_key"].as_str().unwrap_or_default();
+    let on_youtube_music = metadata["webpage_url"].as_str().is_some_and(|url| url.contains("://music.youtube.com/"));
+    in_music_category || on_youtube_music || MUSIC_EXTRACTORS.contains(&extractor)
+}
+
+/// Switch a video download to audio when it is music. The music folder is used only if the
+/// download was going to the default video folder, so a folder the user picked is kept.
+pub async fn apply<R: Runtime>(app: &AppHandle<R>, id: &str, mut request: DownloadRequest) -> DownloadRequest {
+    if request.options.classify != Some(true)
+        || formats::is_audio(&request.download_type)
+        || matches!(request.download_type.as_str(), "direct" | "metalink")
+    {
+        return request;
+    }
+    let metadata = match crate::fetch_metadata_json(app, &request.url).await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("⚠️  Could not check whether {} is music: {}", request.url, e);
+            return request;
+        }
+    };
+    if metadata["_type"].as_str() == Some("playlist") || !is_music(&metadata) {
+        return request;
+    }
+
+    let current = settings::current(app);
+    let default_folder = current
+        .resolve_output_folder(None, &request.download_type, None)
+        .and_then(|folder| storage::validate_output_folder(app, &folder));
+    request.download_type = current.classify.audio_format.clone();
+    if default_folder.as_deref() == Ok(request.output_folder.as_str()) {
+        let music_folder = current
+            .resolve_output_folder(None, &request.download_type, Some(MediaCategory::Music))
+            .and_then(|folder| storage::validate_output_folder(app, &folder));
+        match music_folder {
+            Ok(folder) => request.output_folder = folder,
+            Err(e) => eprintln!("⚠️  Keeping the video folder for {}: {}", request.url, e),
+        }
+    }
+    eprintln!("🎵 {} looks like music, downloading it as {}", request.url, request.download_type);
+
+    // Keep the decision so restarts and retries of this job skip the lookup
+    if let Some(job) = app.state::<DownloadManagerState>().lock().unwrap().job_mut(id) {
+        job.request.download_type = request.download_type.clone();
+        job.request.output_folder = request.output_folder.clone();
+        job.request.options.classify = Some(false);
+    }
+    request
+}
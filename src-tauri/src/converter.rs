@@ -0,0 +1,150 @@
+// Local file conversion with the bundled ffmpeg: pick a file, a target preset and an output folder
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::{binary_manager, ffmpeg};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConversionPreset {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub extension: &'static str,
+    /// Whether the preset produces audio only (video streams are dropped)
+    pub audio_only: bool,
+    /// Whether an audio bitrate can be chosen
+    pub bitrate_adjustable: bool,
+}
+
+const PRESETS: &[ConversionPreset] = &[
+    ConversionPreset { id: "mp4_h264", label: "MP4 (H.264 + AAC)", extension: "mp4", audio_only: false, bitrate_adjustable: true },
+    ConversionPreset { id: "mp4_h265", label: "MP4 (H.265 + AAC)", extension: "mp4", audio_only: false, bitrate_adjustable: true },
+    ConversionPreset { id: "webm_vp9", label: "WebM (VP9 + Opus)", extension: "webm", audio_only: false, bitrate_adjustable: true },
+    ConversionPreset { id: "mkv_remux", label: "MKV (copy streams)", extension: "mkv", audio_only: false, bitrate_adjustable: false },
+    ConversionPreset { id: "mp3", label: "MP3", extension: "mp3", audio_only: true, bitrate_adjustable: true },
+    ConversionPreset { id: "m4a", label: "M4A (AAC)", extension: "m4a", audio_only: true, bitrate_adjustable: true },
+    ConversionPreset { id: "opus", label: "Opus", extension: "opus", audio_only: true, bitrate_adjustable: true },
+    ConversionPreset { id: "flac", label: "FLAC (lossless)", extension: "flac", audio_only: true, bitrate_adjustable: false },
+    ConversionPreset { id: "wav", label: "WAV (uncompressed)", extension: "wav", audio_only: true, bitrate_adjustable: false },
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConversionProgress {
+    pub input: String,
+    pub output: String,
+    pub percentage: f64,
+}
+
+fn find_preset(id: &str) -> Result<&'static ConversionPreset, String> {
+    PRESETS
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown conversion preset: {}", id))
+}
+
+/// Audio bitrate in kbit/s from `192`, `192k` or `192K`
+fn parse_bitrate(bitrate: &str) -> Result<u32, String> {
+    bitrate
+        .trim()
+        .trim_end_matches(['k', 'K'])
+        .parse::<u32>()
+        .ok()
+        .filter(|kbps| (32..=512).contains(kbps))
+        .ok_or_else(|| format!("Bitrate must be between 32k and 512k (got {})", bitrate))
+}
+
+fn codec_args(preset: &ConversionPreset, audio_kbps: u32) -> Vec<String> {
+    let bitrate = format!("{}k", audio_kbps);
+    let args: Vec<&str> = match preset.id {
+        "mp4_h264" => vec!["-c:v", "libx264", "-preset", "medium", "-crf", "23", "-pix_fmt", "yuv420p", "-c:a", "aac", "-b:a", &bitrate, "-movflags", "+faststart"],
+        "mp4_h265" => vec!["-c:v", "libx265", "-preset", "medium", "-crf", "28", "-tag:v", "hvc1", "-pix_fmt", "yuv420p", "-c:a", "aac", "-b:a", &bitrate, "-movflags", "+faststart"],
+        "webm_vp9" => vec!["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0", "-row-mt", "1", "-c:a", "libopus", "-b:a", &bitrate],
+        "mkv_remux" => vec!["-map", "0", "-c", "copy"],
+        "mp3" => vec!["-vn", "-c:a", "libmp3lame", "-b:a", &bitrate],
+        "m4a" => vec!["-vn", "-c:a", "aac", "-b:a", &bitrate],
+        "opus" => vec!["-vn", "-c:a", "libopus", "-b:a", &bitrate],
+        "flac" => vec!["-vn", "-c:a", "flac"],
+        _ => vec!["-vn", "-c:a", "pcm_s16le"],
+    };
+    args.into_iter().map(str::to_string).collect()
+}
+
+/// `<folder>/<stem>.<ext>`, numbered so neither the input nor an existing file is overwritten
+fn output_path(input: &Path, folder: &Path, ext: &str) -> PathBuf {
+    let stem = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let mut candidate = folder.join(format!("{}.{}", stem, ext));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = folder.join(format!("{} ({}).{}", stem, n, ext));
+        n += 1;
+    }
+    candidate
+}
+
+#[tauri::command]
+pub async fn list_conversion_presets() -> Result<Vec<ConversionPreset>, String> {
+    Ok(PRESETS.to_vec())
+}
+
+/// Convert a local media file with a preset; returns the path of the new file
+#[tauri::command]
+pub async fn convert_file<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    preset: String,
+    outputFolder: Option<String>,
+    bitrate: Option<String>,
+) -> Result<String, String> {
+    let input = PathBuf::from(&path);
+    if !input.is_file() {
+        return Err(format!("File not found: {}", input.display()));
+    }
+    let preset = find_preset(&preset)?;
+    let audio_kbps = match bitrate.as_deref() {
+        Some(bitrate) if preset.bitrate_adjustable => parse_bitrate(bitrate)?,
+        Some(_) => return Err(format!("{} does not take a bitrate", preset.label)),
+        None => 192,
+    };
+
+    let folder = match outputFolder {
+        Some(folder) => folder,
+        None => input.parent().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    let folder = PathBuf::from(crate::storage::validate_output_folder(&app_handle, &folder)?);
+
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+
+    let output = output_path(&input, &folder, preset.extension);
+    let mut cmd = binary_manager::tool_command(&paths.ffmpeg);
+    cmd.arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(&input)
+        .args(codec_args(preset, audio_kbps))
+        .arg(&output);
+
+    let input_str = input.to_string_lossy().to_string();
+    let output_str = output.to_string_lossy().to_string();
+    eprintln!("🔄 Converting {} with {}", input_str, preset.id);
+    let (app, ffmpeg_path, progress_input, progress_output) =
+        (app_handle.clone(), paths.ffmpeg.clone(), input_str.clone(), output_str.clone());
+    let result = tokio::task::spawn_blocking(move || {
+        let duration = ffmpeg::probe_duration(&ffmpeg_path, &input);
+        ffmpeg::run_with_progress(cmd, duration, |percentage| {
+            let _ = app.emit(
+                "conversion-progress",
+                ConversionProgress { input: progress_input.clone(), output: progress_output.clone(), percentage },
+            );
+        })
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e))?;
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&output);
+        return Err(e);
+    }
+
+    eprintln!("✅ Converted to {}", output_str);
+    Ok(output_str)
+}
@@ -0,0 +1,223 @@
+// Crash reports: a panic hook writes the panic, a backtrace and the last few app actions to
+// the app data directory, and the next launch offers to open or submit them
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::storage;
+
+const CRASH_DIR: &str = "crashes";
+const MAX_BREADCRUMBS: usize = 30;
+/// Older reports are deleted
+const MAX_REPORTS: usize = 10;
+/// Creation time of the newest report the user has already been offered
+const SEEN_MARKER: &str = ".seen";
+/// GitHub rejects issue URLs much longer than this
+const MAX_ISSUE_BODY_CHARS: usize = 6000;
+
+static REPORT_DIR: OnceLock<PathBuf> = OnceLock::new();
+static BREADCRUMBS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Note an action for the next crash report. Keep these free of personal data: hosts, not URLs.
+pub fn breadcrumb(action: impl Into<String>) {
+    let line = format!("{} {}", crate::download_manager::now_millis(), action.into());
+    if let Ok(mut crumbs) = BREADCRUMBS.lock() {
+        if crumbs.len() == MAX_BREADCRUMBS {
+            crumbs.pop_front();
+        }
+        crumbs.push_back(line);
+    }
+}
+
+/// Host of a URL for breadcrumbs, so reports never carry video ids or tokens
+pub fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown host".to_string())
+}
+
+fn report_dir() -> PathBuf {
+    REPORT_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("u-download-crashes"))
+}
+
+fn render(kind: &str, message: &str, location: Option<String>) -> String {
+    let thread = std::thread::current();
+    let crumbs = BREADCRUMBS
+        .lock()
+        .map(|crumbs| crumbs.iter().cloned().collect::<Vec<_>>().join("\n"))
+        .unwrap_or_default();
+    format!(
+        "U-Download {} crash report\n\nKind: {}\nMessage: {}\nLocation: {}\nThread: {}\nPlatform: {} {} ({})\nTime: {}\n\nLast actions:\n{}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        kind,
+        message,
+        location.unwrap_or_else(|| "unknown".to_string()),
+        thread.name().unwrap_or("unnamed"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::FAMILY,
+        crate::download_manager::now_millis(),
+        if crumbs.is_empty() { "(none)".to_string() } else { crumbs },
+        std::backtrace::Backtrace::force_capture(),
+    )
+}
+
+fn write_report(report: &str) -> Option<PathBuf> {
+    let dir = report_dir();
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("crash-{}.txt", crate::download_manager::now_millis()));
+    std::fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+/// Install the panic hook. Reports go to the temp dir until `set_report_dir` runs in setup.
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        match write_report(&render("panic", &message, location)) {
+            Some(path) => eprintln!("💥 Crash report written to {}", path.display()),
+            None => eprintln!("💥 Failed to write crash report"),
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn set_report_dir<R: Runtime>(app: &AppHandle<R>) {
+    match storage::app_data_file(app, CRASH_DIR) {
+        Ok(dir) => {
+            let _ = REPORT_DIR.set(dir);
+        }
+        Err(e) => eprintln!("⚠️  Crash reports will go to the temp directory: {}", e),
+    }
+}
+
+/// Report an error that stops the app without a panic, such as Tauri failing to start
+pub fn report_fatal(error: &dyn std::fmt::Display) {
+    if let Some(path) = write_report(&render("fatal error", &error.to_string(), None)) {
+        eprintln!("💥 Crash report written to {}", path.display());
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CrashReport {
+    pub path: String,
+    pub created_ms: u64,
+    /// First lines of the report, for the prompt
+    pub summary: String,
+}
+
+fn reports_in(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let created_ms = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix("crash-")?
+                .parse()
+                .unwrap_or(0);
+            Some(CrashReport {
+                path: path.to_string_lossy().to_string(),
+                created_ms,
+                summary: content.lines().take(4).collect::<Vec<_>>().join("\n"),
+            })
+        })
+        .collect();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.created_ms));
+    for old in reports.drain(MAX_REPORTS.min(reports.len())..) {
+        let _ = std::fs::remove_file(&old.path);
+    }
+    reports
+}
+
+fn last_seen(dir: &Path) -> u64 {
+    std::fs::read_to_string(dir.join(SEEN_MARKER))
+        .ok()
+        .and_then(|seen| seen.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Only files in the crash directory may be opened, submitted or dismissed
+fn checked_report(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    let in_crash_dir = path.parent().is_some_and(|parent| parent == report_dir());
+    if in_crash_dir && path.is_file() {
+        Ok(path)
+    } else {
+        Err(format!("{} is not a crash report", path.display()))
+    }
+}
+
+/// Crash reports from earlier runs that the user has not been offered yet
+#[tauri::command]
+pub async fn get_crash_reports() -> Result<Vec<CrashReport>, String> {
+    let dir = report_dir();
+    let seen = last_seen(&dir);
+    Ok(reports_in(&dir).into_iter().filter(|report| report.created_ms > seen).collect())
+}
+
+#[tauri::command]
+pub async fn open_crash_report<R: Runtime>(app: AppHandle<R>, path: String) -> Result<(), String> {
+    let path = checked_report(&path)?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open crash report: {}", e))
+}
+
+/// Open a prefilled GitHub issue with the report for the user to review and send
+#[tauri::command]
+pub async fn submit_crash_report<R: Runtime>(app: AppHandle<R>, path: String) -> Result<(), String> {
+    let path = checked_report(&path)?;
+    let report = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read crash report: {}", e))?;
+    let mut body: String = report.chars().take(MAX_ISSUE_BODY_CHARS).collect();
+    if body.len() < report.len() {
+        body.push_str("\n…(truncated; please attach the full report file)");
+    }
+    let title = report
+        .lines()
+        .find_map(|line| line.strip_prefix("Message: "))
+        .map(|message| format!("Crash: {}", message.chars().take(80).collect::<String>()))
+        .unwrap_or_else(|| "Crash report".to_string());
+    let url = url::Url::parse_with_params(
+        &format!("{}/issues/new", env!("CARGO_PKG_REPOSITORY")),
+        &[("title", title), ("body", format!("```\n{}\n```", body))],
+    )
+    .map_err(|e| format!("Failed to build issue URL: {}", e))?;
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {}", e))
+}
+
+/// Stop offering a report on launch. The file stays, since it may still be open in a viewer.
+#[tauri::command]
+pub async fn dismiss_crash_report(path: String) -> Result<(), String> {
+    let path = checked_report(&path)?;
+    let dir = report_dir();
+    let created_ms = reports_in(&dir)
+        .into_iter()
+        .find(|report| Path::new(&report.path) == path)
+        .map_or(0, |report| report.created_ms);
+    if created_ms > last_seen(&dir) {
+        std::fs::write(dir.join(SEEN_MARKER), created_ms.to_string())
+            .map_err(|e| format!("Failed to update crash reports: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,1833 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::history::HistoryEntry;
+use crate::{calculate_eta, format_speed, DownloadProgress, ProgressState};
+
+/// Number of speed samples kept per download (one per second, so two minutes)
+const SPEED_HISTORY_LEN: usize = 120;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const QUEUE_FILE: &str = "queue.json";
+/// Seconds between queue snapshots while downloads are running
+const PERSIST_EVERY_TICKS: u32 = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpeedSample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub bytes_per_sec: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DownloadRequest {
+    pub url: String,
+    pub download_type: String,
+    pub quality: String,
+    pub output_folder: String,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    /// Output file name for direct downloads; yt-dlp downloads name files from the title
+    #[serde(default)]
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub options: DownloadOptions,
+}
+
+impl DownloadRequest {
+    /// Small downloads that get the fast lane's slots: audio, unless the options say otherwise
+    pub fn is_fast_lane(&self) -> bool {
+        self.options.fast_lane.unwrap_or_else(|| crate::formats::is_audio(&self.download_type))
+    }
+}
+
+/// Subtitle download settings passed through to yt-dlp
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SubtitleOptions {
+    /// Language codes such as `en` or `pt-BR`; empty means `all`
+    pub languages: Vec<String>,
+    /// Also accept auto-generated captions when no uploaded subtitles exist
+    pub auto_generated: bool,
+    /// Embed into the video instead of writing side-car files
+    pub embed: bool,
+    /// Render the first available track into the picture, for players without subtitle support
+    pub burn_in: Option<crate::ffmpeg::BurnInStyle>,
+}
+
+impl SubtitleOptions {
+    /// The side-car yt-dlp wrote for `video` that should be burned in: the first requested
+    /// language that exists, or any track when no languages were requested
+    pub fn burn_in_source(&self, video: &std::path::Path) -> Option<std::path::PathBuf> {
+        let stem = video.file_stem()?.to_string_lossy().to_string();
+        let mut tracks: Vec<(String, std::path::PathBuf)> = std::fs::read_dir(video.parent()?)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // `<stem>.<lang>.srt` / `.vtt` / `.ass`
+                let rest = name.strip_prefix(&stem)?.strip_prefix('.')?;
+                let (language, ext) = rest.rsplit_once('.')?;
+                matches!(ext, "srt" | "vtt" | "ass").then(|| (language.to_string(), entry.path()))
+            })
+            .collect();
+        tracks.sort();
+        if self.languages.is_empty() {
+            return tracks.into_iter().next().map(|(_, path)| path);
+        }
+        self.languages.iter().find_map(|wanted| {
+            tracks.iter().find(|(language, _)| language.eq_ignore_ascii_case(wanted)).map(|(_, path)| path.clone())
+        })
+    }
+}
+
+/// Extra yt-dlp options a download can carry, usually filled in from a preset
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DownloadOptions {
+    /// yt-dlp output template relative to the output folder, e.g. `%(uploader)s/%(title)s.%(ext)s`
+    pub output_template: Option<String>,
+    pub subtitles: Option<SubtitleOptions>,
+    pub embed_thumbnail: bool,
+    pub embed_metadata: bool,
+    pub embed_chapters: bool,
+    /// Playlist entries to fetch, in `--playlist-items` syntax (`1,3,5-7`); all when unset
+    pub playlist_items: Option<String>,
+    /// Start playlist file names with the entry's position, e.g. `03 - Title.mp4`
+    pub playlist_numbered: bool,
+    /// Download playlist entries last to first
+    pub playlist_reverse: bool,
+    /// Put playlist entries in a folder named after the playlist
+    pub playlist_folder: bool,
+    /// Only videos uploaded on or after this date: `YYYYMMDD` or relative like `now-2weeks`
+    pub date_after: Option<String>,
+    pub date_before: Option<String>,
+    /// Stop after this many videos have been downloaded
+    pub max_downloads: Option<u32>,
+    /// Case-insensitive text the video title must contain
+    pub title_contains: Option<String>,
+    /// Raw yt-dlp `--match-filter` expression, combined with `title_contains`
+    pub match_filter: Option<String>,
+    /// Overrides the codec preference from settings
+    pub codec: Option<crate::formats::CodecPreference>,
+    /// Overrides the merge container from settings
+    pub container: Option<crate::formats::Container>,
+    /// Overrides the audio bitrate/VBR level from settings (`192K`, `0`)
+    pub audio_quality: Option<String>,
+    /// Overrides the loudness normalization toggle from settings
+    pub normalize_loudness: Option<bool>,
+    /// Overrides the re-encode preset from settings
+    pub transcode: Option<crate::ffmpeg::TranscodePreset>,
+    /// Fake an X-Forwarded-For header to get past geographic restrictions
+    pub geo_bypass: bool,
+    /// Two-letter ISO country code to pretend to be in; implies `geo_bypass`
+    pub geo_bypass_country: Option<String>,
+    /// Proxy for this download only, e.g. `socks5://127.0.0.1:1080`
+    pub proxy: Option<String>,
+    /// Additional yt-dlp flags, checked by `extra_args::parse`
+    pub extra_args: Option<String>,
+    /// `--extractor-args` on top of the ones in settings, e.g. `youtube:player_client=ios`
+    pub extractor_args: Vec<String>,
+    /// Overrides the `.nfo` sidecar toggle from settings
+    pub write_nfo: Option<bool>,
+    /// Pick up `.part` files left by an earlier attempt instead of starting over
+    pub continue_partial: bool,
+    /// Use yt-dlp's own downloader for every protocol, ignoring the downloader settings
+    pub native_downloader: bool,
+    /// YouTube player client, over the one in settings; set by the rotation after a 403
+    pub player_client: Option<String>,
+    /// Bandwidth cap in bytes per second; unlimited when unset or 0
+    pub speed_limit: Option<u64>,
+    /// Run at full speed even on a metered connection
+    pub allow_metered: bool,
+    /// Overrides `timeouts.overall_minutes` from settings; 0 disables it
+    pub timeout_minutes: Option<u32>,
+    /// Overrides `timeouts.stall_seconds` from settings; 0 disables it
+    pub stall_timeout_secs: Option<u32>,
+    /// Overrides `timeouts.retries`; counts down as the download is retried
+    pub timeout_retries: Option<u32>,
+    /// Write a whisper.cpp transcript next to each downloaded file
+    pub transcribe: Option<crate::transcribe::TranscriptionOptions>,
+    /// Switch to audio when the video turns out to be music; set for downloads queued without a
+    /// chosen format when detection is on in settings
+    pub classify: Option<bool>,
+    /// Run in the fast lane's own slots; audio downloads do unless this says otherwise
+    pub fast_lane: Option<bool>,
+    /// User labels such as `lecture`, kept in history and used for folder routing
+    pub tags: Vec<String>,
+    /// History id of the failed download this one retries, linking the attempts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
+}
+
+fn validate_proxy(proxy: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(proxy.trim()).map_err(|e| format!("Invalid proxy URL \"{}\": {}", proxy, e))?;
+    if !matches!(parsed.scheme(), "http" | "https" | "socks4" | "socks4a" | "socks5" | "socks5h") {
+        return Err(format!("Unsupported proxy scheme: {}", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("Proxy URL has no host: {}", proxy));
+    }
+    Ok(())
+}
+
+/// yt-dlp date: `YYYYMMDD`, or `now`/`today`/`yesterday` with an optional `-N<unit>` offset
+fn validate_date(date: &str) -> Result<(), String> {
+    let date = date.trim();
+    if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(());
+    }
+    let relative = regex::Regex::new(r"^(now|today|yesterday)([+-]\d+(day|week|month|year)s?)?$").unwrap();
+    if relative.is_match(date) {
+        Ok(())
+    } else {
+        Err(format!("Invalid date \"{}\"; use YYYYMMDD or a relative date like now-1week", date))
+    }
+}
+
+impl DownloadOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        for tag in &self.tags {
+            crate::tags::normalize(tag)?;
+        }
+        if let Some(template) = &self.output_template {
+            let path = std::path::Path::new(template);
+            if template.trim().is_empty() || path.is_absolute() {
+                return Err("Output template must be a relative path".to_string());
+            }
+            if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+                return Err("Output template cannot leave the output folder".to_string());
+            }
+            if !template.contains("%(ext)s") {
+                return Err("Output template must end with %(ext)s".to_string());
+            }
+        }
+        if self.stall_timeout_secs.is_some_and(|secs| secs != 0 && secs < 30) {
+            return Err("The no-progress timeout must be at least 30 seconds".to_string());
+        }
+        if self.timeout_retries.is_some_and(|retries| retries > crate::settings::MAX_TIMEOUT_RETRIES) {
+            return Err(format!("Timeout retries cannot exceed {}", crate::settings::MAX_TIMEOUT_RETRIES));
+        }
+        if let Some(style) = self.subtitles.as_ref().and_then(|subs| subs.burn_in.as_ref()) {
+            style.validate()?;
+        }
+        if let Some(items) = &self.playlist_items {
+            crate::playlist::validate_items(items)?;
+        }
+        for date in [&self.date_after, &self.date_before].into_iter().flatten() {
+            validate_date(date)?;
+        }
+        if let Some(quality) = &self.audio_quality {
+            crate::formats::validate_audio_quality(quality)?;
+        }
+        if self.max_downloads == Some(0) {
+            return Err("Max downloads must be at least 1".to_string());
+        }
+        if let Some(country) = &self.geo_bypass_country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(format!("Country must be a two-letter code like US or DE (got \"{}\")", country));
+            }
+        }
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            validate_proxy(proxy)?;
+        }
+        if let Some(extra) = &self.extra_args {
+            crate::extra_args::parse(extra)?;
+        }
+        if let Some(client) = &self.player_client {
+            crate::youtube::validate_player_client(client)?;
+        }
+        for args in &self.extractor_args {
+            crate::settings::validate_extractor_args(args)?;
+        }
+        if let Some(transcription) = &self.transcribe {
+            transcription.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Output path pattern for yt-dlp; trimmed downloads get a `_temp` marker for the trim step.
+    /// The playlist options only make sense for playlists: other videos would be numbered and
+    /// filed under `NA`.
+    pub fn output_pattern(&self, output_folder: &str, temp: bool) -> String {
+        let mut template = self.output_template.clone().unwrap_or_else(|| "%(title)s.%(ext)s".to_string());
+        if self.playlist_numbered {
+            // yt-dlp pads the index to the playlist's length, so names sort in playlist order
+            let name_start = template.rfind(['/', '\\']).map_or(0, |i| i + 1);
+            template.insert_str(name_start, "%(playlist_index)s - ");
+        }
+        if self.playlist_folder {
+            template.insert_str(0, "%(playlist_title,playlist)s/");
+        }
+        let template = if temp {
+            format!("{}_temp.%(ext)s", template.trim_end_matches(".%(ext)s"))
+        } else {
+            template
+        };
+        format!("{}/{}", output_folder, template)
+    }
+
+    /// Subtitle and post-processing flags for yt-dlp
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(subs) = &self.subtitles {
+            args.push("--write-subs".to_string());
+            if subs.auto_generated {
+                args.push("--write-auto-subs".to_string());
+            }
+            args.push("--sub-langs".to_string());
+            args.push(if subs.languages.is_empty() { "all".to_string() } else { subs.languages.join(",") });
+            if subs.burn_in.is_some() {
+                // Burned in after download; SRT is the format ffmpeg's subtitles filter handles best
+                args.push("--convert-subs".to_string());
+                args.push("srt".to_string());
+            } else if subs.embed {
+                args.push("--embed-subs".to_string());
+            }
+        }
+        if self.continue_partial {
+            args.push("--continue".to_string());
+        }
+        if self.embed_thumbnail {
+            args.push("--embed-thumbnail".to_string());
+        }
+        if self.embed_metadata {
+            args.push("--embed-metadata".to_string());
+        }
+        if self.embed_chapters {
+            args.push("--embed-chapters".to_string());
+        }
+        if let Some(items) = self.playlist_items.as_deref().filter(|i| !i.trim().is_empty()) {
+            args.push("--playlist-items".to_string());
+            args.push(items.replace(' ', ""));
+        }
+        if self.playlist_reverse {
+            args.push("--playlist-reverse".to_string());
+        }
+        if let Some(date) = &self.date_after {
+            args.push("--dateafter".to_string());
+            args.push(date.trim().to_string());
+        }
+        if let Some(date) = &self.date_before {
+            args.push("--datebefore".to_string());
+            args.push(date.trim().to_string());
+        }
+        if let Some(max) = self.max_downloads {
+            args.push("--max-downloads".to_string());
+            args.push(max.to_string());
+        }
+        let mut filters = Vec::new();
+        if let Some(text) = self.title_contains.as_deref().filter(|t| !t.trim().is_empty()) {
+            let pattern = regex::escape(text.trim()).replace('\'', "\\'");
+            filters.push(format!("title~='(?i){}'", pattern));
+        }
+        if let Some(filter) = self.match_filter.as_deref().filter(|f| !f.trim().is_empty()) {
+            filters.push(filter.trim().to_string());
+        }
+        if !filters.is_empty() {
+            args.push("--match-filter".to_string());
+            args.push(filters.join(" & "));
+        }
+        if let Some(country) = &self.geo_bypass_country {
+            args.push("--geo-bypass-country".to_string());
+            args.push(country.to_uppercase());
+        } else if self.geo_bypass {
+            args.push("--geo-bypass".to_string());
+        }
+        if let Some(limit) = self.speed_limit.filter(|limit| *limit > 0) {
+            args.push("--limit-rate".to_string());
+            args.push(limit.to_string());
+        }
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            args.push("--proxy".to_string());
+            args.push(proxy.trim().to_string());
+        }
+        for extractor_args in &self.extractor_args {
+            args.push("--extractor-args".to_string());
+            args.push(extractor_args.trim().to_string());
+        }
+        args
+    }
+
+    /// The user's extra arguments, passed after everything else so they win over the app's choices
+    pub fn extra_yt_dlp_args(&self) -> Vec<String> {
+        // Validated when the download was queued; a stale preset that no longer passes is dropped
+        match self.extra_args.as_deref().map(crate::extra_args::parse) {
+            Some(Ok(extra)) => extra,
+            Some(Err(e)) => {
+                eprintln!("⚠️  Ignoring extra arguments: {}", e);
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Active,
+    Completed,
+    Failed,
+    /// Restored from a previous session; waits for the user to resume or discard it
+    Interrupted,
+    /// Stopped because the network went away; queued again when it returns
+    Waiting,
+    /// An upcoming premiere or live stream; queued by its watcher once it goes live
+    Scheduled,
+    Cancelled,
+}
+
+/// Set to ask a running download to stop
+pub type CancelFlag = Arc<AtomicBool>;
+
+/// Process ids of every helper process a download is running right now
+static CHILD_PIDS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+type JobPidSink = Box<dyn Fn(Option<u32>)>;
+
+thread_local! {
+    /// Where `spawn_tracked` reports the processes it starts on this thread, see `run_for_job`
+    static JOB_PID_SINK: std::cell::RefCell<Option<JobPidSink>> = const { std::cell::RefCell::new(None) };
+}
+
+fn report_job_pid(pid: Option<u32>) {
+    JOB_PID_SINK.with(|sink| {
+        if let Some(sink) = sink.borrow().as_ref() {
+            sink(pid);
+        }
+    });
+}
+
+/// Removes a tracked process from the list when the download is done with it
+pub struct ChildGuard(u32);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        CHILD_PIDS.lock().unwrap().retain(|pid| *pid != self.0);
+        report_job_pid(None);
+    }
+}
+
+/// Clears the job's process sink when a post-processing step ends, even by panicking
+struct JobPidSinkGuard;
+
+impl Drop for JobPidSinkGuard {
+    fn drop(&mut self) {
+        JOB_PID_SINK.with(|sink| *sink.borrow_mut() = None);
+    }
+}
+
+/// Run a post-processing step of job `id` on the current blocking thread. Each helper it starts
+/// with `spawn_tracked` (ffmpeg, whisper) is the job's process while it runs, so timeouts and
+/// cancellation stop it like the download itself.
+pub fn run_for_job<R: Runtime, T>(app: &AppHandle<R>, id: &str, step: impl FnOnce() -> T) -> T {
+    set_post_processing(app, id, true);
+    let (sink_app, sink_id) = (app.clone(), id.to_string());
+    JOB_PID_SINK.with(|sink| *sink.borrow_mut() = Some(Box::new(move |pid| set_job_pid(&sink_app, &sink_id, pid))));
+    let guard = JobPidSinkGuard;
+    let result = step();
+    drop(guard);
+    set_post_processing(app, id, false);
+    result
+}
+
+fn set_post_processing<R: Runtime>(app: &AppHandle<R>, id: &str, post_processing: bool) {
+    if let Some(job) = app.state::<DownloadManagerState>().lock().unwrap().job_mut(id) {
+        job.post_processing = post_processing;
+    }
+}
+
+/// Spawn a helper process (yt-dlp, ffmpeg, whisper) in its own process group and remember it,
+/// so quitting the app stops it together with anything it started itself: yt-dlp runs
+/// ffmpeg and aria2c as children of its own
+pub fn spawn_tracked(cmd: &mut std::process::Command) -> std::io::Result<(std::process::Child, ChildGuard)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let child = cmd.spawn()?;
+    let pid = child.id();
+    CHILD_PIDS.lock().unwrap().push(pid);
+    report_job_pid(Some(pid));
+    Ok((child, ChildGuard(pid)))
+}
+
+/// Terminate a process and all of its descendants
+pub fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let result = std::process::Command::new("kill").arg("-KILL").arg(format!("-{}", pid)).output();
+    #[cfg(windows)]
+    let result = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .output();
+    if let Err(e) = result {
+        eprintln!("⚠️  Could not stop process {}: {}", pid, e);
+    }
+}
+
+/// Stop every tracked helper process; called on the way out of the app
+pub fn kill_children() {
+    let pids = std::mem::take(&mut *CHILD_PIDS.lock().unwrap());
+    if !pids.is_empty() {
+        eprintln!("🛑 Stopping {} running helper process(es)", pids.len());
+    }
+    for pid in pids {
+        kill_tree(pid);
+    }
+}
+
+pub struct DownloadJob {
+    pub id: String,
+    pub request: DownloadRequest,
+    pub status: JobStatus,
+    /// Higher runs first; jobs with equal priority run in queue order
+    pub priority: i32,
+    pub progress: ProgressState,
+    pub speed_history: VecDeque<SpeedSample>,
+    /// aria2 gids for direct/metalink downloads, used to pause or cancel them over RPC
+    pub gids: Vec<String>,
+    pub cancel: CancelFlag,
+    /// When the job last became active
+    pub started_at_ms: u64,
+    /// Set for jobs queued together by `start_batch_download`
+    pub batch_id: Option<String>,
+    /// The yt-dlp process running this job, or the post-processing helper after it, so a
+    /// timeout can stop it
+    pub pid: Option<u32>,
+    /// Past the download, in post-processing: `pid` is not yt-dlp and restarting it gains nothing
+    pub post_processing: bool,
+    /// Byte count at the last timeout check, and when it last changed
+    last_bytes: u64,
+    last_progress_ms: u64,
+    /// Why the monitor stopped this job, if it hit a timeout
+    pub timed_out: Option<String>,
+    /// Held by the connectivity monitor: yt-dlp jobs were stopped, aria2 transfers paused
+    pub held_for_network: bool,
+    /// Stopped on purpose to restart with new options; queued again instead of failing
+    pub restart_requested: bool,
+    /// Running in one of the fast lane's slots rather than a regular one
+    pub in_fast_slot: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BatchSummary {
+    pub batch_id: String,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+/// An unfinished download as saved in `queue.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersistedJob {
+    pub id: String,
+    pub request: DownloadRequest,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub bytes_downloaded: u64,
+    #[serde(default)]
+    pub total_bytes: u64,
+    #[serde(default)]
+    pub percentage: f64,
+    /// Files yt-dlp was writing, used to find their partial downloads after a restart
+    #[serde(default)]
+    pub destinations: Vec<String>,
+}
+
+/// One job as listed by `list_downloads` and the remote API
+#[derive(Debug, Serialize, Clone)]
+pub struct JobSummary {
+    pub id: String,
+    pub url: String,
+    pub status: JobStatus,
+    pub priority: i32,
+    /// Eligible for the fast lane's slots
+    pub fast_lane: bool,
+    pub progress: DownloadProgress,
+}
+
+/// Combined progress of every download, emitted as `queue-progress`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QueueProgress {
+    pub active: usize,
+    pub queued: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub bytes_downloaded: u64,
+    pub total_bytes: u64,
+    pub percentage: f64,
+    pub speed_bytes_per_sec: u64,
+    pub speed: String,
+    pub eta: String,
+}
+
+#[derive(Default)]
+pub struct DownloadManager {
+    jobs: Vec<DownloadJob>,
+    next_seq: u64,
+    /// Whether the last `queue-progress` event reported work in flight
+    was_busy: bool,
+    /// Downloads retried this session, claimed before the retry is queued so a second request
+    /// for the same one is refused; history only learns of the retry afterwards
+    retried: HashSet<String>,
+}
+
+pub type DownloadManagerState = Arc<Mutex<DownloadManager>>;
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl DownloadManager {
+    /// Add a download to the queue and return its id
+    pub fn enqueue(&mut self, request: DownloadRequest) -> String {
+        self.next_seq += 1;
+        let id = format!("dl-{}-{}", now_millis(), self.next_seq);
+        let mut progress = DownloadProgress::new(&id);
+        progress.status = "queued".to_string();
+
+        self.jobs.push(DownloadJob {
+            id: id.clone(),
+            request,
+            status: JobStatus::Queued,
+            priority: 0,
+            progress: Arc::new(Mutex::new(progress)),
+            speed_history: VecDeque::with_capacity(SPEED_HISTORY_LEN),
+            gids: Vec::new(),
+            cancel: CancelFlag::default(),
+            started_at_ms: 0,
+            batch_id: None,
+            pid: None,
+            post_processing: false,
+            last_bytes: 0,
+            last_progress_ms: 0,
+            timed_out: None,
+            held_for_network: false,
+            restart_requested: false,
+            in_fast_slot: false,
+        });
+        id
+    }
+
+    /// Add an upcoming premiere or stream that waits for `release_scheduled` instead of running
+    pub fn enqueue_scheduled(&mut self, request: DownloadRequest) -> String {
+        let id = self.enqueue(request);
+        if let Some(job) = self.job_mut(&id) {
+            job.status = JobStatus::Scheduled;
+            job.progress.lock().unwrap().status = "scheduled".to_string();
+        }
+        id
+    }
+
+    /// Queue a scheduled job now that its stream is live. False if it was cancelled meanwhile.
+    pub fn release_scheduled(&mut self, id: &str) -> bool {
+        match self.job_mut(id).filter(|job| job.status == JobStatus::Scheduled) {
+            Some(job) => {
+                job.status = JobStatus::Queued;
+                job.progress.lock().unwrap().status = "queued".to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put jobs saved by a previous session back in the list, held until the user decides
+    fn restore(&mut self, records: Vec<PersistedJob>) {
+        for record in records {
+            if self.job(&record.id).is_some() {
+                continue;
+            }
+            let mut progress = DownloadProgress::new(&record.id);
+            progress.status = "interrupted".to_string();
+            progress.bytes_downloaded = record.bytes_downloaded;
+            progress.total_bytes = record.total_bytes;
+            progress.percentage = record.percentage;
+            progress.destinations = record.destinations;
+
+            self.jobs.push(DownloadJob {
+                id: record.id,
+                request: record.request,
+                status: JobStatus::Interrupted,
+                priority: record.priority,
+                progress: Arc::new(Mutex::new(progress)),
+                speed_history: VecDeque::with_capacity(SPEED_HISTORY_LEN),
+                gids: Vec::new(),
+                cancel: CancelFlag::default(),
+                started_at_ms: 0,
+                batch_id: None,
+                pid: None,
+                post_processing: false,
+                last_bytes: 0,
+                last_progress_ms: 0,
+                timed_out: None,
+                held_for_network: false,
+                restart_requested: false,
+                in_fast_slot: false,
+            });
+        }
+    }
+
+    /// The active download shown in the tray: the first one that has started transferring
+    pub fn headline(&self) -> Option<DownloadProgress> {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Active)
+            .map(|job| job.progress.lock().unwrap().clone())
+            .find(|progress| progress.status == "downloading")
+    }
+
+    pub fn summaries(&self) -> Vec<JobSummary> {
+        self.jobs
+            .iter()
+            .map(|job| JobSummary {
+                id: job.id.clone(),
+                url: job.request.url.clone(),
+                status: job.status,
+                priority: job.priority,
+                fast_lane: job.request.is_fast_lane(),
+                progress: job.progress.lock().unwrap().clone(),
+            })
+            .collect()
+    }
+
+    /// Jobs restored from a previous session that are waiting for the user
+    pub fn interrupted(&self) -> impl Iterator<Item = &DownloadJob> {
+        self.jobs.iter().filter(|job| job.status == JobStatus::Interrupted)
+    }
+
+    /// Output folders of every job in the list, including finished ones
+    pub fn output_folders(&self) -> Vec<String> {
+        self.jobs.iter().map(|job| job.request.output_folder.clone()).collect()
+    }
+
+    /// Files yt-dlp announced for every job in the list, including finished ones
+    pub fn destinations(&self) -> Vec<String> {
+        self.jobs.iter().flat_map(|job| job.progress.lock().unwrap().destinations.clone()).collect()
+    }
+
+    /// Output folder of the most recently queued download
+    pub fn last_output_folder(&self) -> Option<String> {
+        self.jobs.last().map(|job| job.request.output_folder.clone())
+    }
+
+    /// Every job that has not finished yet, in queue order
+    pub fn snapshot(&self) -> Vec<PersistedJob> {
+        self.jobs
+            .iter()
+            .filter(|job| {
+                matches!(
+                    job.status,
+                    JobStatus::Queued | JobStatus::Active | JobStatus::Interrupted | JobStatus::Waiting | JobStatus::Scheduled
+                )
+            })
+            .map(|job| {
+                let progress = job.progress.lock().unwrap();
+                PersistedJob {
+                    id: job.id.clone(),
+                    request: job.request.clone(),
+                    priority: job.priority,
+                    bytes_downloaded: progress.bytes_downloaded,
+                    total_bytes: progress.total_bytes,
+                    percentage: progress.percentage,
+                    destinations: progress.destinations.clone(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn job(&self, id: &str) -> Option<&DownloadJob> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn job_mut(&mut self, id: &str) -> Option<&mut DownloadJob> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    /// Outcome counts for a batch once none of its jobs is queued or running
+    pub fn finished_batch(&self, batch_id: &str) -> Option<BatchSummary> {
+        let mut summary = BatchSummary { batch_id: batch_id.to_string(), completed: 0, failed: 0, cancelled: 0 };
+        for job in self.jobs.iter().filter(|job| job.batch_id.as_deref() == Some(batch_id)) {
+            match job.status {
+                JobStatus::Completed => summary.completed += 1,
+                JobStatus::Failed => summary.failed += 1,
+                JobStatus::Cancelled => summary.cancelled += 1,
+                JobStatus::Queued
+                | JobStatus::Active
+                | JobStatus::Interrupted
+                | JobStatus::Waiting
+                | JobStatus::Scheduled => return None,
+            }
+        }
+        Some(summary)
+    }
+
+    fn count(&self, status: JobStatus) -> usize {
+        self.jobs.iter().filter(|job| job.status == status).count()
+    }
+
+    /// Mark as many queued jobs active as there are free slots and return their ids.
+    /// The highest priority wins; ties go to whichever job is earlier in the queue.
+    /// With `metered_only`, only jobs allowed to use a metered connection are started.
+    /// `fast_lane_slots` more slots run fast-lane jobs only, on top of `max_concurrent`.
+    fn claim_next(&mut self, max_concurrent: usize, fast_lane_slots: usize, metered_only: bool) -> Vec<String> {
+        let active_fast = self.jobs.iter().filter(|job| job.status == JobStatus::Active && job.in_fast_slot).count();
+        let active_regular = self.count(JobStatus::Active) - active_fast;
+        let mut claimed = Vec::new();
+        // Fast-lane jobs take their own slots first so regular slots stay free for the rest
+        for _ in 0..fast_lane_slots.saturating_sub(active_fast) {
+            match self.claim_one(metered_only, true) {
+                Some(id) => claimed.push(id),
+                None => break,
+            }
+        }
+        for _ in 0..max_concurrent.saturating_sub(active_regular) {
+            match self.claim_one(metered_only, false) {
+                Some(id) => claimed.push(id),
+                None => break,
+            }
+        }
+        claimed
+    }
+
+    /// Start the next queued job, only a fast-lane one when `fast_slot` is set
+    fn claim_one(&mut self, metered_only: bool, fast_slot: bool) -> Option<String> {
+        // min_by_key keeps the first of equal keys, preserving queue order
+        let job = self
+            .jobs
+            .iter_mut()
+            .filter(|job| job.status == JobStatus::Queued)
+            .filter(|job| !metered_only || job.request.options.allow_metered)
+            .filter(|job| !fast_slot || job.request.is_fast_lane())
+            .min_by_key(|job| Reverse(job.priority))?;
+        job.status = JobStatus::Active;
+        job.in_fast_slot = fast_slot;
+        job.started_at_ms = now_millis();
+        job.last_progress_ms = job.started_at_ms;
+        job.timed_out = None;
+        Some(job.id.clone())
+    }
+
+    /// Move a waiting job to `index` among the jobs that have not started yet
+    fn move_to(&mut self, id: &str, index: usize) -> Result<(), String> {
+        let from = self
+            .jobs
+            .iter()
+            .position(|job| job.id == id)
+            .ok_or_else(|| format!("Unknown download id: {}", id))?;
+        if !matches!(self.jobs[from].status, JobStatus::Queued | JobStatus::Interrupted) {
+            return Err("Only downloads that have not started can be moved".to_string());
+        }
+
+        let job = self.jobs.remove(from);
+        let waiting: Vec<usize> = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| matches!(job.status, JobStatus::Queued | JobStatus::Interrupted))
+            .map(|(i, _)| i)
+            .collect();
+        let to = match waiting.get(index) {
+            Some(&i) => i,
+            None => waiting.last().map_or(self.jobs.len(), |&i| i + 1),
+        };
+        self.jobs.insert(to, job);
+        Ok(())
+    }
+
+    /// Hold every running download while the network is down. yt-dlp cannot pause, so its jobs
+    /// are stopped and restarted later with `--continue`; aria2 transfers are paused in place.
+    /// Returns the processes to stop and the gids to pause.
+    /// With `metered`, the hold is for a metered connection and spares jobs allowed to use one.
+    pub fn hold_for_network(&mut self, metered: bool) -> (Vec<u32>, Vec<String>) {
+        let mut pids = Vec::new();
+        let mut gids = Vec::new();
+        for job in self
+            .jobs
+            .iter_mut()
+            .filter(|job| job.status == JobStatus::Active && !job.held_for_network && !job.post_processing)
+        {
+            if metered && job.request.options.allow_metered {
+                continue;
+            }
+            job.held_for_network = true;
+            job.progress.lock().unwrap().status =
+                if metered { "paused_metered" } else { "waiting_for_network" }.to_string();
+            if job.gids.is_empty() {
+                job.cancel.store(true, Ordering::Relaxed);
+                pids.extend(job.pid);
+            } else {
+                gids.extend(job.gids.clone());
+            }
+        }
+        (pids, gids)
+    }
+
+    /// aria2 transfers the metered throttle applies to: running, not exempt, no limit of their own
+    pub fn throttle_targets(&self) -> Vec<String> {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Active && !job.held_for_network)
+            .filter(|job| !job.request.options.allow_metered && job.request.options.speed_limit.is_none())
+            .flat_map(|job| job.gids.clone())
+            .collect()
+    }
+
+    /// Requeue the jobs held for the network; returns the aria2 gids to unpause
+    pub fn release_after_network(&mut self) -> Vec<String> {
+        let mut gids = Vec::new();
+        for job in self.jobs.iter_mut().filter(|job| job.held_for_network) {
+            job.held_for_network = false;
+            match job.status {
+                JobStatus::Waiting => {
+                    job.status = JobStatus::Queued;
+                    job.cancel = CancelFlag::default();
+                    job.request.options.continue_partial = true;
+                    job.progress.lock().unwrap().status = "queued".to_string();
+                }
+                JobStatus::Active => {
+                    gids.extend(job.gids.clone());
+                    job.progress.lock().unwrap().status = "downloading".to_string();
+                }
+                _ => {}
+            }
+        }
+        gids
+    }
+
+    /// Stop active jobs that ran past their time limits. Returns the processes and aria2 gids
+    /// to terminate, which the caller does outside the lock.
+    fn check_timeouts(&mut self, defaults: &crate::settings::TimeoutSettings) -> (Vec<u32>, Vec<String>) {
+        let now = now_millis();
+        let mut pids = Vec::new();
+        let mut gids = Vec::new();
+        for job in self.jobs.iter_mut().filter(|job| job.status == JobStatus::Active && job.timed_out.is_none() && !job.held_for_network) {
+            let (status, bytes) = {
+                let progress = job.progress.lock().unwrap();
+                (progress.status.clone(), progress.bytes_downloaded)
+            };
+            // Only transfers can stall; metadata fetches and post-processing are covered by the overall limit
+            if bytes != job.last_bytes || status != "downloading" {
+                job.last_bytes = bytes;
+                job.last_progress_ms = now;
+            }
+
+            let options = &job.request.options;
+            let overall_ms = u64::from(options.timeout_minutes.unwrap_or(defaults.overall_minutes)) * 60_000;
+            let stall_ms = u64::from(options.stall_timeout_secs.unwrap_or(defaults.stall_seconds)) * 1000;
+            let reason = if overall_ms > 0 && now.saturating_sub(job.started_at_ms) > overall_ms {
+                format!("Download took longer than {} minutes", overall_ms / 60_000)
+            } else if stall_ms > 0 && now.saturating_sub(job.last_progress_ms) > stall_ms {
+                format!("No data received for {} seconds", stall_ms / 1000)
+            } else {
+                continue;
+            };
+
+            eprintln!("⏱️  Stopping {}: {}", job.request.url, reason);
+            job.timed_out = Some(reason);
+            job.cancel.store(true, Ordering::Relaxed);
+            pids.extend(job.pid);
+            gids.extend(job.gids.clone());
+        }
+        (pids, gids)
+    }
+
+    /// Append the current speed of every active download to its history buffer
+    fn sample_speeds(&mut self) {
+        let timestamp_ms = now_millis();
+        for job in &mut self.jobs {
+            let (status, speed) = {
+                let progress = job.progress.lock().unwrap();
+                (progress.status.clone(), progress.speed_bytes_per_sec)
+            };
+            if status != "downloading" {
+                continue;
+            }
+            if job.speed_history.len() == SPEED_HISTORY_LEN {
+                job.speed_history.pop_front();
+            }
+            job.speed_history.push_back(SpeedSample { timestamp_ms, bytes_per_sec: speed });
+        }
+    }
+
+    pub fn queue_progress(&self) -> QueueProgress {
+        let mut summary = QueueProgress {
+            active: self.count(JobStatus::Active),
+            queued: self.count(JobStatus::Queued),
+            completed: self.count(JobStatus::Completed),
+            failed: self.count(JobStatus::Failed),
+            ..Default::default()
+        };
+
+        for job in self.jobs.iter().filter(|job| job.status == JobStatus::Active) {
+            let progress = job.progress.lock().unwrap();
+            summary.bytes_downloaded += progress.bytes_downloaded;
+            summary.total_bytes += progress.total_bytes;
+            summary.speed_bytes_per_sec += progress.speed_bytes_per_sec;
+        }
+
+        if summary.total_bytes > 0 {
+            summary.percentage =
+                (summary.bytes_downloaded as f64 / summary.total_bytes as f64 * 100.0).min(100.0);
+        }
+        summary.speed = format_speed(summary.speed_bytes_per_sec);
+        summary.eta = calculate_eta(summary.bytes_downloaded, summary.total_bytes, summary.speed_bytes_per_sec);
+        summary
+    }
+}
+
+/// Queue a download and start it if a slot is free
+pub fn submit<R: Runtime>(app: &AppHandle<R>, request: DownloadRequest) -> String {
+    crate::crash::breadcrumb(format!("queued {} download from {}", request.download_type, crate::crash::host_of(&request.url)));
+    let id = app.state::<DownloadManagerState>().lock().unwrap().enqueue(request);
+    schedule(app);
+    id
+}
+
+/// Request for a URL queued without choosing a format: the site preset for the URL, else the
+/// general defaults
+pub fn default_request<R: Runtime>(
+    app: &AppHandle<R>,
+    url: String,
+    output_folder: Option<String>,
+) -> Result<DownloadRequest, String> {
+    let current = crate::settings::current(app);
+    let (download_type, quality) = current.resolve_format(&url, None, None);
+    let output_folder = current.resolve_output_folder(output_folder, &download_type, None)?;
+    let output_folder = crate::storage::validate_output_folder(app, &output_folder)?;
+    let mut options = DownloadOptions::default();
+    crate::classify::mark_unchosen(app, &mut options);
+    Ok(DownloadRequest {
+        url,
+        download_type,
+        quality,
+        output_folder,
+        start_time: None,
+        end_time: None,
+        filename: None,
+        options,
+    })
+}
+
+/// Replace a job's aria2 gids with the ones its transfers continue under (a followed metalink).
+/// Returns the job's speed limit, which the new transfers need too.
+#[cfg(not(target_os = "android"))]
+pub fn adopt_gids<R: Runtime>(app: &AppHandle<R>, id: &str, gids: &[String]) -> Option<u64> {
+    let state = app.state::<DownloadManagerState>();
+    let mut manager = state.lock().unwrap();
+    let job = manager.job_mut(id)?;
+    job.gids = gids.to_vec();
+    job.request.options.speed_limit.filter(|limit| *limit > 0)
+}
+
+/// Remember the process running a job, for timeouts
+pub fn set_job_pid<R: Runtime>(app: &AppHandle<R>, id: &str, pid: Option<u32>) {
+    if let Some(job) = app.state::<DownloadManagerState>().lock().unwrap().job_mut(id) {
+        job.pid = pid;
+    }
+}
+
+/// Save unfinished jobs so they survive an app restart
+pub fn persist<R: Runtime>(app: &AppHandle<R>) {
+    let records = app.state::<DownloadManagerState>().lock().unwrap().snapshot();
+    let result = crate::storage::app_data_file(app, QUEUE_FILE)
+        .and_then(|path| crate::storage::write_json(&path, &records));
+    if let Err(e) = result {
+        eprintln!("⚠️ Failed to save download queue: {}", e);
+    }
+}
+
+/// Load the queue saved by the previous session. Restored jobs are not started
+/// automatically; the frontend asks the user about each one.
+pub fn restore<R: Runtime>(app: &AppHandle<R>) {
+    let records: Vec<PersistedJob> = match crate::storage::app_data_file(app, QUEUE_FILE)
+        .and_then(|path| crate::storage::read_json(&path))
+    {
+        Ok(records) => records.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("⚠️ Failed to load saved download queue: {}", e);
+            return;
+        }
+    };
+
+    // Android partials have their own resume prompt; don't offer the same download twice
+    let records: Vec<PersistedJob> = records
+        .into_iter()
+        .filter(|r| {
+            crate::android_resume::find_matching(&r.request.url, &r.request.download_type, &r.request.output_folder)
+                .is_none()
+        })
+        .collect();
+
+    if !records.is_empty() {
+        eprintln!("📋 Restored {} unfinished download(s) from the last session", records.len());
+    }
+    app.state::<DownloadManagerState>().lock().unwrap().restore(records);
+}
+
+/// Start queued downloads while there are free slots
+pub fn schedule<R: Runtime>(app: &AppHandle<R>) {
+    // Offline, downloads stay queued (metadata and all); the connectivity monitor schedules
+    // again when the network returns
+    if !crate::network::is_online(app) {
+        persist(app);
+        return;
+    }
+    let settings = crate::settings::current(app);
+    let metered_only =
+        settings.metered.action == crate::settings::MeteredAction::Pause && crate::network::is_metered(app);
+    let claimed = app
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .claim_next(settings.general.max_concurrent_downloads, settings.general.fast_lane_slots, metered_only);
+    for id in claimed {
+        tauri::async_runtime::spawn(run_job(app.clone(), id));
+    }
+    persist(app);
+}
+
+/// Whether yt-dlp hands some protocol of this download to aria2c
+fn uses_aria2c<R: Runtime>(app: &AppHandle<R>, request: &DownloadRequest) -> bool {
+    cfg!(not(target_os = "android"))
+        && !request.options.native_downloader
+        && !matches!(request.download_type.as_str(), "direct" | "metalink")
+        && crate::settings::current(app).downloaders.uses_aria2c()
+}
+
+/// A failed aria2c download gets a native retry unless it was stopped on purpose or failed for
+/// a reason another downloader cannot fix (private video, rate limit, network down...)
+fn should_fall_back<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    request: &DownloadRequest,
+    progress: &ProgressState,
+    cancel: &CancelFlag,
+) -> bool {
+    let stopped = app
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .job(id)
+        .is_none_or(|job| job.timed_out.is_some() || job.restart_requested || job.held_for_network);
+    let unexplained = progress
+        .lock()
+        .unwrap()
+        .failure
+        .as_ref()
+        .is_some_and(|failure| failure.kind == crate::errors::FailureKind::Other);
+    !stopped && !cancel.load(Ordering::Relaxed) && unexplained && uses_aria2c(app, request)
+}
+
+async fn run_job<R: Runtime>(app: AppHandle<R>, id: String) {
+    let job = {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        manager
+            .job(&id)
+            .map(|job| (job.request.clone(), job.progress.clone(), job.cancel.clone()))
+    };
+    let Some((request, progress, cancel)) = job else { return };
+    let request = crate::classify::apply(&app, &id, request).await;
+    let request = crate::network::apply_metered_throttle(&app, request);
+    let request = crate::tags::apply_folder_route(&app, request);
+
+    let result = match request.download_type.as_str() {
+        #[cfg(not(target_os = "android"))]
+        "direct" | "metalink" => run_aria2_job(&app, &id, &progress, &request).await,
+        _ => crate::perform_download(&app, progress.clone(), &request, &cancel).await,
+    };
+
+    // aria2c breaks on some fragmented streams; give yt-dlp's own downloader one try before failing
+    let native_fallback = result.is_err() && should_fall_back(&app, &id, &request, &progress, &cancel);
+    let (request, result) = if native_fallback {
+        eprintln!("🔁 {} failed with aria2c, retrying with the native downloader", request.url);
+        let mut request = request;
+        request.options.native_downloader = true;
+        if let Some(job) = app.state::<DownloadManagerState>().lock().unwrap().job_mut(&id) {
+            job.request.options.native_downloader = true;
+        }
+        let reset = {
+            let mut progress = progress.lock().unwrap();
+            progress.failure = None;
+            progress.status = "downloading".to_string();
+            progress.percentage = 0.0;
+            progress.clone()
+        };
+        let _ = app.emit("download-progress", reset);
+        let result = crate::perform_download(&app, progress.clone(), &request, &cancel).await;
+        if result.is_ok() {
+            eprintln!("✅ {} finished with the native downloader", request.url);
+        }
+        (request, result)
+    } else {
+        (request, result)
+    };
+
+    let timed_out = app
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .job(&id)
+        .and_then(|job| job.timed_out.clone());
+    // Stopped to apply new options: run it again, continuing the partial file
+    if result.is_err() {
+        let restarted = {
+            let state = app.state::<DownloadManagerState>();
+            let mut manager = state.lock().unwrap();
+            manager.job_mut(&id).filter(|job| job.restart_requested).map(|job| {
+                job.restart_requested = false;
+                job.status = JobStatus::Queued;
+                job.cancel = CancelFlag::default();
+                job.request.options.continue_partial = true;
+                job.progress.lock().unwrap().status = "queued".to_string();
+            })
+        };
+        if restarted.is_some() {
+            schedule(&app);
+            return;
+        }
+    }
+
+    // YouTube answers 403 when it blocks a player client; queue the job again with the next one
+    let forbidden = progress
+        .lock()
+        .unwrap()
+        .failure
+        .as_ref()
+        .is_some_and(|failure| failure.kind == crate::errors::FailureKind::Forbidden);
+    if result.is_err() && forbidden && timed_out.is_none() && !cancel.load(Ordering::Relaxed) {
+        if let Some(client) = crate::youtube::next_client(&app, &request) {
+            eprintln!("🔁 {} was refused, retrying with the {} player client", request.url, client);
+            let requeued = {
+                let state = app.state::<DownloadManagerState>();
+                let mut manager = state.lock().unwrap();
+                manager.job_mut(&id).map(|job| {
+                    job.status = JobStatus::Queued;
+                    job.request.options.player_client = Some(client);
+                    let mut progress = job.progress.lock().unwrap();
+                    progress.status = "queued".to_string();
+                    progress.failure = None;
+                    progress.clone()
+                })
+            };
+            if let Some(progress) = requeued {
+                let _ = app.emit("download-progress", progress);
+                schedule(&app);
+                return;
+            }
+        }
+    }
+
+    // A job the connectivity monitor stopped, or one that failed on a network error while the
+    // network is down, waits for the connection instead of failing
+    let network_failure = progress
+        .lock()
+        .unwrap()
+        .failure
+        .as_ref()
+        .is_some_and(|failure| failure.kind == crate::errors::FailureKind::Network);
+    if result.is_err() && timed_out.is_none() {
+        let held = app
+            .state::<DownloadManagerState>()
+            .lock()
+            .unwrap()
+            .job(&id)
+            .is_some_and(|job| job.held_for_network);
+        if held || (network_failure && crate::network::os_reports_offline().await) {
+            {
+                let state = app.state::<DownloadManagerState>();
+                let mut manager = state.lock().unwrap();
+                if let Some(job) = manager.job_mut(&id) {
+                    job.status = JobStatus::Waiting;
+                    job.held_for_network = true;
+                }
+            }
+            let mut progress = progress.lock().unwrap();
+            // A held job already shows why it was held (offline or metered)
+            if !held {
+                crate::network::set_offline(&app);
+                progress.status = "waiting_for_network".to_string();
+            }
+            let _ = app.emit("download-progress", progress.clone());
+            eprintln!("📡 {} is waiting for the network", request.url);
+            persist(&app);
+            return;
+        }
+    }
+
+    let result = match (&timed_out, result) {
+        (Some(reason), Err(_)) => {
+            let failure = crate::errors::DownloadFailure::timeout(reason.clone());
+            let message = failure.to_string();
+            progress.lock().unwrap().failure = Some(failure);
+            Err(message)
+        }
+        (_, result) => result,
+    };
+
+    let status = if result.is_ok() {
+        JobStatus::Completed
+    } else if timed_out.is_none() && cancel.load(Ordering::Relaxed) {
+        JobStatus::Cancelled
+    } else {
+        JobStatus::Failed
+    };
+    if status == JobStatus::Cancelled {
+        progress.lock().unwrap().failure = Some(crate::errors::DownloadFailure::cancelled());
+    }
+    crate::crash::breadcrumb(format!("download {} finished: {:?}", id, status));
+
+    let started_at_ms = {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        manager.job_mut(&id).map_or(0, |job| {
+            job.status = status;
+            job.started_at_ms
+        })
+    };
+    let (title, bytes, files, destinations) = {
+        let progress = progress.lock().unwrap();
+        (progress.title.clone(), progress.bytes_downloaded, progress.files.clone(), progress.destinations.clone())
+    };
+    let mut entry = HistoryEntry::new(&id, &request, &title, status, &result, bytes, started_at_ms);
+    entry.native_fallback = native_fallback;
+    entry.destinations = destinations;
+    if let Some(metadata) = crate::metadata_cache::get(&app, &request.url) {
+        entry.add_metadata(&metadata);
+    }
+    if status == JobStatus::Completed {
+        let ffmpeg = crate::binary_manager::resolve_paths(&app).ok().map(|paths| paths.ffmpeg);
+        // Hashing multi-gigabyte files takes a while; keep it off the async workers
+        entry.files = tokio::task::spawn_blocking(move || {
+            let mut files = crate::history::checksum_files(&files);
+            if let Some(ffmpeg) = ffmpeg {
+                for file in files.iter_mut() {
+                    file.duration = crate::ffmpeg::probe_duration(&ffmpeg, std::path::Path::new(&file.path));
+                }
+            }
+            files
+        })
+        .await
+        .unwrap_or_default();
+    }
+    let outcome = result.map(|_| crate::DownloadComplete {
+        id: id.clone(),
+        title: title.clone(),
+        path: entry.files.first().map(|file| file.path.clone()),
+        size_bytes: entry.files.iter().map(|file| file.size).sum(),
+        duration: entry.files.first().and_then(|file| file.duration),
+        files: entry.files.clone(),
+    });
+    crate::history::record(&app, entry);
+    if status == JobStatus::Completed {
+        crate::podcast::on_download_finished(&app, &request.download_type, &request.output_folder);
+    }
+
+    let error = outcome.as_ref().err().cloned();
+    crate::report_download_result(&app, &progress, outcome);
+    let bot_check = progress
+        .lock()
+        .unwrap()
+        .failure
+        .as_ref()
+        .is_some_and(|failure| failure.kind == crate::errors::FailureKind::BotCheck);
+    if status == JobStatus::Failed && bot_check {
+        crate::bot_check::on_bot_check(&app, &id, &request.url);
+    }
+
+    // Direct files have no yt-dlp metadata to take a thumbnail from
+    let thumbnail_url = (!matches!(request.download_type.as_str(), "direct" | "metalink")).then_some(request.url.as_str());
+    match (status, error) {
+        (JobStatus::Completed, _) => crate::notifications::notify(
+            &app,
+            crate::notifications::NotificationEvent::Completed,
+            &crate::i18n::t("notify-complete"),
+            &title,
+            thumbnail_url,
+            Some(crate::notifications::NotificationTarget { id: id.clone() }),
+        ),
+        (JobStatus::Failed, Some(error)) => {
+            let reason = progress
+                .lock()
+                .unwrap()
+                .failure
+                .as_ref()
+                .map(|failure| failure.hint.clone().unwrap_or_else(|| failure.message.clone()))
+                .unwrap_or(error);
+            crate::notifications::notify(
+                &app,
+                crate::notifications::NotificationEvent::Failed,
+                &crate::i18n::tf("notify-failed", &[("title", &title)]),
+                &reason,
+                thumbnail_url,
+                Some(crate::notifications::NotificationTarget { id: id.clone() }),
+            );
+        }
+        _ => {}
+    }
+
+    let finished_batch = {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        manager
+            .job(&id)
+            .and_then(|job| job.batch_id.clone())
+            .and_then(|batch_id| manager.finished_batch(&batch_id))
+    };
+    if let Some(summary) = finished_batch {
+        eprintln!(
+            "📦 Batch {} finished: {} completed, {} failed, {} cancelled",
+            summary.batch_id, summary.completed, summary.failed, summary.cancelled
+        );
+        crate::notifications::notify(
+            &app,
+            crate::notifications::NotificationEvent::BatchComplete,
+            &crate::i18n::t("notify-batch-finished"),
+            &crate::i18n::tf(
+                "notify-batch-summary",
+                &[
+                    ("completed", &summary.completed.to_string()),
+                    ("failed", &summary.failed.to_string()),
+                    ("cancelled", &summary.cancelled.to_string()),
+                ],
+            ),
+            None,
+            None,
+        );
+        let _ = app.emit("batch-complete", summary);
+    }
+
+    if status == JobStatus::Failed && timed_out.is_some() {
+        let retries = request
+            .options
+            .timeout_retries
+            .unwrap_or(crate::settings::current(&app).timeouts.retries);
+        if retries > 0 {
+            let mut retry = request.clone();
+            retry.options.timeout_retries = Some(retries - 1);
+            // A YouTube download that stalls is usually throttled on its player client
+            if let Some(client) = crate::youtube::next_client(&app, &retry) {
+                retry.options.player_client = Some(client);
+            }
+            eprintln!("🔁 Retrying {} after a timeout ({} retries left)", retry.url, retries - 1);
+            submit(&app, retry);
+        }
+    }
+
+    schedule(&app);
+}
+
+/// Direct and metalink downloads go straight to the aria2c RPC daemon
+#[cfg(not(target_os = "android"))]
+async fn run_aria2_job<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    progress: &ProgressState,
+    request: &DownloadRequest,
+) -> Result<String, String> {
+    let gids = if request.download_type == "metalink" {
+        crate::aria2::add_metalink(app, &request.url, &request.output_folder).await?
+    } else {
+        vec![crate::aria2::add_direct(app, &request.url, &request.output_folder, request.filename.as_deref()).await?]
+    };
+
+    if let Some(job) = app.state::<DownloadManagerState>().lock().unwrap().job_mut(id) {
+        job.gids = gids.clone();
+    }
+    if let Some(limit) = request.options.speed_limit.filter(|limit| *limit > 0) {
+        let rpc = crate::aria2::rpc(app).await?;
+        for gid in &gids {
+            rpc.change_option(gid, serde_json::json!({ "max-download-limit": limit.to_string() })).await?;
+        }
+    }
+    progress.lock().unwrap().title = request
+        .filename
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| request.url.rsplit('/').next().unwrap_or(&request.url).to_string());
+
+    let saved = crate::aria2::track(app, id, progress, gids).await?;
+    if saved.is_empty() && request.download_type == "metalink" {
+        return Err("Metalink download finished without producing any files".to_string());
+    }
+    eprintln!("✅ {} download finished: {} file(s)", request.download_type, saved.len());
+    progress.lock().unwrap().files = saved.clone();
+    Ok(saved.join("\n"))
+}
+
+/// Pause every active aria2 transfer. yt-dlp downloads cannot be paused and keep running.
+pub async fn pause_all<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+    crate::crash::breadcrumb("pause all downloads");
+    #[cfg(target_os = "android")]
+    {
+        let _ = app;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let gids: Vec<String> = {
+            let state = app.state::<DownloadManagerState>();
+            let manager = state.lock().unwrap();
+            manager
+                .jobs
+                .iter()
+                .filter(|job| job.status == JobStatus::Active)
+                .flat_map(|job| job.gids.clone())
+                .collect()
+        };
+        if gids.is_empty() {
+            return Ok(0);
+        }
+        let rpc = crate::aria2::rpc(app).await?;
+        for gid in &gids {
+            rpc.pause(gid).await?;
+        }
+        Ok(gids.len())
+    }
+}
+
+/// Cancel every queued and active download
+pub async fn cancel_all<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+    crate::crash::breadcrumb("cancel all downloads");
+    let (cancelled, pids, gids) = {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let mut cancelled = 0;
+        let mut pids = Vec::new();
+        let mut gids = Vec::new();
+        for job in manager.jobs.iter_mut() {
+            match job.status {
+                JobStatus::Queued | JobStatus::Interrupted | JobStatus::Waiting | JobStatus::Scheduled => {
+                    job.status = JobStatus::Cancelled;
+                    job.held_for_network = false;
+                    job.progress.lock().unwrap().status = "cancelled".to_string();
+                }
+                JobStatus::Active => {
+                    // Otherwise it would be parked as Waiting and queued again when the network returns
+                    job.held_for_network = false;
+                    job.cancel.store(true, Ordering::Relaxed);
+                    // The flag is only seen when yt-dlp prints; a stalled transfer or a
+                    // post-processing step has to be stopped
+                    pids.extend(job.pid);
+                    gids.extend(job.gids.clone());
+                }
+                _ => continue,
+            }
+            cancelled += 1;
+        }
+        (cancelled, pids, gids)
+    };
+
+    for pid in pids {
+        kill_tree(pid);
+    }
+    #[cfg(not(target_os = "android"))]
+    if !gids.is_empty() {
+        let rpc = crate::aria2::rpc(app).await?;
+        for gid in &gids {
+            let _ = rpc.remove(gid).await;
+        }
+    }
+    #[cfg(target_os = "android")]
+    let _ = gids;
+
+    persist(app);
+    Ok(cancelled)
+}
+
+/// Background task sampling download speeds and emitting `queue-progress` once per second
+pub fn spawn_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        let mut ticks: u32 = 0;
+        loop {
+            interval.tick().await;
+
+            let timeouts = crate::settings::current(&app).timeouts;
+            let (timed_out_pids, timed_out_gids) =
+                app.state::<DownloadManagerState>().lock().unwrap().check_timeouts(&timeouts);
+            for pid in timed_out_pids {
+                kill_tree(pid);
+            }
+            #[cfg(not(target_os = "android"))]
+            if !timed_out_gids.is_empty() {
+                if let Ok(rpc) = crate::aria2::rpc(&app).await {
+                    for gid in &timed_out_gids {
+                        let _ = rpc.remove(gid).await;
+                    }
+                }
+            }
+            #[cfg(target_os = "android")]
+            let _ = timed_out_gids;
+
+            let summary = {
+                let state = app.state::<DownloadManagerState>();
+                let mut manager = state.lock().unwrap();
+                manager.sample_speeds();
+
+                let summary = manager.queue_progress();
+                let busy = summary.active > 0 || summary.queued > 0;
+                // Keep quiet while idle, but send one final update when the queue drains
+                let emit = busy || manager.was_busy;
+                let drained = manager.was_busy && !busy;
+                manager.was_busy = busy;
+                emit.then_some((summary, drained))
+            };
+
+            if let Some((summary, drained)) = summary {
+                #[cfg(not(target_os = "android"))]
+                {
+                    let headline = app.state::<DownloadManagerState>().lock().unwrap().headline();
+                    crate::tray::update(&app, &summary, headline.as_ref());
+                    crate::taskbar::update(&app, &summary);
+                }
+                let _ = app.emit("queue-progress", summary);
+                if drained {
+                    crate::power::on_queue_drained(&app);
+                }
+
+                // Refresh the saved byte counts now and then so a crash loses little progress
+                ticks = ticks.wrapping_add(1);
+                if ticks.is_multiple_of(PERSIST_EVERY_TICKS) {
+                    persist(&app);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_speed_history(
+    manager: State<'_, DownloadManagerState>,
+    id: String,
+) -> Result<Vec<SpeedSample>, String> {
+    let manager = manager.lock().unwrap();
+    manager
+        .job(&id)
+        .map(|job| job.speed_history.iter().cloned().collect())
+        .ok_or_else(|| format!("Unknown download id: {}", id))
+}
+
+#[tauri::command]
+pub async fn get_queue_progress(manager: State<'_, DownloadManagerState>) -> Result<QueueProgress, String> {
+    Ok(manager.lock().unwrap().queue_progress())
+}
+
+#[tauri::command]
+pub async fn get_restored_downloads(manager: State<'_, DownloadManagerState>) -> Result<Vec<PersistedJob>, String> {
+    let manager = manager.lock().unwrap();
+    Ok(manager
+        .snapshot()
+        .into_iter()
+        .filter(|record| {
+            manager
+                .job(&record.id)
+                .is_some_and(|job| job.status == JobStatus::Interrupted)
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn resume_restored_download<R: Runtime>(app: AppHandle<R>, id: String) -> Result<String, String> {
+    {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let job = manager
+            .job_mut(&id)
+            .filter(|job| job.status == JobStatus::Interrupted)
+            .ok_or_else(|| format!("No interrupted download with id {}", id))?;
+        job.status = JobStatus::Queued;
+        job.progress.lock().unwrap().status = "queued".to_string();
+    }
+    schedule(&app);
+    Ok(id)
+}
+
+/// Cap (or uncap, with 0 or null) a download's bandwidth. aria2 transfers change in place;
+/// a running yt-dlp download is restarted with `--continue` to pick the new limit up.
+#[tauri::command]
+pub async fn set_download_speed_limit<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    bytes_per_sec: Option<u64>,
+) -> Result<(), String> {
+    let limit = bytes_per_sec.filter(|limit| *limit > 0);
+    let (gids, restart_pid) = {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let job = manager.job_mut(&id).ok_or_else(|| format!("Unknown download id: {}", id))?;
+        if matches!(job.status, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled) {
+            return Err("This download has already finished".to_string());
+        }
+        job.request.options.speed_limit = limit;
+
+        let active = job.status == JobStatus::Active;
+        // Only the yt-dlp download takes the limit; its pid is cleared once it exits
+        let restart_pid = if active && job.gids.is_empty() && !job.post_processing { job.pid } else { None };
+        if restart_pid.is_some() {
+            job.restart_requested = true;
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        (if active { job.gids.clone() } else { Vec::new() }, restart_pid)
+    };
+
+    eprintln!(
+        "🚦 Speed limit for {}: {}",
+        id,
+        limit.map_or("unlimited".to_string(), |limit| format!("{} bytes/s", limit))
+    );
+    #[cfg(not(target_os = "android"))]
+    if !gids.is_empty() {
+        let rpc = crate::aria2::rpc(&app).await?;
+        let value = limit.unwrap_or(0).to_string();
+        for gid in &gids {
+            rpc.change_option(gid, serde_json::json!({ "max-download-limit": value })).await?;
+        }
+    }
+    #[cfg(target_os = "android")]
+    let _ = gids;
+    if let Some(pid) = restart_pid {
+        kill_tree(pid);
+    }
+    persist(&app);
+    Ok(())
+}
+
+/// Queue a failed download again with the same options. `id` is a job in the current list or
+/// a history entry from an earlier session; the new attempt is linked to it in history.
+#[tauri::command]
+pub async fn retry_download<R: Runtime>(app: AppHandle<R>, id: String) -> Result<String, String> {
+    if let Some(newer) = crate::history::find(&app, &id).and_then(|entry| entry.retried_by) {
+        return Err(format!("This download was already retried as {}", newer));
+    }
+    let in_list = {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        manager.job(&id).filter(|job| job.status == JobStatus::Failed).map(|job| job.request.clone())
+    };
+    let mut request = match in_list {
+        Some(request) => request,
+        None => {
+            let entry = crate::history::find(&app, &id)
+                .filter(|entry| matches!(entry.status, JobStatus::Failed | JobStatus::Cancelled))
+                .ok_or_else(|| format!("No failed download with id {}", id))?;
+            let output_folder = crate::storage::validate_output_folder(&app, &entry.output_folder)?;
+            DownloadRequest {
+                url: entry.url,
+                download_type: entry.download_type,
+                quality: entry.quality,
+                output_folder,
+                start_time: None,
+                end_time: None,
+                filename: None,
+                options: entry.options,
+            }
+        }
+    };
+    if !app.state::<DownloadManagerState>().lock().unwrap().retried.insert(id.clone()) {
+        return Err("This download was already retried".to_string());
+    }
+    request.options.retry_of = Some(id.clone());
+    request.options.continue_partial = true;
+    eprintln!("🔁 Retrying {}", request.url);
+    let new_id = submit(&app, request);
+    crate::history::mark_retried(&app, &id, &new_id);
+    Ok(new_id)
+}
+
+/// Queue a failed download again with a region override, for "not available in your country" errors
+#[tauri::command]
+pub async fn retry_with_region<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    country: Option<String>,
+    proxy: Option<String>,
+) -> Result<String, String> {
+    let mut request = {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        manager
+            .job(&id)
+            .filter(|job| job.status == JobStatus::Failed)
+            .map(|job| job.request.clone())
+            .ok_or_else(|| format!("No failed download with id {}", id))?
+    };
+
+    request.options.geo_bypass = true;
+    request.options.geo_bypass_country = country.filter(|c| !c.trim().is_empty()).map(|c| c.trim().to_string());
+    if let Some(proxy) = proxy.filter(|p| !p.trim().is_empty()) {
+        request.options.proxy = Some(proxy);
+    }
+    request.options.validate()?;
+
+    eprintln!(
+        "🌍 Retrying {} with geo bypass ({})",
+        request.url,
+        request.options.geo_bypass_country.as_deref().unwrap_or("auto")
+    );
+    Ok(submit(&app, request))
+}
+
+#[tauri::command]
+pub async fn discard_restored_download<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let before = manager.jobs.len();
+        manager
+            .jobs
+            .retain(|job| !(job.id == id && job.status == JobStatus::Interrupted));
+        if manager.jobs.len() == before {
+            return Err(format!("No interrupted download with id {}", id));
+        }
+    }
+    persist(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_download_priority<R: Runtime>(app: AppHandle<R>, id: String, prio: i32) -> Result<(), String> {
+    {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let job = manager
+            .job_mut(&id)
+            .ok_or_else(|| format!("Unknown download id: {}", id))?;
+        job.priority = prio;
+    }
+    persist(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn move_in_queue<R: Runtime>(app: AppHandle<R>, id: String, index: usize) -> Result<(), String> {
+    app.state::<DownloadManagerState>().lock().unwrap().move_to(&id, index)?;
+    persist(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pause_all_downloads<R: Runtime>(app: AppHandle<R>) -> Result<usize, String> {
+    pause_all(&app).await
+}
+
+#[tauri::command]
+pub async fn cancel_all_downloads<R: Runtime>(app: AppHandle<R>) -> Result<usize, String> {
+    cancel_all(&app).await
+}
+
+#[tauri::command]
+pub async fn list_downloads(manager: State<'_, DownloadManagerState>) -> Result<Vec<JobSummary>, String> {
+    Ok(manager.lock().unwrap().summaries())
+}
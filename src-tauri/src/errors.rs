@@ -0,0 +1,178 @@
+// Known yt-dlp failure categories, recognised from stderr so the UI can show what went wrong
+// and what to do about it instead of a raw log
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// "Sign in to confirm you're not a bot": the site wants cookies from a signed-in browser
+    BotCheck,
+    RateLimited,
+    Private,
+    AgeRestricted,
+    GeoRestricted,
+    MembersOnly,
+    Unavailable,
+    /// HTTP 403 on the media itself; on YouTube usually a blocked player client
+    Forbidden,
+    /// Stopped by the app after running too long or receiving nothing for too long
+    Timeout,
+    /// DNS failures, refused or reset connections: usually the local network, not the site
+    Network,
+    /// Stopped by the user
+    Cancelled,
+    /// Nothing recognisable; the message is yt-dlp's own
+    Other,
+}
+
+/// Lowercase stderr fragments for each category, checked in order: YouTube prefixes several of
+/// the specific reasons with "Video unavailable", so that one comes last
+const MARKERS: &[(FailureKind, &[&str])] = &[
+    (FailureKind::BotCheck, &["not a bot", "captcha"]),
+    (FailureKind::RateLimited, &["http error 429", "too many requests"]),
+    (FailureKind::Private, &["private video", "this video is private"]),
+    (
+        FailureKind::AgeRestricted,
+        &["sign in to confirm your age", "age-restricted", "inappropriate for some users"],
+    ),
+    (
+        FailureKind::GeoRestricted,
+        &[
+            "not available in your country",
+            "not available from your location",
+            "geo restrict",
+            "blocked it in your country",
+        ],
+    ),
+    (
+        FailureKind::MembersOnly,
+        &["members-only", "join this channel", "available to this channel's members", "only available for registered users"],
+    ),
+    (
+        FailureKind::Network,
+        &[
+            "getaddrinfo failed",
+            "name or service not known",
+            "temporary failure in name resolution",
+            "nodename nor servname",
+            "network is unreachable",
+            "connection reset by peer",
+            "connection refused",
+            "connection aborted",
+            "timed out",
+        ],
+    ),
+    (FailureKind::Forbidden, &["http error 403"]),
+    (
+        FailureKind::Unavailable,
+        &["video unavailable", "has been removed", "http error 404", "does not exist", "unsupported url"],
+    ),
+];
+
+impl FailureKind {
+    /// What to do about it, in the current locale
+    pub fn hint(self) -> Option<String> {
+        let key = match self {
+            FailureKind::BotCheck => "hint-bot-check",
+            FailureKind::RateLimited => "hint-rate-limited",
+            FailureKind::Private => "hint-private",
+            FailureKind::AgeRestricted => "hint-age-restricted",
+            FailureKind::GeoRestricted => "hint-geo-restricted",
+            FailureKind::MembersOnly => "hint-members-only",
+            FailureKind::Unavailable => "hint-unavailable",
+            FailureKind::Forbidden => "hint-forbidden",
+            FailureKind::Network => "hint-network",
+            FailureKind::Timeout => "hint-timeout",
+            FailureKind::Cancelled | FailureKind::Other => return None,
+        };
+        Some(crate::i18n::t(key))
+    }
+
+    /// Worth retrying unchanged a bit later
+    pub fn retryable(self) -> bool {
+        matches!(self, FailureKind::RateLimited | FailureKind::Network | FailureKind::Timeout)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DownloadFailure {
+    pub kind: FailureKind,
+    /// yt-dlp's error line, without the log prefix
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl DownloadFailure {
+    /// Classify a failed yt-dlp run from its stderr
+    pub fn from_yt_dlp(stderr: &str, exit_code: i32) -> Self {
+        let lower = stderr.to_lowercase();
+        let kind = MARKERS
+            .iter()
+            .find(|(_, markers)| markers.iter().any(|marker| lower.contains(marker)))
+            .map_or(FailureKind::Other, |(kind, _)| *kind);
+
+        // The last ERROR line is the one that ended the run; warnings before it are noise
+        let message = stderr
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("ERROR:"))
+            .map(|line| line.trim().to_string())
+            .or_else(|| stderr.lines().rev().map(str::trim).find(|line| !line.is_empty()).map(str::to_string))
+            .unwrap_or_else(|| format!("yt-dlp failed with exit code {}", exit_code));
+
+        Self { kind, message, hint: kind.hint() }
+    }
+
+    pub fn timeout(message: String) -> Self {
+        let kind = FailureKind::Timeout;
+        Self { kind, message, hint: kind.hint() }
+    }
+
+    pub fn cancelled() -> Self {
+        Self { kind: FailureKind::Cancelled, message: "Download cancelled".to_string(), hint: None }
+    }
+}
+
+impl fmt::Display for DownloadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.hint {
+            Some(hint) => write!(f, "{} — {}", self.message, hint),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Payload of the `download-error` event. `code` is stable across releases and locales, so the
+/// frontend keys its own text on it and falls back to `message`.
+#[derive(Debug, Serialize, Clone)]
+pub struct DownloadErrorEvent {
+    pub id: String,
+    pub code: FailureKind,
+    pub message: String,
+    pub details: ErrorDetails,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ErrorDetails {
+    /// yt-dlp's (or aria2's) own error line
+    pub source_message: String,
+    pub hint: Option<String>,
+    pub retryable: bool,
+}
+
+impl DownloadErrorEvent {
+    /// `failure` is the classified error when there is one; otherwise `error` is reported as `other`
+    pub fn new(id: &str, error: &str, failure: Option<&DownloadFailure>) -> Self {
+        let (code, source_message, hint) = match failure {
+            Some(failure) => (failure.kind, failure.message.clone(), failure.hint.clone()),
+            None => (FailureKind::Other, error.to_string(), None),
+        };
+        Self {
+            id: id.to_string(),
+            code,
+            message: format!("Download failed: {}", error),
+            details: ErrorDetails { source_message, hint, retryable: code.retryable() },
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// User-supplied yt-dlp arguments: split like a shell would, then checked against flags that
+// could run programs, write outside the output folder or fight with what the app manages
+const DENIED_FLAGS: &[&str] = &[
+    // Run arbitrary commands
+    "--exec",
+    "--exec-before-download",
+    "--netrc-cmd",
+    "--use-postprocessor",
+    "--plugin-dirs",
+    "--external-downloader",
+    "--downloader",
+    "--external-downloader-args",
+    "--downloader-args",
+    "--postprocessor-args",
+    "--ppa",
+    // Write or read files outside the download
+    "--output",
+    "-o",
+    "--paths",
+    "-P",
+    "--batch-file",
+    "-a",
+    "--load-info-json",
+    "--print-to-file",
+    "--cookies",
+    "--download-archive",
+    "--config-location",
+    "--config-locations",
+    "--ffmpeg-location",
+    "--cache-dir",
+    "--enable-file-urls",
+    // Replace the bundled binary
+    "--update",
+    "-U",
+    "--update-to",
+    "--alias",
+];
+
+/// Flags that take values as separate arguments, with how many. Any other flag is treated as a
+/// switch, so a stray word after it is refused rather than passed to yt-dlp as an extra URL;
+/// values for flags missing here can still be given inline as `--flag=value`.
+const VALUE_FLAGS: &[(&str, usize)] = &[
+    ("--format", 1),
+    ("-f", 1),
+    ("--format-sort", 1),
+    ("-S", 1),
+    ("--merge-output-format", 1),
+    ("--remux-video", 1),
+    ("--recode-video", 1),
+    ("--audio-format", 1),
+    ("--audio-quality", 1),
+    ("--limit-rate", 1),
+    ("-r", 1),
+    ("--throttled-rate", 1),
+    ("--retries", 1),
+    ("-R", 1),
+    ("--fragment-retries", 1),
+    ("--extractor-retries", 1),
+    ("--file-access-retries", 1),
+    ("--retry-sleep", 1),
+    ("--concurrent-fragments", 1),
+    ("-N", 1),
+    ("--buffer-size", 1),
+    ("--http-chunk-size", 1),
+    ("--playlist-items", 1),
+    ("-I", 1),
+    ("--playlist-start", 1),
+    ("--playlist-end", 1),
+    ("--max-downloads", 1),
+    ("--skip-playlist-after-errors", 1),
+    ("--min-filesize", 1),
+    ("--max-filesize", 1),
+    ("--date", 1),
+    ("--datebefore", 1),
+    ("--dateafter", 1),
+    ("--match-filters", 1),
+    ("--match-filter", 1),
+    ("--break-match-filters", 1),
+    ("--age-limit", 1),
+    ("--download-sections", 1),
+    ("--sub-langs", 1),
+    ("--sub-format", 1),
+    ("--convert-subs", 1),
+    ("--convert-thumbnails", 1),
+    ("--parse-metadata", 1),
+    ("--replace-in-metadata", 3),
+    ("--remove-chapters", 1),
+    ("--sponsorblock-mark", 1),
+    ("--sponsorblock-remove", 1),
+    ("--sponsorblock-chapter-title", 1),
+    ("--fixup", 1),
+    ("--proxy", 1),
+    ("--socket-timeout", 1),
+    ("--source-address", 1),
+    ("--impersonate", 1),
+    ("--geo-verification-proxy", 1),
+    ("--xff", 1),
+    ("--sleep-interval", 1),
+    ("--min-sleep-interval", 1),
+    ("--max-sleep-interval", 1),
+    ("--sleep-requests", 1),
+    ("--sleep-subtitles", 1),
+    ("--user-agent", 1),
+    ("--referer", 1),
+    ("--add-headers", 1),
+    ("--extractor-args", 1),
+    ("--cookies-from-browser", 1),
+    ("--username", 1),
+    ("-u", 1),
+    ("--password", 1),
+    ("-p", 1),
+    ("--twofactor", 1),
+    ("-2", 1),
+    ("--video-password", 1),
+    ("--trim-filenames", 1),
+    ("--output-na-placeholder", 1),
+    ("--compat-options", 1),
+    ("--wait-for-video", 1),
+    ("--encoding", 1),
+    ("--print", 1),
+    ("-O", 1),
+    ("--progress-template", 1),
+];
+
+/// Number of separate values `flag` takes
+fn value_count(flag: &str) -> usize {
+    VALUE_FLAGS.iter().find(|(name, _)| *name == flag).map_or(0, |(_, count)| *count)
+}
+
+/// Split a command line into arguments, honouring single quotes, double quotes and backslashes
+pub fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => match chars.next() {
+                Some(escaped) => current.push(escaped),
+                None => return Err("Extra arguments end with a dangling backslash".to_string()),
+            },
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_token = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_token {
+                    args.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Extra arguments have an unclosed quote".to_string());
+    }
+    if in_token {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn is_denied(flag: &str) -> bool {
+    DENIED_FLAGS.iter().any(|denied| {
+        // Long options may be abbreviated, so also refuse unambiguous prefixes of denied flags
+        flag == *denied || (flag.starts_with("--") && flag.len() >= 4 && denied.starts_with(flag))
+    })
+}
+
+/// Tokenize and validate extra arguments. Every token must be a flag or one of the values that
+/// flag takes, so nothing can be slipped in as an extra URL.
+pub fn parse(input: &str) -> Result<Vec<String>, String> {
+    let args = tokenize(input)?;
+    let mut pending_values = 0;
+    for arg in &args {
+        if arg.contains(['\n', '\r', '\0']) {
+            return Err("Extra arguments cannot contain line breaks".to_string());
+        }
+        if pending_values > 0 {
+            pending_values -= 1;
+        } else if arg.starts_with('-') && arg.len() > 1 {
+            if arg.starts_with("--") {
+                let (flag, inline_value) = match arg.split_once('=') {
+                    Some((flag, _)) => (flag, true),
+                    None => (arg.as_str(), false),
+                };
+                if is_denied(flag) {
+                    return Err(format!("{} is not allowed in extra arguments", flag));
+                }
+                pending_values = if inline_value { 0 } else { value_count(flag) };
+            } else {
+                // Short options can be bundled (-xo) or carry a value (-ofile), so any denied
+                // letter anywhere in the token is refused; pass values as a separate argument
+                let denied = arg[1..].chars().find(|c| is_denied(&format!("-{}", c)));
+                if let Some(letter) = denied {
+                    return Err(format!("-{} is not allowed in extra arguments", letter));
+                }
+                // The first letter that takes a value ends the bundle; the rest of the token is
+                // its value, or the next argument is when nothing follows
+                let letters: Vec<char> = arg[1..].chars().collect();
+                pending_values = match letters.iter().position(|c| value_count(&format!("-{}", c)) > 0) {
+                    Some(index) if index == letters.len() - 1 => value_count(&format!("-{}", letters[index])),
+                    _ => 0,
+                };
+            }
+        } else {
+            return Err(format!(
+                "Unexpected argument \"{}\"; extra arguments must be yt-dlp flags (give values as --flag=value)",
+                arg
+            ));
+        }
+    }
+    if pending_values > 0 {
+        return Err("Extra arguments end with a flag that is missing its value".to_string());
+    }
+    Ok(args)
+}
@@ -0,0 +1,571 @@
+// Helpers for running the bundled ffmpeg: duration probing, progress parsing and
+// post-download processing steps
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Media duration in seconds, read from ffmpeg's input banner
+pub fn probe_duration(ffmpeg: &Path, input: &Path) -> Option<f64> {
+    let output = crate::binary_manager::tool_command(ffmpeg).arg("-hide_banner").arg("-i").arg(input).output().ok()?;
+    // ffmpeg exits non-zero without an output file, but still prints the input info
+    parse_duration(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// `Duration: HH:MM:SS.xx` from ffmpeg's stderr, in seconds
+fn parse_duration(stderr: &str) -> Option<f64> {
+    let captures = Regex::new(r"Duration: (\d+):(\d{2}):(\d{2}(?:\.\d+)?)").unwrap().captures(stderr)?;
+    let hours: f64 = captures[1].parse().ok()?;
+    let minutes: f64 = captures[2].parse().ok()?;
+    let seconds: f64 = captures[3].parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Run an ffmpeg command, calling `on_progress` with 0–100 as `-progress` output arrives.
+/// `duration` is the length of the output in seconds; without it no progress is reported.
+pub fn run_with_progress(
+    mut cmd: Command,
+    duration: Option<f64>,
+    mut on_progress: impl FnMut(f64),
+) -> Result<(), String> {
+    cmd.arg("-progress")
+        .arg("pipe:1")
+        .arg("-nostats")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    eprintln!("Executing FFmpeg: {:?}", cmd);
+
+    let (mut child, _child_guard) =
+        crate::download_manager::spawn_tracked(&mut cmd).map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+
+    // Drain stderr on its own thread so a chatty ffmpeg cannot block on a full pipe
+    let stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            use std::io::Read;
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            // Despite the name, out_time_ms is in microseconds, like out_time_us
+            let micros = line
+                .strip_prefix("out_time_us=")
+                .or_else(|| line.strip_prefix("out_time_ms="))
+                .and_then(|v| v.trim().parse::<f64>().ok());
+            if let (Some(micros), Some(total)) = (micros, duration.filter(|d| *d > 0.0)) {
+                on_progress((micros / 1_000_000.0 / total * 100.0).clamp(0.0, 100.0));
+            }
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("FFmpeg process error: {}", e))?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if status.success() {
+        on_progress(100.0);
+        Ok(())
+    } else {
+        Err(format!("FFmpeg failed: {}", stderr.trim()))
+    }
+}
+
+/// Sibling path used while rewriting a file in place: `name.udl-tmp.ext`
+pub fn working_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}.udl-tmp.{}", stem, ext))
+}
+
+/// Audio encoder arguments that keep a file's format when its audio has to be re-encoded
+fn audio_encoder_args(path: &Path, bitrate: &str) -> Vec<String> {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let bitrate = if bitrate.ends_with(['K', 'k']) { bitrate.to_lowercase() } else { "192k".to_string() };
+    let args: Vec<&str> = match ext.as_str() {
+        "mp3" => vec!["-c:a", "libmp3lame", "-b:a", &bitrate],
+        "opus" | "webm" => vec!["-c:a", "libopus", "-b:a", &bitrate],
+        "flac" => vec!["-c:a", "flac"],
+        "wav" => vec!["-c:a", "pcm_s16le"],
+        _ => vec!["-c:a", "aac", "-b:a", &bitrate],
+    };
+    args.into_iter().map(str::to_string).collect()
+}
+
+/// Two-pass EBU R128 loudness normalization, rewriting `path` in place. Video streams are copied.
+pub fn normalize_loudness(
+    ffmpeg: &Path,
+    path: &Path,
+    target_lufs: f64,
+    bitrate: &str,
+    on_progress: impl FnMut(f64),
+) -> Result<(), String> {
+    let filter = format!("loudnorm=I={}:TP=-1.5:LRA=11", target_lufs);
+
+    // Pass 1: measure. Decoding a long file takes a while, so it is tracked like pass 2.
+    let mut measure = crate::binary_manager::tool_command(ffmpeg);
+    measure
+        .arg("-hide_banner")
+        .arg("-i")
+        .arg(path)
+        .arg("-af")
+        .arg(format!("{}:print_format=json", filter))
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let (child, _child_guard) =
+        crate::download_manager::spawn_tracked(&mut measure).map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+    let analysis = child.wait_with_output().map_err(|e| format!("FFmpeg process error: {}", e))?;
+    if !analysis.status.success() {
+        return Err(format!("Loudness analysis failed: {}", String::from_utf8_lossy(&analysis.stderr).trim()));
+    }
+
+    // The measurements are the last JSON object ffmpeg prints to stderr
+    let stderr = String::from_utf8_lossy(&analysis.stderr);
+    let json_start = stderr.rfind('{').ok_or("Loudness analysis produced no measurements")?;
+    let json_end = stderr.rfind('}').ok_or("Loudness analysis produced no measurements")?;
+    let measured: serde_json::Value = serde_json::from_str(&stderr[json_start..=json_end])
+        .map_err(|e| format!("Failed to parse loudness measurements: {}", e))?;
+    let field = |name: &str| measured[name].as_str().unwrap_or("0").to_string();
+    // Silence measures -inf LUFS, which pass 2 cannot take; there is nothing to normalize anyway
+    if !field("input_i").parse::<f64>().is_ok_and(f64::is_finite) {
+        eprintln!("🔇 {} is silent; skipping loudness normalization", path.display());
+        return Ok(());
+    }
+
+    // Pass 2: apply with the measured values for an accurate, linear adjustment
+    let filter = format!(
+        "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+        filter,
+        field("input_i"),
+        field("input_tp"),
+        field("input_lra"),
+        field("input_thresh"),
+        field("target_offset"),
+    );
+    let working = working_path(path);
+    let mut cmd = crate::binary_manager::tool_command(ffmpeg);
+    cmd.arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(path)
+        .arg("-map")
+        .arg("0")
+        .arg("-c")
+        .arg("copy")
+        .arg("-af")
+        .arg(&filter)
+        .args(audio_encoder_args(path, bitrate))
+        // loudnorm resamples to 192 kHz internally; bring it back to a normal rate
+        .arg("-ar")
+        .arg("48000")
+        .arg(&working);
+
+    if let Err(e) = run_with_progress(cmd, probe_duration(ffmpeg, path), on_progress) {
+        let _ = std::fs::remove_file(&working);
+        return Err(e);
+    }
+    std::fs::rename(&working, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Re-encode presets offered after a video download
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscodePreset {
+    /// H.264 capped at 1080p, plays everywhere
+    #[serde(rename = "h264_1080p")]
+    H264FullHd,
+    /// H.265 at a lower quality target, roughly half the size of H.264
+    H265Small,
+    /// H.264 Main profile at 720p with stereo AAC, for phones and tablets
+    Phone,
+}
+
+enum VideoCodec {
+    H264,
+    Hevc,
+}
+
+/// Names of the encoders this ffmpeg build was compiled with, from `ffmpeg -encoders`
+fn available_encoders(ffmpeg: &Path) -> &'static [String] {
+    static ENCODERS: OnceLock<Vec<String>> = OnceLock::new();
+    ENCODERS.get_or_init(|| {
+        let output = match crate::binary_manager::tool_command(ffmpeg).arg("-hide_banner").arg("-encoders").output() {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("⚠️  Could not list FFmpeg encoders: {}", e);
+                return Vec::new();
+            }
+        };
+        // Lines look like " V....D h264_nvenc           NVIDIA NVENC H.264 encoder"
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let flags = parts.next()?;
+                let name = parts.next()?;
+                (flags.len() == 6 && flags.starts_with('V')).then(|| name.to_string())
+            })
+            .collect()
+    })
+}
+
+/// Hardware encoders to try for a codec, in order of preference, followed by the software encoder
+fn encoder_candidates(codec: &VideoCodec) -> Vec<&'static str> {
+    let (hardware, software): (&[&str], &str) = match codec {
+        VideoCodec::H264 => (&["h264_videotoolbox", "h264_nvenc", "h264_qsv"], "libx264"),
+        VideoCodec::Hevc => (&["hevc_videotoolbox", "hevc_nvenc", "hevc_qsv"], "libx265"),
+    };
+    let mut candidates: Vec<&str> = hardware.to_vec();
+    candidates.push(software);
+    candidates
+}
+
+/// Quality arguments for an encoder; hardware encoders each take their own rate-control flags
+fn encoder_quality_args(encoder: &str, quality: u32) -> Vec<String> {
+    let crf = quality.to_string();
+    // VideoToolbox takes 1–100 where higher is better; map the CRF-style value onto it
+    let videotoolbox_quality = 100u32.saturating_sub(quality * 2).to_string();
+    let args: Vec<&str> = if encoder.ends_with("_nvenc") {
+        vec!["-preset", "p5", "-rc", "vbr", "-cq", &crf]
+    } else if encoder.ends_with("_qsv") {
+        vec!["-global_quality", &crf]
+    } else if encoder.ends_with("_videotoolbox") {
+        vec!["-q:v", &videotoolbox_quality]
+    } else {
+        vec!["-preset", "medium", "-crf", &crf]
+    };
+    args.into_iter().map(str::to_string).collect()
+}
+
+impl TranscodePreset {
+    fn codec(self) -> VideoCodec {
+        match self {
+            TranscodePreset::H265Small => VideoCodec::Hevc,
+            TranscodePreset::H264FullHd | TranscodePreset::Phone => VideoCodec::H264,
+        }
+    }
+
+    fn max_height(self) -> u32 {
+        match self {
+            TranscodePreset::H264FullHd | TranscodePreset::H265Small => 1080,
+            TranscodePreset::Phone => 720,
+        }
+    }
+
+    /// CRF-style quality target; lower is better
+    fn quality(self) -> u32 {
+        match self {
+            TranscodePreset::H264FullHd => 21,
+            TranscodePreset::H265Small => 28,
+            TranscodePreset::Phone => 23,
+        }
+    }
+
+    fn args(self, encoder: &str) -> Vec<String> {
+        let mut args: Vec<String> = vec![
+            "-map".into(),
+            "0:v:0".into(),
+            "-map".into(),
+            "0:a?".into(),
+            // Never upscale; keep the width even as most encoders require
+            "-vf".into(),
+            format!("scale=-2:'min({},ih)'", self.max_height()),
+            "-c:v".into(),
+            encoder.into(),
+        ];
+        args.extend(encoder_quality_args(encoder, self.quality()));
+        if matches!(self.codec(), VideoCodec::Hevc) {
+            // Lets QuickTime and Apple devices recognise the HEVC stream
+            args.extend(["-tag:v".into(), "hvc1".into()]);
+        }
+        if self == TranscodePreset::Phone {
+            args.extend(["-profile:v".into(), "main".into(), "-ac".into(), "2".into()]);
+        }
+        args.extend([
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+            "-c:a".into(),
+            "aac".into(),
+            "-b:a".into(),
+            "160k".into(),
+            "-movflags".into(),
+            "+faststart".into(),
+        ]);
+        args
+    }
+}
+
+/// Re-encode `path` with a preset into an MP4 next to it, replacing the original.
+/// Hardware encoders are tried first; a build can list an encoder the machine has no device for,
+/// so each failure falls through to the next candidate. Returns the new file's path.
+pub fn transcode(
+    ffmpeg: &Path,
+    path: &Path,
+    preset: TranscodePreset,
+    mut on_progress: impl FnMut(f64),
+) -> Result<PathBuf, String> {
+    let available = available_encoders(ffmpeg);
+    let duration = probe_duration(ffmpeg, path);
+    let output = path.with_extension("mp4");
+    let working = working_path(&output);
+
+    let mut last_error = String::from("No suitable video encoder found in FFmpeg");
+    for encoder in encoder_candidates(&preset.codec()) {
+        if !available.iter().any(|name| name == encoder) {
+            continue;
+        }
+        eprintln!("🎞️  Transcoding {} with {}", path.display(), encoder);
+        let mut cmd = crate::binary_manager::tool_command(ffmpeg);
+        cmd.arg("-hide_banner").arg("-y").arg("-i").arg(path).args(preset.args(encoder)).arg(&working);
+        match run_with_progress(cmd, duration, &mut on_progress) {
+            Ok(()) => {
+                std::fs::rename(&working, &output)
+                    .map_err(|e| format!("Failed to move {} into place: {}", output.display(), e))?;
+                if output != path {
+                    let _ = std::fs::remove_file(path);
+                }
+                return Ok(output);
+            }
+            Err(e) => {
+                eprintln!("⚠️  {} failed, trying the next encoder", encoder);
+                let _ = std::fs::remove_file(&working);
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// Where burned-in subtitles sit on screen
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitlePosition {
+    #[default]
+    Bottom,
+    Middle,
+    Top,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct BurnInStyle {
+    /// Font size in libass units, relative to a 288-line script height
+    pub font_size: u32,
+    pub position: SubtitlePosition,
+}
+
+impl Default for BurnInStyle {
+    fn default() -> Self {
+        Self { font_size: 18, position: SubtitlePosition::default() }
+    }
+}
+
+impl BurnInStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(8..=72).contains(&self.font_size) {
+            return Err("Subtitle font size must be between 8 and 72".to_string());
+        }
+        Ok(())
+    }
+
+    /// libass `force_style` overrides; alignment uses the numpad layout (2 bottom, 5 middle, 8 top)
+    fn force_style(&self) -> String {
+        let alignment = match self.position {
+            SubtitlePosition::Bottom => 2,
+            SubtitlePosition::Middle => 5,
+            SubtitlePosition::Top => 8,
+        };
+        format!("FontSize={},Alignment={},MarginV=20", self.font_size, alignment)
+    }
+}
+
+/// Render a subtitle file into the video picture, replacing `video`. `offset` is how many seconds
+/// were cut from the start of the video after the subtitles were written, so cues are shifted back
+/// by that much. Containers that cannot hold H.264 become MP4. Returns the new file's path.
+pub fn burn_subtitles(
+    ffmpeg: &Path,
+    video: &Path,
+    subtitles: &Path,
+    offset: f64,
+    style: &BurnInStyle,
+    mut on_progress: impl FnMut(f64),
+) -> Result<PathBuf, String> {
+    // The subtitles filter takes a path inside a filtergraph, where drive colons, quotes and
+    // commas all need escaping. Converting to a plain-named SRT in the temp dir and running
+    // from there sidesteps that, and applies the offset at the same time.
+    let work_dir = std::env::temp_dir();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let sub_name = format!("udl-burn-{}-{}.srt", std::process::id(), nanos);
+    let mut convert = crate::binary_manager::tool_command(ffmpeg);
+    convert.arg("-hide_banner").arg("-loglevel").arg("error").arg("-y");
+    if offset > 0.0 {
+        convert.arg("-itsoffset").arg(format!("-{}", offset));
+    }
+    let converted = convert
+        .arg("-i")
+        .arg(subtitles)
+        .arg(work_dir.join(&sub_name))
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+    if !converted.status.success() {
+        return Err(format!(
+            "Could not read subtitles {}: {}",
+            subtitles.display(),
+            String::from_utf8_lossy(&converted.stderr).trim()
+        ));
+    }
+
+    let ext = video.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let keeps_container = matches!(ext.as_str(), "mp4" | "mkv" | "mov");
+    let output = if keeps_container { video.to_path_buf() } else { video.with_extension("mp4") };
+    let working = working_path(&output);
+    let duration = probe_duration(ffmpeg, video);
+    let filter = format!("subtitles={}:force_style='{}'", sub_name, style.force_style());
+
+    let available = available_encoders(ffmpeg);
+    let mut result = Err(String::from("No suitable video encoder found in FFmpeg"));
+    for encoder in encoder_candidates(&VideoCodec::H264) {
+        if !available.iter().any(|name| name == encoder) {
+            continue;
+        }
+        eprintln!("💬 Burning subtitles into {} with {}", video.display(), encoder);
+        let mut cmd = crate::binary_manager::tool_command(ffmpeg);
+        cmd.current_dir(&work_dir)
+            .arg("-hide_banner")
+            .arg("-y")
+            .arg("-i")
+            .arg(video)
+            .arg("-map")
+            .arg("0:v:0")
+            .arg("-map")
+            .arg("0:a?")
+            .arg("-vf")
+            .arg(&filter)
+            .arg("-c:v")
+            .arg(encoder)
+            .args(encoder_quality_args(encoder, 20))
+            .arg("-pix_fmt")
+            .arg("yuv420p");
+        if keeps_container {
+            cmd.arg("-c:a").arg("copy");
+        } else {
+            cmd.arg("-c:a").arg("aac").arg("-b:a").arg("160k");
+        }
+        cmd.arg(&working);
+        result = run_with_progress(cmd, duration, &mut on_progress);
+        if result.is_ok() {
+            break;
+        }
+        eprintln!("⚠️  {} failed, trying the next encoder", encoder);
+        let _ = std::fs::remove_file(&working);
+    }
+    let _ = std::fs::remove_file(work_dir.join(&sub_name));
+    result?;
+
+    std::fs::rename(&working, &output).map_err(|e| format!("Failed to move {} into place: {}", output.display(), e))?;
+    if output != video {
+        let _ = std::fs::remove_file(video);
+    }
+    Ok(output)
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct StreamInfo {
+    pub index: u32,
+    /// `video`, `audio`, `subtitle`, `data` or `attachment`
+    pub kind: String,
+    pub codec: String,
+    pub language: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<String>,
+    /// kbit/s
+    pub bitrate: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct MediaInfo {
+    pub container: String,
+    /// Seconds
+    pub duration: Option<f64>,
+    /// Overall kbit/s
+    pub bitrate: Option<u32>,
+    pub size_bytes: u64,
+    pub streams: Vec<StreamInfo>,
+}
+
+fn parse_stream(line: &str) -> Option<StreamInfo> {
+    // Stream #0:0[0x1](und): Video: h264 (High) (avc1 / 0x31637661), yuv420p(progressive), 1920x1080 [SAR 1:1 DAR 16:9], 4500 kb/s, 29.97 fps, ...
+    let header = Regex::new(r"Stream #\d+:(\d+)(?:\[\w+\])?(?:\((\w+)\))?: (\w+): (\w+)").unwrap();
+    let captures = header.captures(line)?;
+    let language = captures.get(2).map(|m| m.as_str().to_string()).filter(|l| l != "und");
+    let mut stream = StreamInfo {
+        index: captures[1].parse().ok()?,
+        kind: captures[3].to_lowercase(),
+        codec: captures[4].to_string(),
+        language,
+        ..Default::default()
+    };
+
+    let details = &line[captures.get(0)?.end()..];
+    if let Some(size) = Regex::new(r", (\d{2,5})x(\d{2,5})").unwrap().captures(details) {
+        stream.width = size[1].parse().ok();
+        stream.height = size[2].parse().ok();
+    }
+    stream.fps = Regex::new(r"([\d.]+) fps").unwrap().captures(details).and_then(|c| c[1].parse().ok());
+    stream.sample_rate = Regex::new(r"(\d+) Hz").unwrap().captures(details).and_then(|c| c[1].parse().ok());
+    stream.bitrate = Regex::new(r"(\d+) kb/s").unwrap().captures(details).and_then(|c| c[1].parse().ok());
+    if stream.kind == "audio" {
+        // The channel layout follows the sample rate: "44100 Hz, stereo, fltp"
+        stream.channels = details
+            .split(", ")
+            .skip_while(|part| !part.ends_with(" Hz"))
+            .nth(1)
+            .map(str::to_string);
+    }
+    Some(stream)
+}
+
+/// Container, duration, bitrate and per-stream details, parsed from `ffmpeg -i`
+pub fn media_info(ffmpeg: &Path, input: &Path) -> Result<MediaInfo, String> {
+    let size_bytes = std::fs::metadata(input)
+        .map_err(|e| format!("Cannot read {}: {}", input.display(), e))?
+        .len();
+    let output = crate::binary_manager::tool_command(ffmpeg)
+        .arg("-hide_banner")
+        .arg("-i")
+        .arg(input)
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let container = Regex::new(r"Input #0, ([^ ]+), from")
+        .unwrap()
+        .captures(&stderr)
+        .map(|c| c[1].trim_end_matches(',').to_string())
+        .ok_or_else(|| format!("Not a recognised media file: {}", input.display()))?;
+
+    let bitrate = Regex::new(r"Duration: .*bitrate: (\d+) kb/s")
+        .unwrap()
+        .captures(&stderr)
+        .and_then(|c| c[1].parse().ok());
+
+    Ok(MediaInfo {
+        container,
+        duration: parse_duration(&stderr),
+        bitrate,
+        size_bytes,
+        streams: stderr.lines().filter_map(parse_stream).collect(),
+    })
+}
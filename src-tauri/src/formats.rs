@@ -0,0 +1,182 @@
+// yt-dlp format selection: resolution tiers and codec preference
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CodecPreference {
+    /// yt-dlp's own ordering
+    #[default]
+    Auto,
+    /// Smallest files at a given quality: AV1 > VP9 > H.264
+    Efficient,
+    /// H.264 only, for older TVs, editors and players
+    Compatible,
+}
+
+/// Container video and audio streams are merged into
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Container {
+    #[default]
+    Mp4,
+    /// Holds any codec, so VP9/AV1 + Opus streams are kept as-is
+    Mkv,
+    /// VP9/AV1 video with Opus/Vorbis audio only
+    Webm,
+}
+
+impl Container {
+    pub fn ext(self) -> &'static str {
+        match self {
+            Container::Mp4 => "mp4",
+            Container::Mkv => "mkv",
+            Container::Webm => "webm",
+        }
+    }
+}
+
+/// Height limit for a quality tier; `None` means no limit ("best")
+fn max_height(quality: &str) -> Result<Option<u32>, String> {
+    match quality {
+        "best" => Ok(None),
+        "360" | "480" | "720" | "1080" | "1440" | "2160" | "4320" => Ok(quality.parse().ok()),
+        other => Err(format!("Unsupported quality: {}", other)),
+    }
+}
+
+/// `-f`, `-S` and container arguments for a video download
+pub fn video_format_args(quality: &str, codec: CodecPreference, container: Container) -> Result<Vec<String>, String> {
+    let height = max_height(quality)?
+        .map(|h| format!("[height<={}]", h))
+        .unwrap_or_default();
+
+    let selector = match (codec, container) {
+        (CodecPreference::Compatible, Container::Webm) => {
+            return Err("H.264 video cannot be stored in WebM; choose MP4 or MKV".to_string());
+        }
+        // WebM can only hold VP9/AV1 with Opus/Vorbis, so pick streams that merge without re-encoding
+        (_, Container::Webm) => format!(
+            "bestvideo{h}[vcodec~='^(vp0?9|av01)']+bestaudio[acodec~='^(opus|vorbis)']/best{h}[ext=webm]",
+            h = height
+        ),
+        // Fall back to any codec if the site offers no H.264 stream at all
+        (CodecPreference::Compatible, _) => format!(
+            "bestvideo{h}[vcodec^=avc1]+bestaudio[acodec^=mp4a]/best{h}[vcodec^=avc1]/bestvideo{h}+bestaudio/best{h}",
+            h = height
+        ),
+        _ => format!("bestvideo{h}+bestaudio/best{h}", h = height),
+    };
+
+    let mut args = vec!["-f".to_string(), selector];
+    // Resolution stays the first sort key so the codec only breaks ties between equal heights
+    match codec {
+        CodecPreference::Auto => {}
+        CodecPreference::Efficient => args.extend(["-S".to_string(), "res,vcodec:av01".to_string()]),
+        CodecPreference::Compatible => args.extend(["-S".to_string(), "res,vcodec:h264,acodec:m4a".to_string()]),
+    }
+
+    args.extend(["--merge-output-format".to_string(), container.ext().to_string()]);
+    // A single pre-merged stream skips the merge step; MKV accepts anything, so remux it too
+    if container == Container::Mkv {
+        args.extend(["--remux-video".to_string(), "mkv".to_string()]);
+    }
+    Ok(args)
+}
+
+/// Audio-only download types; each is also the output file extension
+pub const AUDIO_FORMATS: &[&str] = &["mp3", "m4a", "opus", "flac", "wav"];
+
+pub fn is_audio(download_type: &str) -> bool {
+    AUDIO_FORMATS.contains(&download_type)
+}
+
+/// `192K`-style bitrate (32K–320K) or a VBR level from 0 (best) to 10
+pub fn validate_audio_quality(quality: &str) -> Result<(), String> {
+    let valid = match quality.strip_suffix(['K', 'k']) {
+        Some(kbps) => kbps.parse::<u32>().is_ok_and(|k| (32..=320).contains(&k)),
+        None => quality.parse::<u32>().is_ok_and(|level| level <= 10),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid audio quality \"{}\"; use a bitrate like 192K or a VBR level 0-10", quality))
+    }
+}
+
+/// Extraction arguments for an audio download
+pub fn audio_format_args(format: &str, quality: &str) -> Result<Vec<String>, String> {
+    if !is_audio(format) {
+        return Err(format!("Unsupported audio format: {}", format));
+    }
+    let mut args = vec!["-x".to_string(), "--audio-format".to_string(), format.to_string()];
+    // FLAC and WAV are lossless; a bitrate would be ignored
+    if !matches!(format, "flac" | "wav") {
+        validate_audio_quality(quality)?;
+        args.extend(["--audio-quality".to_string(), quality.to_uppercase()]);
+    }
+    Ok(args)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct SizeEstimate {
+    pub bytes: u64,
+    /// True when any part came from `filesize_approx` or a bitrate × duration guess
+    pub approximate: bool,
+}
+
+/// Size of one format entry from yt-dlp's `formats` list
+fn format_size(format: &serde_json::Value, duration: f64) -> Option<SizeEstimate> {
+    if let Some(bytes) = format["filesize"].as_u64() {
+        return Some(SizeEstimate { bytes, approximate: false });
+    }
+    if let Some(bytes) = format["filesize_approx"].as_u64() {
+        return Some(SizeEstimate { bytes, approximate: true });
+    }
+    // tbr is in kbit/s
+    let tbr = format["tbr"].as_f64().filter(|t| *t > 0.0)?;
+    (duration > 0.0).then(|| SizeEstimate { bytes: (tbr * 1000.0 / 8.0 * duration) as u64, approximate: true })
+}
+
+fn has_codec(format: &serde_json::Value, key: &str) -> bool {
+    format[key].as_str().is_some_and(|codec| codec != "none")
+}
+
+/// Expected download size for a quality tier, mirroring the `bestvideo+bestaudio` selection
+/// from `--dump-json` metadata. `None` when the site reports neither sizes nor bitrates.
+pub fn estimate_size(metadata: &serde_json::Value, download_type: &str, quality: &str) -> Option<SizeEstimate> {
+    let duration = metadata["duration"].as_f64().unwrap_or(0.0);
+    let formats = metadata["formats"].as_array()?;
+    let bitrate = |f: &&serde_json::Value| (f["tbr"].as_f64().unwrap_or(0.0) * 1000.0) as u64;
+
+    let best_audio = formats
+        .iter()
+        .filter(|f| has_codec(f, "acodec") && !has_codec(f, "vcodec"))
+        .max_by_key(bitrate)
+        .and_then(|f| format_size(f, duration));
+    if is_audio(download_type) {
+        return best_audio;
+    }
+
+    let height_limit = max_height(quality).ok()?.unwrap_or(u32::MAX);
+    let fits = |f: &&serde_json::Value| f["height"].as_u64().is_some_and(|h| h <= height_limit as u64);
+    let best_video = formats
+        .iter()
+        .filter(|f| has_codec(f, "vcodec") && !has_codec(f, "acodec"))
+        .filter(fits)
+        .max_by_key(|f| (f["height"].as_u64().unwrap_or(0), bitrate(f)))
+        .and_then(|f| format_size(f, duration));
+
+    match (best_video, best_audio) {
+        (Some(video), Some(audio)) => Some(SizeEstimate {
+            bytes: video.bytes + audio.bytes,
+            approximate: video.approximate || audio.approximate,
+        }),
+        // No separate streams: fall back to the best pre-merged format
+        _ => formats
+            .iter()
+            .filter(|f| has_codec(f, "vcodec") && has_codec(f, "acodec"))
+            .filter(fits)
+            .max_by_key(|f| (f["height"].as_u64().unwrap_or(0), bitrate(f)))
+            .and_then(|f| format_size(f, duration)),
+    }
+}
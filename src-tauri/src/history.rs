@@ -0,0 +1,527 @@
+// Record of finished downloads, kept in history.json in the app data directory
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::{DownloadOptions, DownloadRequest, JobStatus};
+use crate::storage;
+
+const HISTORY_FILE: &str = "history.json";
+/// Oldest entries are dropped past this many
+const MAX_ENTRIES: usize = 5000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub url: String,
+    /// Host of the URL without `www.`, for per-site grouping
+    pub site: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub uploader: Option<String>,
+    /// yt-dlp's `extractor_key:id`, e.g. `Youtube:dQw4w9WgXcQ`, the same for every URL form of a video
+    #[serde(default)]
+    pub video_id: Option<String>,
+    pub download_type: String,
+    pub quality: String,
+    pub output_folder: String,
+    pub status: JobStatus,
+    /// What the download produced: the title or saved paths on success
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub bytes: u64,
+    pub started_at_ms: u64,
+    pub finished_at_ms: u64,
+    /// Files the download produced, with their checksums at completion
+    #[serde(default)]
+    pub files: Vec<HistoryFile>,
+    /// Options the download ran with, so it can be retried as it was
+    #[serde(default)]
+    pub options: DownloadOptions,
+    /// The failed attempt this one retried
+    #[serde(default)]
+    pub retry_of: Option<String>,
+    /// The attempt that retried this one; a failed entry with this set shows as retried
+    #[serde(default)]
+    pub retried_by: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// aria2c failed and yt-dlp's native downloader finished the download
+    #[serde(default)]
+    pub native_fallback: bool,
+    /// Every file yt-dlp announced writing, intermediate ones included, so leftovers from a
+    /// failed run can be told apart from the user's own files
+    #[serde(default)]
+    pub destinations: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryFile {
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256
+    pub sha256: String,
+    /// Media length in seconds, when ffmpeg could read it
+    #[serde(default)]
+    pub duration: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileIntegrity {
+    Ok,
+    Modified,
+    Missing,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileVerification {
+    pub path: String,
+    pub status: FileIntegrity,
+}
+
+/// Size and SHA-256 of each finished file; files that cannot be read are skipped with a warning
+pub fn checksum_files(paths: &[String]) -> Vec<HistoryFile> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let file = std::path::Path::new(path);
+            let checksum = std::fs::metadata(file)
+                .map_err(|e| e.to_string())
+                .and_then(|meta| crate::checksum::sha256_file(file).map(|sha256| (meta.len(), sha256)));
+            match checksum {
+                Ok((size, sha256)) => Some(HistoryFile { path: path.clone(), size, sha256, duration: None }),
+                Err(e) => {
+                    eprintln!("⚠️  Could not checksum {}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+impl HistoryEntry {
+    pub fn new(
+        id: &str,
+        request: &DownloadRequest,
+        title: &str,
+        status: JobStatus,
+        result: &Result<String, String>,
+        bytes: u64,
+        started_at_ms: u64,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            url: request.url.clone(),
+            site: site_of(&request.url),
+            title: title.to_string(),
+            uploader: None,
+            video_id: None,
+            download_type: request.download_type.clone(),
+            quality: request.quality.clone(),
+            output_folder: request.output_folder.clone(),
+            status,
+            result: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+            bytes,
+            started_at_ms,
+            finished_at_ms: crate::download_manager::now_millis(),
+            files: Vec::new(),
+            options: request.options.clone(),
+            retry_of: request.options.retry_of.clone(),
+            retried_by: None,
+            tags: request.options.tags.clone(),
+            native_fallback: false,
+            destinations: Vec::new(),
+        }
+    }
+
+    /// Fill in the uploader and video id from the yt-dlp info the download used
+    pub fn add_metadata(&mut self, metadata: &serde_json::Value) {
+        self.uploader = metadata["uploader"].as_str().or(metadata["channel"].as_str()).map(str::to_string);
+        if let (Some(extractor), Some(id)) = (metadata["extractor_key"].as_str(), metadata["id"].as_str()) {
+            self.video_id = Some(format!("{}:{}", extractor, id));
+        }
+    }
+
+    /// What identifies the video for duplicate detection: the video id, else the URL without its
+    /// fragment
+    fn video_key(&self) -> String {
+        self.video_id.clone().unwrap_or_else(|| {
+            url::Url::parse(&self.url)
+                .map(|mut url| {
+                    url.set_fragment(None);
+                    url.to_string()
+                })
+                .unwrap_or_else(|_| self.url.clone())
+        })
+    }
+
+    fn matches_text(&self, terms: &[String]) -> bool {
+        let haystack = format!("{} {}", self.title, self.uploader.as_deref().unwrap_or_default()).to_lowercase();
+        terms.iter().all(|term| haystack.contains(term.as_str()))
+    }
+
+    /// Average transfer rate over the whole download
+    pub fn average_speed(&self) -> u64 {
+        let secs = self.finished_at_ms.saturating_sub(self.started_at_ms) as f64 / 1000.0;
+        if secs <= 0.0 {
+            0
+        } else {
+            (self.bytes as f64 / secs) as u64
+        }
+    }
+}
+
+pub fn site_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        .map(|host| host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+        .unwrap_or_else(|| "local".to_string())
+}
+
+/// Newest entries last; loaded once at startup
+pub type HistoryState = Arc<Mutex<Vec<HistoryEntry>>>;
+
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Vec<HistoryEntry> {
+    match storage::app_data_file(app, HISTORY_FILE).and_then(|path| storage::read_json(&path)) {
+        Ok(entries) => entries.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("⚠️  Failed to load download history: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, entries: &[HistoryEntry]) -> Result<(), String> {
+    let path = storage::app_data_file(app, HISTORY_FILE)?;
+    storage::write_json(&path, &entries)
+}
+
+pub fn record<R: Runtime>(app: &AppHandle<R>, entry: HistoryEntry) {
+    let snapshot = {
+        let state = app.state::<HistoryState>();
+        let mut entries = state.lock().unwrap();
+        entries.push(entry);
+        if entries.len() > MAX_ENTRIES {
+            let excess = entries.len() - MAX_ENTRIES;
+            entries.drain(..excess);
+        }
+        entries.clone()
+    };
+    if let Err(e) = save(app, &snapshot) {
+        eprintln!("⚠️  Failed to save download history: {}", e);
+    }
+}
+
+/// Snapshot of every entry, oldest first
+pub fn all<R: Runtime>(app: &AppHandle<R>) -> Vec<HistoryEntry> {
+    app.state::<HistoryState>().lock().unwrap().clone()
+}
+
+/// The latest entry with this id
+pub fn find<R: Runtime>(app: &AppHandle<R>, id: &str) -> Option<HistoryEntry> {
+    app.state::<HistoryState>().lock().unwrap().iter().rev().find(|entry| entry.id == id).cloned()
+}
+
+/// Change the latest entry with this id and save. False if there is no such entry.
+pub fn update<R: Runtime>(app: &AppHandle<R>, id: &str, change: impl FnOnce(&mut HistoryEntry)) -> bool {
+    let snapshot = {
+        let state = app.state::<HistoryState>();
+        let mut entries = state.lock().unwrap();
+        let Some(entry) = entries.iter_mut().rev().find(|entry| entry.id == id) else { return false };
+        change(entry);
+        entries.clone()
+    };
+    if let Err(e) = save(app, &snapshot) {
+        eprintln!("⚠️  Failed to save download history: {}", e);
+    }
+    true
+}
+
+/// Link a finished download to the attempt that retries it
+pub fn mark_retried<R: Runtime>(app: &AppHandle<R>, id: &str, retry_id: &str) {
+    update(app, id, |entry| entry.retried_by = Some(retry_id.to_string()));
+}
+
+/// Most recent entries first, optionally filtered by status
+pub fn recent<R: Runtime>(app: &AppHandle<R>, limit: usize, status: Option<JobStatus>) -> Vec<HistoryEntry> {
+    let state = app.state::<HistoryState>();
+    let entries = state.lock().unwrap();
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| status.is_none() || status == Some(entry.status))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// Filters for `search_history`; unset fields match everything
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// Words that must all appear in the title or uploader, case-insensitively
+    pub text: Option<String>,
+    pub statuses: Vec<JobStatus>,
+    pub download_types: Vec<String>,
+    pub site: Option<String>,
+    /// Tags that must all be on the entry
+    pub tags: Vec<String>,
+    /// Finish time range in milliseconds since the epoch, inclusive
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+    pub offset: usize,
+    /// 100 when unset
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryPage {
+    /// Matches before `offset` and `limit` are applied
+    pub total: usize,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Completed downloads of the same video
+#[derive(Debug, Serialize, Clone)]
+pub struct DuplicateGroup {
+    pub video_key: String,
+    pub title: String,
+    /// Bytes that deleting all but the newest copy would free
+    pub redundant_bytes: u64,
+    /// Newest first
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Columns `export_history` writes when the caller does not choose any
+const DEFAULT_COLUMNS: &[&str] = &[
+    "finished_at", "status", "title", "url", "site", "download_type", "quality", "bytes", "output_folder",
+];
+const ALL_COLUMNS: &[&str] = &[
+    "id", "url", "site", "title", "download_type", "quality", "output_folder", "status", "result", "error",
+    "bytes", "average_speed", "started_at", "finished_at",
+];
+
+fn column_value(entry: &HistoryEntry, column: &str) -> serde_json::Value {
+    use serde_json::json;
+    match column {
+        "id" => json!(entry.id),
+        "url" => json!(entry.url),
+        "site" => json!(entry.site),
+        "title" => json!(entry.title),
+        "download_type" => json!(entry.download_type),
+        "quality" => json!(entry.quality),
+        "output_folder" => json!(entry.output_folder),
+        "status" => json!(entry.status),
+        "result" => json!(entry.result),
+        "error" => json!(entry.error),
+        "bytes" => json!(entry.bytes),
+        "average_speed" => json!(entry.average_speed()),
+        "started_at" => json!(crate::stats::format_timestamp(entry.started_at_ms)),
+        "finished_at" => json!(crate::stats::format_timestamp(entry.finished_at_ms)),
+        _ => serde_json::Value::Null,
+    }
+}
+
+fn csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Write history entries finished between `from_ms` and `to_ms` (inclusive) as CSV or JSON.
+/// Returns the number of entries written.
+#[tauri::command]
+pub async fn export_history<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    format: String,
+    columns: Option<Vec<String>>,
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+) -> Result<usize, String> {
+    let columns: Vec<String> = columns
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect());
+    if let Some(unknown) = columns.iter().find(|c| !ALL_COLUMNS.contains(&c.as_str())) {
+        return Err(format!("Unknown history column: {} (expected one of {})", unknown, ALL_COLUMNS.join(", ")));
+    }
+
+    let entries: Vec<HistoryEntry> = all(&app_handle)
+        .into_iter()
+        .filter(|e| e.finished_at_ms >= from_ms.unwrap_or(0) && e.finished_at_ms <= to_ms.unwrap_or(u64::MAX))
+        .collect();
+
+    let contents = match format.to_lowercase().as_str() {
+        "json" => {
+            let rows: Vec<serde_json::Map<String, serde_json::Value>> = entries
+                .iter()
+                .map(|entry| columns.iter().map(|c| (c.clone(), column_value(entry, c))).collect())
+                .collect();
+            serde_json::to_string_pretty(&rows).map_err(|e| format!("Failed to serialize history: {}", e))?
+        }
+        "csv" => {
+            let mut csv = columns.join(",");
+            csv.push_str("\r\n");
+            for entry in &entries {
+                let row: Vec<String> = columns.iter().map(|c| csv_field(&column_value(entry, c))).collect();
+                csv.push_str(&row.join(","));
+                csv.push_str("\r\n");
+            }
+            csv
+        }
+        other => return Err(format!("Unsupported export format: {} (use csv or json)", other)),
+    };
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    eprintln!("📤 Exported {} history entries to {}", entries.len(), path);
+    Ok(entries.len())
+}
+
+#[tauri::command]
+pub async fn get_history<R: Runtime>(
+    app_handle: AppHandle<R>,
+    limit: Option<usize>,
+    status: Option<JobStatus>,
+) -> Result<Vec<HistoryEntry>, String> {
+    Ok(recent(&app_handle, limit.unwrap_or(100), status))
+}
+
+/// Every attempt of a download, first to last, following the retry links from any of them
+#[tauri::command]
+pub async fn get_retry_chain<R: Runtime>(app_handle: AppHandle<R>, id: String) -> Result<Vec<HistoryEntry>, String> {
+    let entries = all(&app_handle);
+    let by_id = |id: &str| entries.iter().rev().find(|entry| entry.id == id);
+    let mut first = by_id(&id).ok_or_else(|| format!("No download with id {} in history", id))?;
+    // Bounded in case hand-edited history links back on itself
+    for _ in 0..entries.len() {
+        match first.retry_of.as_deref().and_then(by_id) {
+            Some(earlier) => first = earlier,
+            None => break,
+        }
+    }
+
+    let mut chain = vec![first.clone()];
+    while let Some(next) = chain.last().and_then(|entry| entry.retried_by.as_deref()).and_then(by_id) {
+        if chain.iter().any(|entry| entry.id == next.id) {
+            break;
+        }
+        chain.push(next.clone());
+    }
+    Ok(chain)
+}
+
+/// Newest matches first
+#[tauri::command]
+pub async fn search_history<R: Runtime>(app_handle: AppHandle<R>, query: HistoryQuery) -> Result<HistoryPage, String> {
+    let terms: Vec<String> = query
+        .text
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let site = query.site.as_deref().map(str::to_lowercase);
+    let tags: Vec<String> = query.tags.iter().map(|tag| crate::tags::normalize(tag)).collect::<Result<_, _>>()?;
+
+    let state = app_handle.state::<HistoryState>();
+    let entries = state.lock().unwrap();
+    let matches: Vec<&HistoryEntry> = entries
+        .iter()
+        .rev()
+        .filter(|entry| query.statuses.is_empty() || query.statuses.contains(&entry.status))
+        .filter(|entry| query.download_types.is_empty() || query.download_types.contains(&entry.download_type))
+        .filter(|entry| site.is_none() || site.as_deref() == Some(entry.site.as_str()))
+        .filter(|entry| entry.finished_at_ms >= query.from_ms.unwrap_or(0))
+        .filter(|entry| entry.finished_at_ms <= query.to_ms.unwrap_or(u64::MAX))
+        .filter(|entry| tags.iter().all(|tag| entry.tags.contains(tag)))
+        .filter(|entry| entry.matches_text(&terms))
+        .collect();
+
+    Ok(HistoryPage {
+        total: matches.len(),
+        entries: matches.into_iter().skip(query.offset).take(query.limit.unwrap_or(100)).cloned().collect(),
+    })
+}
+
+/// Videos downloaded more than once, largest waste first
+#[tauri::command]
+pub async fn find_duplicate_downloads<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<DuplicateGroup>, String> {
+    let mut groups: std::collections::HashMap<String, Vec<HistoryEntry>> = std::collections::HashMap::new();
+    for entry in all(&app_handle).into_iter().rev().filter(|entry| entry.status == JobStatus::Completed) {
+        groups.entry(entry.video_key()).or_default().push(entry);
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .filter(|(_, entries)| entries.len() > 1)
+        .map(|(video_key, entries)| DuplicateGroup {
+            title: entries[0].title.clone(),
+            redundant_bytes: entries[1..].iter().map(|entry| entry.bytes).sum(),
+            video_key,
+            entries,
+        })
+        .collect();
+    duplicates.sort_by_key(|group| std::cmp::Reverse(group.redundant_bytes));
+    Ok(duplicates)
+}
+
+#[tauri::command]
+pub async fn clear_history<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    app_handle.state::<HistoryState>().lock().unwrap().clear();
+    save(&app_handle, &[])
+}
+
+/// Re-hash the files of a completed download and compare them with the recorded checksums
+#[tauri::command]
+pub async fn verify_download<R: Runtime>(app_handle: AppHandle<R>, id: String) -> Result<Vec<FileVerification>, String> {
+    let files = {
+        let state = app_handle.state::<HistoryState>();
+        let entries = state.lock().unwrap();
+        let entry = entries
+            .iter()
+            .rev()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("No download with id {} in history", id))?;
+        if entry.files.is_empty() {
+            return Err("No checksums were recorded for this download".to_string());
+        }
+        entry.files.clone()
+    };
+
+    tokio::task::spawn_blocking(move || {
+        files
+            .into_iter()
+            .map(|file| {
+                let path = std::path::Path::new(&file.path);
+                let status = if !path.is_file() {
+                    FileIntegrity::Missing
+                } else {
+                    match crate::checksum::sha256_file(path) {
+                        Ok(sha256) if sha256 == file.sha256 => FileIntegrity::Ok,
+                        Ok(_) => FileIntegrity::Modified,
+                        Err(_) => FileIntegrity::Missing,
+                    }
+                };
+                if status != FileIntegrity::Ok {
+                    eprintln!("⚠️  {} failed verification: {:?}", file.path, status);
+                }
+                FileVerification { path: file.path, status }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Verification task failed: {}", e))
+}
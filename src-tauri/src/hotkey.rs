@@ -0,0 +1,64 @@
+// Global "paste and download" shortcut: queues the link on the clipboard without opening the
+// window, and reports the result with a notification
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::download_manager::{self, DownloadRequest};
+use crate::i18n::t;
+use crate::{notifications, presets, settings};
+
+pub fn parse(shortcut: &str) -> Result<Shortcut, String> {
+    shortcut
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", shortcut, e))
+}
+
+/// (Re)register the shortcut from the current settings; called at startup and when they change
+pub fn register<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| format!("Failed to clear shortcuts: {}", e))?;
+
+    let hotkey = settings::current(app).hotkey;
+    if !hotkey.enabled {
+        return Ok(());
+    }
+    let shortcut = parse(&hotkey.shortcut)?;
+    global_shortcut
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                paste_and_download(app);
+            }
+        })
+        // Usually another app holding the same combination
+        .map_err(|e| format!("Failed to register shortcut {}: {}", hotkey.shortcut, e))?;
+    eprintln!("⌨️ Registered download shortcut {}", hotkey.shortcut);
+    Ok(())
+}
+
+fn request_for<R: Runtime>(app: &AppHandle<R>, url: String) -> Result<DownloadRequest, String> {
+    match settings::current(app).hotkey.preset {
+        Some(name) => presets::request_for(app, presets::find(app, &name)?, url, None),
+        None => download_manager::default_request(app, url, None),
+    }
+}
+
+fn queue_clipboard_url<R: Runtime>(app: &AppHandle<R>) -> Result<String, String> {
+    let text = app.clipboard().read_text().map_err(|_| t("clipboard-no-url"))?;
+    let url = crate::parse_shared_text(&text)
+        .accepted
+        .into_iter()
+        .next()
+        .ok_or_else(|| t("clipboard-no-url"))?
+        .url;
+    download_manager::submit(app, request_for(app, url.clone())?);
+    eprintln!("⌨️ Queued {} from the clipboard", url);
+    Ok(url)
+}
+
+fn paste_and_download<R: Runtime>(app: &AppHandle<R>) {
+    notifications::notify_queued(app, queue_clipboard_url(app));
+}
@@ -0,0 +1,243 @@
+// Translations for strings the backend shows directly: notifications, the tray menu, dialogs
+// and failure hints. The frontend reports the UI locale with `set_locale`; keys missing from a
+// locale fall back to English.
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tauri::{AppHandle, Runtime};
+
+/// Locales with a catalog, by language subtag
+pub const LOCALES: &[&str] = &["en", "es", "fr", "de"];
+
+static LOCALE: RwLock<&'static str> = RwLock::new("en");
+
+/// Messages use `{name}` placeholders, filled in by [`tf`]
+type Catalog = &'static [(&'static str, &'static str)];
+
+const EN: Catalog = &[
+    ("notify-started", "Download started"),
+    ("notify-complete", "Download complete"),
+    ("notify-failed", "Download failed: {title}"),
+    ("notify-batch-finished", "Downloads finished"),
+    ("notify-batch-summary", "{completed} completed, {failed} failed, {cancelled} cancelled"),
+    ("tray-idle", "No active downloads"),
+    ("tray-preparing", "Preparing download…"),
+    ("tray-downloading", "Downloading"),
+    ("tray-progress", "{title} — {percent}% at {speed}"),
+    ("tray-queue-empty", "Queue empty"),
+    ("tray-queue", "{active} active, {queued} queued — {percent}% overall"),
+    ("tray-show", "Show"),
+    ("tray-pause-all", "Pause All"),
+    ("tray-cancel-all", "Cancel All"),
+    ("tray-open-folder", "Open Downloads Folder"),
+    ("tray-overlay", "Progress Overlay"),
+    ("tray-quit", "Quit"),
+    ("queued", "Download queued"),
+    ("queue-failed", "Couldn't queue download"),
+    ("clipboard-no-url", "No link on the clipboard"),
+    ("quit-title", "Quit Confirmation"),
+    ("quit-message", "Are you sure you want to quit U-Download?"),
+    ("yes", "Yes"),
+    ("no", "No"),
+    ("action-open-file", "Open file"),
+    ("action-show-in-folder", "Show in folder"),
+    ("action-retry", "Retry"),
+    ("hint-rate-limited", "The site is rate-limiting requests. Wait a while, run fewer downloads at once, or add cookies."),
+    ("hint-private", "This video is private. Add cookies from an account that has access to it."),
+    ("hint-age-restricted", "This video is age-restricted. Add cookies from a signed-in account."),
+    ("hint-geo-restricted", "This video is not available in your region. Retry with a geo-bypass country or a proxy."),
+    ("hint-members-only", "This video is for channel members only. Add cookies from a member account."),
+    ("hint-bot-check", "The site wants to confirm you're not a bot. Import cookies from your browser or sign in, and the download is retried."),
+    ("login-window-title", "Sign in"),
+    ("hint-unavailable", "The video was removed or the link is wrong. Check the URL."),
+    ("hint-forbidden", "The site refused the download. Update yt-dlp or pick another YouTube player client in settings."),
+    ("hint-network", "The connection failed. Check your network; the download restarts on its own when it is back."),
+    ("hint-timeout", "The download hung and was stopped. Retry it, or raise the timeouts in settings."),
+];
+
+const ES: Catalog = &[
+    ("notify-started", "Descarga iniciada"),
+    ("notify-complete", "Descarga completada"),
+    ("notify-failed", "Error en la descarga: {title}"),
+    ("notify-batch-finished", "Descargas terminadas"),
+    ("notify-batch-summary", "{completed} completadas, {failed} fallidas, {cancelled} canceladas"),
+    ("tray-idle", "No hay descargas activas"),
+    ("tray-preparing", "Preparando descarga…"),
+    ("tray-downloading", "Descargando"),
+    ("tray-progress", "{title} — {percent}% a {speed}"),
+    ("tray-queue-empty", "Cola vacía"),
+    ("tray-queue", "{active} activas, {queued} en cola — {percent}% en total"),
+    ("tray-show", "Mostrar"),
+    ("tray-pause-all", "Pausar todo"),
+    ("tray-cancel-all", "Cancelar todo"),
+    ("tray-open-folder", "Abrir carpeta de descargas"),
+    ("tray-overlay", "Superposición de progreso"),
+    ("tray-quit", "Salir"),
+    ("queued", "Descarga en cola"),
+    ("queue-failed", "No se pudo poner en cola la descarga"),
+    ("clipboard-no-url", "No hay ningún enlace en el portapapeles"),
+    ("quit-title", "Confirmar salida"),
+    ("quit-message", "¿Seguro que quieres salir de U-Download?"),
+    ("yes", "Sí"),
+    ("no", "No"),
+    ("action-open-file", "Abrir archivo"),
+    ("action-show-in-folder", "Mostrar en la carpeta"),
+    ("action-retry", "Reintentar"),
+    ("hint-rate-limited", "El sitio está limitando las solicitudes. Espera un rato, haz menos descargas a la vez o añade cookies."),
+    ("hint-private", "Este vídeo es privado. Añade cookies de una cuenta con acceso a él."),
+    ("hint-age-restricted", "Este vídeo tiene restricción de edad. Añade cookies de una cuenta con sesión iniciada."),
+    ("hint-geo-restricted", "Este vídeo no está disponible en tu región. Reinténtalo con un país de geo-bypass o un proxy."),
+    ("hint-members-only", "Este vídeo es solo para miembros del canal. Añade cookies de una cuenta de miembro."),
+    ("hint-bot-check", "El sitio quiere confirmar que no eres un bot. Importa las cookies de tu navegador o inicia sesión y la descarga se reintentará."),
+    ("login-window-title", "Iniciar sesión"),
+    ("hint-unavailable", "El vídeo se eliminó o el enlace es incorrecto. Comprueba la URL."),
+    ("hint-forbidden", "El sitio rechazó la descarga. Actualiza yt-dlp o elige otro cliente de reproductor de YouTube en la configuración."),
+    ("hint-network", "Falló la conexión. Revisa tu red; la descarga se reanuda sola cuando vuelva."),
+    ("hint-timeout", "La descarga se bloqueó y se detuvo. Reinténtala o aumenta los tiempos de espera en la configuración."),
+];
+
+const FR: Catalog = &[
+    ("notify-started", "Téléchargement démarré"),
+    ("notify-complete", "Téléchargement terminé"),
+    ("notify-failed", "Échec du téléchargement : {title}"),
+    ("notify-batch-finished", "Téléchargements terminés"),
+    ("notify-batch-summary", "{completed} terminés, {failed} échoués, {cancelled} annulés"),
+    ("tray-idle", "Aucun téléchargement en cours"),
+    ("tray-preparing", "Préparation du téléchargement…"),
+    ("tray-downloading", "Téléchargement"),
+    ("tray-progress", "{title} — {percent} % à {speed}"),
+    ("tray-queue-empty", "File d'attente vide"),
+    ("tray-queue", "{active} en cours, {queued} en attente — {percent} % au total"),
+    ("tray-show", "Afficher"),
+    ("tray-pause-all", "Tout mettre en pause"),
+    ("tray-cancel-all", "Tout annuler"),
+    ("tray-open-folder", "Ouvrir le dossier des téléchargements"),
+    ("tray-overlay", "Incrustation de progression"),
+    ("tray-quit", "Quitter"),
+    ("queued", "Téléchargement ajouté"),
+    ("queue-failed", "Impossible d'ajouter le téléchargement"),
+    ("clipboard-no-url", "Aucun lien dans le presse-papiers"),
+    ("quit-title", "Confirmer la fermeture"),
+    ("quit-message", "Voulez-vous vraiment quitter U-Download ?"),
+    ("yes", "Oui"),
+    ("no", "Non"),
+    ("action-open-file", "Ouvrir le fichier"),
+    ("action-show-in-folder", "Afficher dans le dossier"),
+    ("action-retry", "Réessayer"),
+    ("hint-rate-limited", "Le site limite les requêtes. Patientez, lancez moins de téléchargements à la fois ou ajoutez des cookies."),
+    ("hint-private", "Cette vidéo est privée. Ajoutez les cookies d'un compte qui y a accès."),
+    ("hint-age-restricted", "Cette vidéo est soumise à une limite d'âge. Ajoutez les cookies d'un compte connecté."),
+    ("hint-geo-restricted", "Cette vidéo n'est pas disponible dans votre région. Réessayez avec un pays de contournement ou un proxy."),
+    ("hint-members-only", "Cette vidéo est réservée aux membres de la chaîne. Ajoutez les cookies d'un compte membre."),
+    ("hint-bot-check", "Le site veut vérifier que vous n'êtes pas un robot. Importez les cookies de votre navigateur ou connectez-vous, et le téléchargement sera relancé."),
+    ("login-window-title", "Se connecter"),
+    ("hint-unavailable", "La vidéo a été supprimée ou le lien est incorrect. Vérifiez l'URL."),
+    ("hint-forbidden", "Le site a refusé le téléchargement. Mettez yt-dlp à jour ou choisissez un autre client de lecture YouTube dans les paramètres."),
+    ("hint-network", "La connexion a échoué. Vérifiez votre réseau ; le téléchargement reprendra tout seul à son retour."),
+    ("hint-timeout", "Le téléchargement était bloqué et a été arrêté. Réessayez ou augmentez les délais dans les paramètres."),
+];
+
+const DE: Catalog = &[
+    ("notify-started", "Download gestartet"),
+    ("notify-complete", "Download abgeschlossen"),
+    ("notify-failed", "Download fehlgeschlagen: {title}"),
+    ("notify-batch-finished", "Downloads beendet"),
+    ("notify-batch-summary", "{completed} abgeschlossen, {failed} fehlgeschlagen, {cancelled} abgebrochen"),
+    ("tray-idle", "Keine aktiven Downloads"),
+    ("tray-preparing", "Download wird vorbereitet…"),
+    ("tray-downloading", "Wird heruntergeladen"),
+    ("tray-progress", "{title} — {percent} % mit {speed}"),
+    ("tray-queue-empty", "Warteschlange leer"),
+    ("tray-queue", "{active} aktiv, {queued} wartend — {percent} % gesamt"),
+    ("tray-show", "Anzeigen"),
+    ("tray-pause-all", "Alle pausieren"),
+    ("tray-cancel-all", "Alle abbrechen"),
+    ("tray-open-folder", "Download-Ordner öffnen"),
+    ("tray-overlay", "Fortschritts-Overlay"),
+    ("tray-quit", "Beenden"),
+    ("queued", "Download eingereiht"),
+    ("queue-failed", "Download konnte nicht eingereiht werden"),
+    ("clipboard-no-url", "Kein Link in der Zwischenablage"),
+    ("quit-title", "Beenden bestätigen"),
+    ("quit-message", "Möchtest du U-Download wirklich beenden?"),
+    ("yes", "Ja"),
+    ("no", "Nein"),
+    ("action-open-file", "Datei öffnen"),
+    ("action-show-in-folder", "Im Ordner anzeigen"),
+    ("action-retry", "Erneut versuchen"),
+    ("hint-rate-limited", "Die Seite begrenzt Anfragen. Warte eine Weile, lade weniger gleichzeitig herunter oder füge Cookies hinzu."),
+    ("hint-private", "Dieses Video ist privat. Füge Cookies eines Kontos hinzu, das Zugriff darauf hat."),
+    ("hint-age-restricted", "Dieses Video ist altersbeschränkt. Füge Cookies eines angemeldeten Kontos hinzu."),
+    ("hint-geo-restricted", "Dieses Video ist in deiner Region nicht verfügbar. Versuche es mit einem Geo-Bypass-Land oder einem Proxy."),
+    ("hint-members-only", "Dieses Video ist nur für Kanalmitglieder. Füge Cookies eines Mitgliedskontos hinzu."),
+    ("hint-bot-check", "Die Seite möchte bestätigen, dass du kein Bot bist. Importiere die Cookies deines Browsers oder melde dich an, dann wird der Download wiederholt."),
+    ("login-window-title", "Anmelden"),
+    ("hint-unavailable", "Das Video wurde entfernt oder der Link ist falsch. Prüfe die URL."),
+    ("hint-forbidden", "Die Seite hat den Download abgelehnt. Aktualisiere yt-dlp oder wähle in den Einstellungen einen anderen YouTube-Player-Client."),
+    ("hint-network", "Die Verbindung ist fehlgeschlagen. Prüfe dein Netzwerk; der Download startet neu, sobald es wieder da ist."),
+    ("hint-timeout", "Der Download hing und wurde gestoppt. Versuche es erneut oder erhöhe die Zeitlimits in den Einstellungen."),
+];
+
+fn catalog(locale: &str) -> Catalog {
+    match locale {
+        "es" => ES,
+        "fr" => FR,
+        "de" => DE,
+        _ => EN,
+    }
+}
+
+fn lookup(catalog: Catalog, key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, message)| *message)
+}
+
+/// Supported locale for a BCP 47 tag such as `es-MX` or `pt_BR`, by its language subtag
+fn supported(tag: &str) -> Option<&'static str> {
+    let language = tag.trim().split(['-', '_']).next()?.to_lowercase();
+    LOCALES.iter().copied().find(|locale| *locale == language)
+}
+
+pub fn locale() -> &'static str {
+    *LOCALE.read().unwrap()
+}
+
+/// The message for `key` in the current locale
+pub fn t(key: &str) -> String {
+    lookup(catalog(locale()), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// [`t`] with `{name}` placeholders replaced by `args`
+pub fn tf(key: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(t(key), |message, (name, value)| message.replace(&format!("{{{}}}", name), value))
+}
+
+/// Switch backend strings to the frontend's locale; unsupported locales use English.
+/// Returns the locale in use.
+#[tauri::command]
+pub async fn set_locale<R: Runtime>(app: AppHandle<R>, locale: String) -> Result<String, String> {
+    let resolved = supported(&locale).unwrap_or("en");
+    *LOCALE.write().unwrap() = resolved;
+    eprintln!("🌐 Locale set to {} (requested {})", resolved, locale);
+
+    #[cfg(not(target_os = "android"))]
+    crate::tray::relabel(&app);
+    #[cfg(target_os = "android")]
+    let _ = app;
+    Ok(resolved.to_string())
+}
+
+/// Messages for `keys` in the current locale, for the few frontend strings the OS shows, such
+/// as notification buttons
+#[tauri::command]
+pub async fn get_translations(keys: Vec<String>) -> Result<HashMap<String, String>, String> {
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let message = t(&key);
+            (key, message)
+        })
+        .collect())
+}
@@ -2,23 +2,86 @@
 #[cfg(not(target_os = "android"))]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tauri::{AppHandle, Emitter, State, Runtime};
+
+mod android_resume;
+mod api;
+#[cfg(not(target_os = "android"))]
+mod aria2;
+mod bandwidth;
+mod batch;
+mod binary_manager;
+mod bookmarks;
+mod bot_check;
+mod binary_updates;
+mod checksum;
+mod classify;
+mod converter;
+mod crash;
+mod download_manager;
+mod errors;
+mod extra_args;
+mod ffmpeg;
+mod formats;
+mod history;
 #[cfg(not(target_os = "android"))]
-use tauri::menu::{Menu, MenuItem};
+mod hotkey;
+mod i18n;
+mod link_files;
+mod media_tools;
+mod metadata_cache;
+mod monitor;
+mod naming;
+mod network;
+mod nfo;
+mod notifications;
+mod partials;
+mod playlist;
+mod podcast;
+mod power;
+mod premieres;
+mod presets;
+mod queue_transfer;
+mod search;
+mod secrets;
+mod settings;
 #[cfg(not(target_os = "android"))]
-use tauri::tray::TrayIconBuilder;
+mod shell_integration;
+mod stats;
+mod storage;
+mod tags;
 #[cfg(not(target_os = "android"))]
-use tauri::Manager;
-use tauri::{AppHandle, Emitter, State, Window, Runtime};
+mod taskbar;
+mod thumbnails;
+mod transcribe;
+mod transliterate;
 #[cfg(not(target_os = "android"))]
-use tauri_plugin_dialog::DialogExt;
+mod tray;
+mod updater;
+mod windows_paths;
+mod youtube;
+mod yt_dlp_config;
 
-mod binary_manager;
 
+/// Payload of `download-complete`
+#[derive(Debug, Serialize, Clone)]
+struct DownloadComplete {
+    id: String,
+    title: String,
+    /// The main output file, for "Open file"; `None` if the download reported no files
+    path: Option<String>,
+    size_bytes: u64,
+    /// Seconds
+    duration: Option<f64>,
+    files: Vec<history::HistoryFile>,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct DownloadProgress {
+    #[serde(default)]
+    id: String,
     percentage: f64,
     speed: String,
     speed_bytes_per_sec: u64,
@@ -27,6 +90,36 @@ struct DownloadProgress {
     bytes_downloaded: u64,
     total_bytes: u64,
     download_start_time: std::time::SystemTime,
+    #[serde(default)]
+    connections: u32,
+    /// Video title or file name, once known
+    #[serde(default)]
+    title: String,
+    /// Per-entry status when downloading a playlist
+    #[serde(default)]
+    playlist_items: Vec<PlaylistItemProgress>,
+    /// Extension of the file being produced, e.g. "mkv" or "flac"
+    #[serde(default)]
+    extension: String,
+    /// Final paths of the files produced, once the download has finished
+    #[serde(default)]
+    files: Vec<String>,
+    /// Files yt-dlp reported it was writing, while the download runs
+    #[serde(default)]
+    destinations: Vec<String>,
+    /// Why the download failed, when yt-dlp's error was recognised
+    #[serde(default)]
+    failure: Option<errors::DownloadFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PlaylistItemProgress {
+    /// 1-based position among the entries being downloaded
+    position: u32,
+    total: u32,
+    title: String,
+    /// "downloading", "completed" or "failed"
+    status: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,10 +130,37 @@ struct VideoMetadata {
     uploader: String,
     view_count: Option<u64>,
     upload_date: Option<String>,
+    /// yt-dlp's `live_status`: `is_upcoming` for premieres and streams that have not started
+    live_status: Option<String>,
+    /// Announced start of an upcoming premiere or stream, in Unix seconds
+    release_timestamp: Option<i64>,
 }
 
 type ProgressState = Arc<Mutex<DownloadProgress>>;
 
+impl DownloadProgress {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            percentage: 0.0,
+            speed: String::new(),
+            speed_bytes_per_sec: 0,
+            eta: String::new(),
+            status: "idle".to_string(),
+            bytes_downloaded: 0,
+            total_bytes: 0,
+            download_start_time: std::time::SystemTime::now(),
+            connections: 0,
+            title: String::new(),
+            playlist_items: Vec::new(),
+            extension: String::new(),
+            files: Vec::new(),
+            destinations: Vec::new(),
+            failure: None,
+        }
+    }
+}
+
 fn format_speed(bytes_per_sec: u64) -> String {
     if bytes_per_sec == 0 {
         return "Calculating...".to_string();
@@ -154,10 +274,6 @@ fn calculate_eta(bytes_downloaded: u64, total_bytes: u64, speed_bytes_per_sec: u
     }
 }
 
-fn send_download_complete_notification(_filename: &str) -> Result<(), String> { Ok(()) }
-fn send_download_error_notification(_error: &str) -> Result<(), String> { Ok(()) }
-fn send_download_started_notification(_filename: &str) -> Result<(), String> { Ok(()) }
-
 #[tauri::command]
 async fn get_shared_url() -> Result<String, String> {
     #[cfg(target_os = "android")]
@@ -180,6 +296,116 @@ async fn get_shared_url() -> Result<String, String> {
     { Err("unsupported".into()) }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SharedLink {
+    url: String,
+    is_playlist: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct SharedUrls {
+    accepted: Vec<SharedLink>,
+    rejected: Vec<String>,
+}
+
+/// Split shared text into links; share sheets often wrap URLs in prose or send several at once
+fn parse_shared_text(text: &str) -> SharedUrls {
+    let mut result = SharedUrls::default();
+
+    for token in text.split_whitespace() {
+        let candidate = token.trim_matches(|c: char| matches!(c, '"' | '\'' | '<' | '>' | '(' | ')' | ',' | ';'));
+        if !candidate.contains("://") {
+            continue;
+        }
+
+        match url::Url::parse(candidate) {
+            Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {
+                let normalized = parsed.to_string();
+                if result.accepted.iter().any(|link| link.url == normalized) {
+                    continue;
+                }
+                let is_playlist = parsed.path().starts_with("/playlist")
+                    || parsed.query_pairs().any(|(key, _)| key == "list");
+                result.accepted.push(SharedLink { url: normalized, is_playlist });
+            }
+            _ => result.rejected.push(candidate.to_string()),
+        }
+    }
+
+    result
+}
+
+#[tauri::command]
+async fn get_shared_urls() -> Result<SharedUrls, String> {
+    #[cfg(target_os = "android")]
+    {
+        use std::fs;
+        use std::path::PathBuf;
+        let base = std::env::var("UDL_FILES_DIR").unwrap_or_default();
+        if base.is_empty() { return Err("not-android".into()); }
+
+        // The share activity appends one entry per intent to shared_urls.txt; older
+        // builds only wrote the single-link shared_url.txt, so drain both.
+        let mut text = String::new();
+        for name in ["shared_urls.txt", "shared_url.txt"] {
+            let path = PathBuf::from(&base).join(name);
+            if let Ok(s) = fs::read_to_string(&path) {
+                let _ = fs::remove_file(&path);
+                text.push_str(&s);
+                text.push('\n');
+            }
+        }
+
+        let shared = parse_shared_text(&text);
+        if shared.accepted.is_empty() && shared.rejected.is_empty() {
+            return Err("empty".into());
+        }
+        eprintln!("Share intent: {} accepted, {} rejected", shared.accepted.len(), shared.rejected.len());
+        Ok(shared)
+    }
+    #[cfg(not(target_os = "android"))]
+    { Err("unsupported".into()) }
+}
+
+#[tauri::command]
+async fn get_interrupted_downloads() -> Result<Vec<android_resume::InflightDownload>, String> {
+    #[cfg(target_os = "android")]
+    { Ok(android_resume::interrupted()) }
+    #[cfg(not(target_os = "android"))]
+    { Err("unsupported".into()) }
+}
+
+#[tauri::command]
+async fn resume_interrupted_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
+    id: String,
+) -> Result<String, String> {
+    let record = android_resume::find(&id).ok_or_else(|| format!("No interrupted download with id {}", id))?;
+    // The regular download path detects the partial file and continues it with a Range request
+    start_download(
+        app_handle,
+        manager,
+        record.url,
+        Some(record.download_type),
+        Some(record.quality),
+        Some(record.output_folder),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn discard_interrupted_download(id: String) -> Result<(), String> {
+    if let Some(record) = android_resume::find(&id) {
+        let _ = std::fs::remove_file(&record.temp_path);
+    }
+    android_resume::remove(&id)
+}
+
 #[tauri::command]
 async fn get_android_videos_dir() -> Result<String, String> {
     #[cfg(target_os = "android")]
@@ -198,17 +424,35 @@ async fn get_android_videos_dir() -> Result<String, String> {
     { Err("unsupported".into()) }
 }
 
-#[tauri::command]
-async fn get_video_metadata<R: Runtime>(app_handle: AppHandle<R>, url: String) -> Result<VideoMetadata, String> {
-    let paths = binary_manager::resolve_paths(&app_handle)?;
+/// Full `--dump-json` output for a URL, from the metadata cache when it is fresh
+async fn fetch_metadata_json<R: Runtime>(app_handle: &AppHandle<R>, url: &str) -> Result<serde_json::Value, String> {
+    if let Some(metadata) = metadata_cache::get(app_handle, url) {
+        return Ok(metadata);
+    }
+    let metadata = fetch_fresh_metadata_json(app_handle, url).await?;
+    metadata_cache::insert(app_handle, url, &metadata);
+    Ok(metadata)
+}
+
+/// `fetch_metadata_json` without the cache, for pages that change such as upcoming streams
+async fn fetch_fresh_metadata_json<R: Runtime>(app_handle: &AppHandle<R>, url: &str) -> Result<serde_json::Value, String> {
+    let paths = binary_manager::resolve_paths(app_handle)?;
     binary_manager::ensure_executable(&paths)?;
 
-    // Get video information using bundled yt-dlp --dump-json
-    let output = Command::new(&paths.yt_dlp)
+    // Get video information using bundled yt-dlp --dump-json; async so batch lookups can overlap
+    let mut cmd = tokio::process::Command::from(binary_manager::tool_command(&paths.yt_dlp));
+    if let Some(config) = yt_dlp_config::config_location(app_handle) {
+        cmd.arg("--config-location").arg(config);
+    }
+    let output = cmd
         .arg("--dump-json")
         .arg("--no-download")
-        .arg(&url)
+        // Upcoming premieres and streams have no formats yet but still report their start time
+        .arg("--ignore-no-formats-error")
+        .arg("--")
+        .arg(url)
         .output()
+        .await
         .map_err(|e| format!("Failed to get video info: {}", e))?;
 
     if !output.status.success() {
@@ -217,8 +461,10 @@ async fn get_video_metadata<R: Runtime>(app_handle: AppHandle<R>, url: String) -
     }
 
     let json_output = String::from_utf8_lossy(&output.stdout);
-    let metadata: serde_json::Value = serde_json::from_str(&json_output)
-        .map_err(|e| format!("Failed to parse video metadata: {}", e))?;
+    serde_json::from_str(&json_output).map_err(|e| format!("Failed to parse video metadata: {}", e))
+}
+
+fn parse_video_metadata(metadata: &serde_json::Value) -> VideoMetadata {
 
     let title = metadata["title"]
         .as_str()
@@ -238,14 +484,39 @@ async fn get_video_metadata<R: Runtime>(app_handle: AppHandle<R>, url: String) -
 
     let upload_date = metadata["upload_date"].as_str().map(|s| s.to_string());
 
-    Ok(VideoMetadata {
+    let live_status = metadata["live_status"].as_str().map(|s| s.to_string());
+
+    let release_timestamp = metadata["release_timestamp"].as_i64();
+
+    VideoMetadata {
         title,
         duration,
         thumbnail_url,
         uploader,
         view_count,
         upload_date,
-    })
+        live_status,
+        release_timestamp,
+    }
+}
+
+#[tauri::command]
+async fn get_video_metadata<R: Runtime>(app_handle: AppHandle<R>, url: String) -> Result<VideoMetadata, String> {
+    let metadata = fetch_metadata_json(&app_handle, &url).await?;
+    Ok(parse_video_metadata(&metadata))
+}
+
+/// Expected download size for the chosen type and quality, before starting
+#[tauri::command]
+async fn estimate_download_size<R: Runtime>(
+    app_handle: AppHandle<R>,
+    url: String,
+    downloadType: Option<String>,
+    quality: Option<String>,
+) -> Result<Option<formats::SizeEstimate>, String> {
+    let (download_type, quality) = settings::current(&app_handle).resolve_format(&url, downloadType, quality);
+    let metadata = fetch_metadata_json(&app_handle, &url).await?;
+    Ok(formats::estimate_size(&metadata, &download_type, &quality))
 }
 
 // Android-specific HTTP downloader removed; use unified yt-dlp/ffmpeg flow on all platforms.
@@ -255,7 +526,7 @@ async fn check_ffmpeg<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, St
     let paths = binary_manager::resolve_paths(&app_handle)?;
     binary_manager::ensure_executable(&paths)?;
 
-    match Command::new(&paths.ffmpeg).arg("-version").output() {
+    match binary_manager::tool_command(&paths.ffmpeg).arg("-version").output() {
         Ok(output) => {
             let version = String::from_utf8_lossy(&output.stdout);
             Ok(format!(
@@ -301,62 +572,197 @@ async fn select_output_folder<R: Runtime>(app_handle: AppHandle<R>) -> Result<St
 
 #[tauri::command]
 async fn start_download<R: Runtime>(
-    window: Window<R>,
-    progress_state: State<'_, ProgressState>,
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
     url: String,
-    downloadType: String,
-    quality: String,
-    outputFolder: String,
+    downloadType: Option<String>,
+    quality: Option<String>,
+    outputFolder: Option<String>,
     startTime: Option<f64>,
     endTime: Option<f64>,
-) -> Result<(), String> {
-    let window_clone = window.clone();
-    let progress_arc = progress_state.inner().clone();
-    let url_clone = url.clone();
-    let download_type_clone = downloadType.clone();
-    let quality_clone = quality.clone();
-    let output_folder_clone = outputFolder.clone();
-    let start_time_clone = startTime;
-    let end_time_clone = endTime;
+    playlistItems: Option<String>,
+    options: Option<download_manager::DownloadOptions>,
+) -> Result<String, String> {
+    let mut options = options.unwrap_or_default();
+    if let Some(items) = playlistItems.filter(|items| !items.trim().is_empty()) {
+        options.playlist_items = Some(items);
+    }
+    options.validate()?;
+    if downloadType.is_none() {
+        classify::mark_unchosen(&app_handle, &mut options);
+    }
 
-    tokio::spawn(async move {
-        let result = perform_download(
-            &window_clone,
-            progress_arc.clone(),
-            &url_clone,
-            &download_type_clone,
-            &quality_clone,
-            &output_folder_clone,
-            start_time_clone,
-            end_time_clone,
-        )
-        .await;
+    // Anything the caller leaves out comes from the site preset for this URL, then the general defaults
+    let current_settings = settings::current(&app_handle);
+    let auto_quality = quality.as_deref() == Some("auto");
+    let (download_type, mut quality) =
+        current_settings.resolve_format(&url, downloadType, quality.filter(|_| !auto_quality));
+    // "auto" picks the highest quality the measured connection downloads in real time
+    if auto_quality {
+        quality = bandwidth::suggest(&app_handle, &url, &download_type, None).await?.quality;
+    }
+    let output_folder = current_settings.resolve_output_folder(outputFolder, &download_type, None)?;
+    let output_folder = storage::validate_output_folder(&app_handle, &output_folder)?;
+
+    let id = manager.lock().unwrap().enqueue(download_manager::DownloadRequest {
+        url,
+        download_type,
+        quality,
+        output_folder,
+        start_time: startTime,
+        end_time: endTime,
+        filename: None,
+        options,
+    });
 
-        match result {
-            Ok(filename) => {
-                let mut progress = progress_arc.lock().unwrap();
-                progress.status = "completed".to_string();
-                progress.percentage = 100.0;
-                let progress_copy = progress.clone();
-                let _ = window_clone.emit("download-progress", progress_copy);
-                
-                // Send completion notification
-                let _ = send_download_complete_notification(&filename);
-                let _ = window_clone.emit("download-complete", filename);
-            }
-            Err(e) => {
-                let mut progress = progress_arc.lock().unwrap();
-                progress.status = "error".to_string();
-                eprintln!("Download error: {}", e);
-                
-                // Send error notification
-                let _ = send_download_error_notification(&e);
-                let _ = window_clone.emit("download-error", format!("Download failed: {}", e));
+    download_manager::schedule(&app_handle);
+    Ok(id)
+}
+
+/// Emit the final progress/complete/error events for a finished download task
+fn report_download_result<R: Runtime>(
+    app: &AppHandle<R>,
+    progress_arc: &ProgressState,
+    result: Result<DownloadComplete, String>,
+) {
+    match result {
+        Ok(complete) => {
+            let mut progress = progress_arc.lock().unwrap();
+            progress.status = "completed".to_string();
+            progress.percentage = 100.0;
+            let progress_copy = progress.clone();
+            let _ = app.emit("download-progress", progress_copy);
+            let _ = app.emit("download-complete", complete);
+        }
+        Err(e) => {
+            let mut progress = progress_arc.lock().unwrap();
+            progress.status = "error".to_string();
+            eprintln!("Download error: {}", e);
+            let event = errors::DownloadErrorEvent::new(&progress.id, &e, progress.failure.as_ref());
+            let _ = app.emit("download-error", event);
+        }
+    }
+}
+
+#[tauri::command]
+async fn start_direct_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
+    url: String,
+    output_folder: String,
+    filename: Option<String>,
+) -> Result<String, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, manager, url, output_folder, filename);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let is_metalink = aria2::is_metalink_source(&url);
+        if !(is_metalink && std::path::Path::new(&url).is_file()) {
+            let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+            if !matches!(parsed.scheme(), "http" | "https" | "ftp" | "sftp") {
+                return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
             }
         }
-    });
+        let output_folder = storage::validate_output_folder(&app_handle, &output_folder)?;
+        let filename = filename
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| storage::validate_file_name(&name))
+            .transpose()?;
+
+        let id = manager.lock().unwrap().enqueue(download_manager::DownloadRequest {
+            url,
+            download_type: if is_metalink { "metalink" } else { "direct" }.to_string(),
+            quality: String::new(),
+            output_folder,
+            start_time: None,
+            end_time: None,
+            filename,
+            options: Default::default(),
+        });
 
-    Ok(())
+        download_manager::schedule(&app_handle);
+        Ok(id)
+    }
+}
+
+/// aria2 gids of a direct/metalink download
+#[cfg(not(target_os = "android"))]
+fn direct_download_gids(manager: &download_manager::DownloadManagerState, id: &str) -> Result<Vec<String>, String> {
+    let manager = manager.lock().unwrap();
+    let job = manager.job(id).ok_or_else(|| format!("Unknown download id: {}", id))?;
+    if job.gids.is_empty() {
+        return Err(format!("Download {} is not an active aria2 transfer", id));
+    }
+    Ok(job.gids.clone())
+}
+
+#[tauri::command]
+async fn pause_direct_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
+    id: String,
+) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, manager, id);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let gids = direct_download_gids(&manager, &id)?;
+        let rpc = aria2::rpc(&app_handle).await?;
+        for gid in gids {
+            rpc.pause(&gid).await?;
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+async fn resume_direct_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
+    id: String,
+) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, manager, id);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let gids = direct_download_gids(&manager, &id)?;
+        let rpc = aria2::rpc(&app_handle).await?;
+        for gid in gids {
+            rpc.unpause(&gid).await?;
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+async fn cancel_direct_download<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, download_manager::DownloadManagerState>,
+    id: String,
+) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app_handle, manager, id);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let gids = direct_download_gids(&manager, &id)?;
+        let rpc = aria2::rpc(&app_handle).await?;
+        for gid in gids {
+            rpc.remove(&gid).await?;
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -366,7 +772,7 @@ async fn test_dependencies<R: Runtime>(app_handle: AppHandle<R>) -> Result<Strin
     let mut results = Vec::new();
 
     // Test yt-dlp (bundled)
-    match Command::new(&paths.yt_dlp).arg("--version").output() {
+    match binary_manager::tool_command(&paths.yt_dlp).arg("--version").output() {
         Ok(output) => {
             let version = String::from_utf8_lossy(&output.stdout);
             results.push(format!("✅ yt-dlp: {}", version.trim()));
@@ -377,7 +783,7 @@ async fn test_dependencies<R: Runtime>(app_handle: AppHandle<R>) -> Result<Strin
     }
 
     // Test aria2c (bundled)
-    match Command::new(&paths.aria2c).arg("--version").output() {
+    match binary_manager::tool_command(&paths.aria2c).arg("--version").output() {
         Ok(output) => {
             let version = String::from_utf8_lossy(&output.stdout);
             results.push(format!(
@@ -394,19 +800,23 @@ async fn test_dependencies<R: Runtime>(app_handle: AppHandle<R>) -> Result<Strin
 }
 
 async fn perform_download<R: Runtime>(
-    window: &Window<R>,
+    app: &AppHandle<R>,
     progress_state: ProgressState,
-    url: &str,
-    download_type: &str,
-    quality: &str,
-    output_folder: &str,
-    start_time: Option<f64>,
-    end_time: Option<f64>,
+    request: &download_manager::DownloadRequest,
+    cancel: &download_manager::CancelFlag,
 ) -> Result<String, String> {
+    let url = request.url.as_str();
+    let download_type = request.download_type.as_str();
+    let quality = request.quality.as_str();
+    let output_folder = request.output_folder.as_str();
+    let start_time = request.start_time;
+    let end_time = request.end_time;
+
     #[cfg(target_os = "android")]
     {
+        let _ = cancel;
         return perform_download_android(
-            window,
+            app,
             progress_state,
             url,
             download_type,
@@ -421,12 +831,12 @@ async fn perform_download<R: Runtime>(
     #[cfg(not(target_os = "android"))]
     {
         // Unified flow for desktop platforms
-        let app_handle = window.app_handle();
-    let paths = binary_manager::resolve_paths(&app_handle)?;
+        let app_handle = app;
+    let paths = binary_manager::resolve_paths(app_handle)?;
     binary_manager::ensure_executable(&paths)?;
 
     // First, test if yt-dlp is available
-    match Command::new(&paths.yt_dlp).arg("--version").output() {
+    match binary_manager::tool_command(&paths.yt_dlp).arg("--version").output() {
         Ok(output) => {
             let version = String::from_utf8_lossy(&output.stdout);
             eprintln!("yt-dlp version: {}", version.trim());
@@ -439,7 +849,7 @@ async fn perform_download<R: Runtime>(
     // Test if aria2c is available (skip on Android)
     #[cfg(not(target_os = "android"))]
     {
-        match Command::new(&paths.aria2c).arg("--version").output() {
+        match binary_manager::tool_command(&paths.aria2c).arg("--version").output() {
             Ok(output) => {
                 let version = String::from_utf8_lossy(&output.stdout);
                 eprintln!(
@@ -456,7 +866,7 @@ async fn perform_download<R: Runtime>(
     // Check if FFmpeg is available for trimming
     let trimming_enabled = start_time.is_some() || end_time.is_some();
     if trimming_enabled {
-        match Command::new(&paths.ffmpeg).arg("-version").output() {
+        match binary_manager::tool_command(&paths.ffmpeg).arg("-version").output() {
             Ok(_) => {
                 eprintln!("FFmpeg is available for trimming");
             }
@@ -466,84 +876,145 @@ async fn perform_download<R: Runtime>(
         }
     }
 
-    let mut cmd = Command::new(&paths.yt_dlp);
-    // Ensure yt-dlp can find bundled aria2c and ffmpeg
-    binary_manager::augment_path_env(&mut cmd, &paths.dir);
+    // The PATH it gets finds the bundled aria2c and ffmpeg
+    let mut cmd = binary_manager::tool_command(&paths.yt_dlp);
 
     // Basic arguments for better quality and performance
+    // aria2c is not bundled on Android, so everything uses yt-dlp's own downloader there
     #[cfg(not(target_os = "android"))]
     {
-        cmd.arg("--external-downloader")
-            .arg("aria2c")
-            .arg("--external-downloader-args")
-            .arg("-x 16 -s 16 -k 1M");
+        let current = settings::current(app_handle);
+        let mut downloader_args = current.aria2.external_downloader_args();
+        // --limit-rate only reaches yt-dlp's own downloader; aria2c needs the cap itself
+        if let Some(limit) = request.options.speed_limit.filter(|limit| *limit > 0) {
+            downloader_args.push_str(&format!(" --max-download-limit={}", limit));
+        }
+        if !request.options.native_downloader {
+            cmd.args(current.downloaders.yt_dlp_args(&downloader_args));
+        }
     }
     cmd.arg("--progress")
         .arg("--newline")
-        .arg("--merge-output-format")
-        .arg("mp4")
         .arg("--prefer-free-formats")
         .arg("--ffmpeg-location")
         .arg(&paths.ffmpeg);
+    let concurrent_fragments = settings::current(app_handle).general.concurrent_fragments;
+    if concurrent_fragments > 1 {
+        cmd.arg("--concurrent-fragments").arg(concurrent_fragments.to_string());
+    }
+
+    let video_settings = settings::current(app_handle).video;
+    let codec = request.options.codec.unwrap_or(video_settings.codec_preference);
+    let container = request.options.container.unwrap_or(video_settings.container);
+    let output_ext = if formats::is_audio(download_type) { download_type } else { container.ext() };
+    progress_state.lock().unwrap().extension = output_ext.to_string();
 
     // Format selection based on type and quality
     match download_type {
-        "mp3" => {
-            cmd.arg("-x")
-                .arg("--audio-format")
-                .arg("mp3")
-                .arg("--audio-quality")
-                .arg("192K");
+        audio if formats::is_audio(audio) => {
+            let audio_quality = request
+                .options
+                .audio_quality
+                .clone()
+                .unwrap_or_else(|| settings::current(app_handle).audio.quality);
+            cmd.args(formats::audio_format_args(audio, &audio_quality)?);
         }
         "mp4" => {
-            // Improved format selection for better video quality
-            let format_selector = match quality {
-                "360" => "bestvideo[height<=360]+bestaudio/best[height<=360]",
-                "480" => "bestvideo[height<=480]+bestaudio/best[height<=480]",
-                "720" => "bestvideo[height<=720]+bestaudio/best[height<=720]",
-                "1080" => "bestvideo[height<=1080]+bestaudio/best[height<=1080]",
-                "best" => "bestvideo+bestaudio/best",
-                _ => "bestvideo+bestaudio/best",
-            };
-            cmd.arg("-f").arg(format_selector);
+            cmd.args(formats::video_format_args(quality, codec, container)?);
         }
         _ => return Err("Invalid download type".to_string()),
     }
 
+    if let Some(config) = yt_dlp_config::config_location(app_handle) {
+        cmd.arg("--config-location").arg(config);
+    }
+    // Before the user's own extractor args, so a `youtube:` entry there takes precedence
+    if let Some(player_client) = youtube::extractor_args(app_handle, request) {
+        cmd.arg("--extractor-args").arg(player_client);
+    }
+    for extractor_args in settings::current(app_handle).advanced.extractor_args {
+        cmd.arg("--extractor-args").arg(extractor_args.trim());
+    }
+    cmd.args(request.options.yt_dlp_args());
+
+    // Cookies come out of the keychain into a private temp file that lives until yt-dlp exits
+    let _cookie_file = match secrets::get(secrets::COOKIES) {
+        Ok(Some(cookies)) => {
+            let file = secrets::TempSecretFile::write(secrets::COOKIES, &cookies)?;
+            cmd.arg("--cookies").arg(&file.path);
+            Some(file)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("⚠️ {}", e);
+            None
+        }
+    };
+
     // For trimming, we'll download the full video first, then trim with FFmpeg
     // Set a temporary output pattern that we can identify later
-    let temp_output_pattern = if trimming_enabled {
-        format!("{}/%(title)s_temp.%(ext)s", output_folder)
-    } else {
-        format!("{}/%(title)s.%(ext)s", output_folder)
-    };
+    let temp_output_pattern = request.options.output_pattern(output_folder, trimming_enabled);
 
     cmd.arg("-o").arg(&temp_output_pattern);
 
-    cmd.arg(url);
+    let mut filename_settings = settings::current(app_handle).filenames;
+    // Windows fails on paths longer than MAX_PATH and on device names such as CON
+    if cfg!(windows) {
+        let budget = windows_paths::name_budget(output_folder, &temp_output_pattern);
+        filename_settings.max_length = Some(filename_settings.max_length.map_or(budget, |length| length.min(budget)));
+        cmd.args(windows_paths::reserved_name_args());
+    }
+    cmd.args(filename_settings.yt_dlp_args());
+
+    // yt-dlp writes the final path of every file it produces here, for post-processing
+    let printed_paths = secrets::TempSecretFile::write("paths", "")?;
+    cmd.arg("--print-to-file").arg("after_move:filepath").arg(&printed_paths.path);
+
+    // The full info dict of each file, one JSON object per line, for the NFO sidecars
+    let write_nfo = !formats::is_audio(download_type)
+        && request.options.write_nfo.unwrap_or(settings::current(app_handle).video.write_nfo);
+    let printed_info = secrets::TempSecretFile::write("info", "")?;
+    if write_nfo {
+        cmd.arg("--print-to-file").arg("after_move:%()j").arg(&printed_info.path);
+    }
+
+    cmd.args(request.options.extra_yt_dlp_args());
+    // Everything after `--` is a URL, so a crafted one cannot be read as an option
+    cmd.arg("--").arg(url);
 
     // Log the full command for debugging
     eprintln!("Executing command: {:?}", cmd);
 
-    let mut child = cmd
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
+    cmd.stdout(std::process::Stdio::piped()).stderr(std::process::Stdio::piped());
+    let (mut child, _child_guard) = download_manager::spawn_tracked(&mut cmd)
         .map_err(|e| {
             format!(
                 "Failed to start bundled yt-dlp: {}. This is an application error; please reinstall or report a bug.",
                 e
             )
         })?;
+    let job_id = progress_state.lock().unwrap().id.clone();
+    download_manager::set_job_pid(app, &job_id, Some(child.id()));
 
     // Get video title for notification
-    let video_title = match get_video_metadata(app_handle.clone(), url.to_string()).await {
-        Ok(metadata) => metadata.title,
-        Err(_) => "Unknown Video".to_string(),
+    let (video_title, size_estimate) = match fetch_metadata_json(app_handle, url).await {
+        Ok(metadata) => (
+            parse_video_metadata(&metadata).title,
+            formats::estimate_size(&metadata, download_type, quality),
+        ),
+        Err(_) => ("Unknown Video".to_string(), None),
     };
 
-    // Send download start notification
-    let _ = send_download_started_notification(&video_title);
+    progress_state.lock().unwrap().title = video_title.clone();
+
+    notifications::notify(
+        app,
+        notifications::NotificationEvent::Started,
+        &i18n::t("notify-started"),
+        &video_title,
+        Some(url),
+        None,
+    );
 
     // Initialize download start time and periodic update task
     {
@@ -552,12 +1023,13 @@ async fn perform_download<R: Runtime>(
         progress.status = "downloading".to_string();
         progress.percentage = 0.0;
         progress.bytes_downloaded = 0;
-        progress.total_bytes = 0;
+        // Seed from format metadata so early ETAs have a real total; yt-dlp's own figures replace it
+        progress.total_bytes = size_estimate.map_or(0, |estimate| estimate.bytes);
     }
 
     // Start periodic progress update task
     let periodic_progress_state = progress_state.clone();
-    let periodic_window = window.clone();
+    let periodic_app = app.clone();
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
         let mut last_percentage = 0.0;
@@ -610,7 +1082,7 @@ async fn perform_download<R: Runtime>(
             };
             
             // Send periodic update to frontend
-            let _ = periodic_window.emit("download-progress", should_update);
+            let _ = periodic_app.emit("download-progress", should_update);
         }
     });
 
@@ -621,6 +1093,8 @@ async fn perform_download<R: Runtime>(
 
         // Regex patterns for different output formats
         let dl_status_regex = Regex::new(r"\[DL:([\d.]+)([GMK]?)iB\]").unwrap(); // aria2c download status
+        let playlist_item_regex = Regex::new(r"\[download\] Downloading (?:item|video) (\d+) of (\d+)").unwrap();
+        let destination_regex = Regex::new(r"\[download\] Destination: (.+)$").unwrap();
         let fragment_regex = Regex::new(r"\[hlsnative\]\s+Total fragments:\s+(\d+)").unwrap(); // HLS fragment count
         let standard_progress_patterns = vec![
             // Standard yt-dlp progress patterns
@@ -638,11 +1112,52 @@ async fn perform_download<R: Runtime>(
         let mut accumulated_size = 0u64;
 
         for line in reader.lines() {
+            if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                // Killing only yt-dlp would leave its ffmpeg/aria2c children running
+                download_manager::kill_tree(child.id());
+                let _ = child.wait();
+                return Err("Download was cancelled".to_string());
+            }
             if let Ok(line) = line {
                 eprintln!("yt-dlp output: {}", line);
                 let now = std::time::SystemTime::now();
                 let mut progress_updated = false;
 
+                // Playlist entries: a new "Downloading item N of M" means the previous one finished
+                if let Some(captures) = playlist_item_regex.captures(&line) {
+                    let position: u32 = captures[1].parse().unwrap_or(0);
+                    let total: u32 = captures[2].parse().unwrap_or(0);
+                    let mut progress = progress_state.lock().unwrap();
+                    for item in progress.playlist_items.iter_mut().filter(|i| i.status == "downloading") {
+                        item.status = "completed".to_string();
+                    }
+                    progress.playlist_items.push(PlaylistItemProgress {
+                        position,
+                        total,
+                        title: String::new(),
+                        status: "downloading".to_string(),
+                    });
+                    let _ = app.emit("download-progress", progress.clone());
+                }
+                if let Some(captures) = destination_regex.captures(&line) {
+                    progress_state.lock().unwrap().destinations.push(captures[1].trim().to_string());
+                    // Saved so a crash or quit mid-file leaves a resumable record
+                    download_manager::persist(app);
+                    let mut progress = progress_state.lock().unwrap();
+                    if let Some(item) = progress.playlist_items.last_mut().filter(|i| i.title.is_empty()) {
+                        let stem = std::path::Path::new(captures[1].trim())
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        // Separate video/audio streams are named "<title>.f137" until merged
+                        let stem = match stem.rsplit_once(".f") {
+                            Some((base, format_id)) if format_id.chars().all(|c| c.is_ascii_digit()) => base.to_string(),
+                            _ => stem,
+                        };
+                        item.title = stem.trim_end_matches("_temp").to_string();
+                    }
+                }
+
                 // 1. Check for total fragments count (HLS streams)
                 if let Some(captures) = fragment_regex.captures(&line) {
                     if let Ok(fragments) = captures.get(1).unwrap().as_str().parse::<u32>() {
@@ -690,9 +1205,11 @@ async fn perform_download<R: Runtime>(
                         
                         (progress.min(100.0), speed)
                     } else {
-                        // Estimate progress based on download size (rough estimation)
-                        // Assume an average video is around 100MB to 1GB
-                        let estimated_total = 500_000_000u64; // 500MB estimate
+                        // Estimate progress from the metadata size estimate, or assume a 500MB video without one
+                        let estimated_total = match progress_state.lock().unwrap().total_bytes {
+                            0 => 500_000_000u64,
+                            total => total,
+                        };
                         let progress = ((accumulated_size as f64 / estimated_total as f64) * 100.0).min(95.0);
                         
                         let elapsed = now.duration_since({
@@ -737,7 +1254,7 @@ async fn perform_download<R: Runtime>(
                         progress.clone()
                     };
 
-                    let _ = window.emit("download-progress", progress_copy);
+                    let _ = app.emit("download-progress", progress_copy);
                     progress_updated = true;
                 }
 
@@ -810,7 +1327,7 @@ async fn perform_download<R: Runtime>(
                                 progress.clone()
                             };
 
-                            let _ = window.emit("download-progress", progress_copy);
+                            let _ = app.emit("download-progress", progress_copy);
                             progress_updated = true;
                             break;
                         }
@@ -843,7 +1360,7 @@ async fn perform_download<R: Runtime>(
                                 
                                 let progress_copy = progress.clone();
                                 drop(progress);
-                                let _ = window.emit("download-progress", progress_copy);
+                                let _ = app.emit("download-progress", progress_copy);
                             }
                         }
                     }
@@ -863,66 +1380,190 @@ async fn perform_download<R: Runtime>(
         String::new()
     };
 
-    let output = child.wait().map_err(|e| format!("Process error: {}", e))?;
+    let output = child.wait();
+    // The pid may be reused once the child is reaped; post-processing registers its own
+    download_manager::set_job_pid(app, &job_id, None);
+    let output = output.map_err(|e| format!("Process error: {}", e))?;
+    // yt-dlp exits with 101 when it stops because --max-downloads was reached
+    let succeeded = output.success() || (request.options.max_downloads.is_some() && output.code() == Some(101));
+
+    {
+        let final_status = if succeeded { "completed" } else { "failed" };
+        let mut progress = progress_state.lock().unwrap();
+        for item in progress.playlist_items.iter_mut().filter(|i| i.status == "downloading") {
+            item.status = final_status.to_string();
+        }
+    }
+
+    if succeeded {
+        let mut output_files: Vec<std::path::PathBuf> = std::fs::read_to_string(&printed_paths.path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(std::path::PathBuf::from)
+            .collect();
+        // Side-car subtitles are named after the file as yt-dlp wrote it, before any trimming
+        let downloaded_files = output_files.clone();
 
-    if output.success() {
         // If trimming is enabled, perform FFmpeg trimming
         if trimming_enabled {
-            perform_trimming(window, progress_state, output_folder, start_time, end_time, paths.ffmpeg.clone()).await?;
+            if output_files.is_empty() {
+                return Err("yt-dlp did not report the downloaded file, so it cannot be trimmed".to_string());
+            }
+            for file in output_files.iter_mut() {
+                *file = perform_trimming(app, progress_state.clone(), file, start_time, end_time, paths.ffmpeg.clone()).await?;
+            }
+        }
+
+        let burn_in = request.options.subtitles.as_ref().and_then(|subs| Some((subs, subs.burn_in.as_ref()?)));
+        if let Some((subs, style)) = burn_in.filter(|_| !formats::is_audio(download_type)) {
+            let offset = if trimming_enabled { start_time.unwrap_or(0.0) } else { 0.0 };
+            for (file, downloaded) in output_files.iter_mut().zip(&downloaded_files) {
+                let Some(source) = subs.burn_in_source(downloaded) else {
+                    eprintln!("⚠️  No subtitles found to burn into {}", file.display());
+                    continue;
+                };
+                let (ffmpeg_path, video, style) = (paths.ffmpeg.clone(), file.clone(), *style);
+                let on_progress = step_progress(app, &progress_state, "burning_subtitles");
+                *file = run_step(app, &progress_state, "Subtitle burn-in", move || {
+                    ffmpeg::burn_subtitles(&ffmpeg_path, &video, &source, offset, &style, on_progress)
+                })
+                .await?;
+            }
+        }
+
+        let current_settings = settings::current(app);
+        let transcode = request.options.transcode.or(current_settings.video.transcode_preset);
+        if let Some(preset) = transcode.filter(|_| !formats::is_audio(download_type)) {
+            for file in output_files.iter_mut() {
+                let (ffmpeg_path, video) = (paths.ffmpeg.clone(), file.clone());
+                let on_progress = step_progress(app, &progress_state, "transcoding");
+                *file = run_step(app, &progress_state, "Transcoding", move || {
+                    ffmpeg::transcode(&ffmpeg_path, &video, preset, on_progress)
+                })
+                .await?;
+            }
+        }
+
+        let audio_settings = current_settings.audio;
+        if request.options.normalize_loudness.unwrap_or(audio_settings.normalize_loudness) {
+            let bitrate = request.options.audio_quality.clone().unwrap_or(audio_settings.quality);
+            let target = audio_settings.loudness_target;
+            for file in &output_files {
+                eprintln!("🔊 Normalizing loudness of {}", file.display());
+                let (ffmpeg_path, file, bitrate) = (paths.ffmpeg.clone(), file.clone(), bitrate.clone());
+                let on_progress = step_progress(app, &progress_state, "normalizing");
+                run_step(app, &progress_state, "Loudness normalization", move || {
+                    ffmpeg::normalize_loudness(&ffmpeg_path, &file, target, &bitrate, on_progress)
+                })
+                .await?;
+            }
         }
+
+        if let Some(transcription) = &request.options.transcribe {
+            for file in &output_files {
+                let (transcribe_app, media, transcription) = (app.clone(), file.clone(), transcription.clone());
+                let on_progress = step_progress(app, &progress_state, "transcribing");
+                let transcript = run_step(app, &progress_state, "Transcription", move || {
+                    transcribe::transcribe(&transcribe_app, &media, &transcription, on_progress)
+                })
+                .await?;
+                eprintln!("📝 Wrote transcript {}", transcript.display());
+            }
+        }
+
+        if write_nfo {
+            let infos = std::fs::read_to_string(&printed_info.path).unwrap_or_default();
+            let infos = infos.lines().filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok());
+            for (file, info) in output_files.iter().zip(infos) {
+                match nfo::write(file, &info) {
+                    Ok(path) => eprintln!("📝 Wrote {}", path.display()),
+                    Err(e) => eprintln!("⚠️  {}", e),
+                }
+            }
+        }
+
+        if current_settings.filenames.transliterate {
+            for file in output_files.iter_mut() {
+                match transliterate::rename(file) {
+                    Ok(renamed) => *file = renamed,
+                    Err(e) => eprintln!("⚠️  {}", e),
+                }
+            }
+        }
+
+        progress_state.lock().unwrap().files =
+            output_files.iter().map(|file| file.to_string_lossy().to_string()).collect();
         Ok(video_title)
     } else {
         let exit_code = output.code().unwrap_or(-1);
-        let error_msg = if !stderr_output.is_empty() {
-            format!(
-                "yt-dlp failed (exit code {}): {}",
-                exit_code,
-                stderr_output.trim()
-            )
-        } else {
-            format!("yt-dlp failed with exit code {}", exit_code)
-        };
-        eprintln!("Download failed: {}", error_msg);
+        eprintln!("Download failed (exit code {}): {}", exit_code, stderr_output.trim());
+        let failure = errors::DownloadFailure::from_yt_dlp(&stderr_output, exit_code);
+        let error_msg = failure.to_string();
+        progress_state.lock().unwrap().failure = Some(failure);
         Err(error_msg)
     }
     } // Close #[cfg(not(target_os = "android"))] block
 }
 
+/// Run a post-processing step on a blocking thread, with the helpers it starts registered as
+/// the job's process
+async fn run_step<R: Runtime, T: Send + 'static>(
+    app: &AppHandle<R>,
+    progress_state: &ProgressState,
+    name: &str,
+    step: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let (app, job_id) = (app.clone(), progress_state.lock().unwrap().id.clone());
+    tokio::task::spawn_blocking(move || download_manager::run_for_job(&app, &job_id, step))
+        .await
+        .map_err(|e| format!("{} task failed: {}", name, e))?
+}
+
+/// Progress callback for a post-processing step that runs on a blocking thread
+fn step_progress<R: Runtime>(
+    app: &AppHandle<R>,
+    progress_state: &ProgressState,
+    status: &'static str,
+) -> impl FnMut(f64) + Send + 'static {
+    let app = app.clone();
+    let progress_state = progress_state.clone();
+    move |percentage| {
+        let mut progress = progress_state.lock().unwrap();
+        progress.status = status.to_string();
+        progress.percentage = percentage;
+        let _ = app.emit("download-progress", progress.clone());
+    }
+}
+
 async fn perform_trimming<R: Runtime>(
-    window: &Window<R>,
+    app: &AppHandle<R>,
     progress_state: ProgressState,
-    output_folder: &str,
+    temp_path: &std::path::Path,
     start_time: Option<f64>,
     end_time: Option<f64>,
     ffmpeg_path: std::path::PathBuf,
-) -> Result<(), String> {
+) -> Result<std::path::PathBuf, String> {
     use std::fs;
-    use std::path::Path;
-
-    // Find the downloaded file (it should have "_temp" in the name)
-    let folder_path = Path::new(output_folder);
-    let temp_files: Vec<_> = fs::read_dir(folder_path)
-        .map_err(|e| format!("Failed to read output directory: {}", e))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_name().to_string_lossy().contains("_temp"))
-        .collect();
 
-    if temp_files.is_empty() {
-        return Err("No temporary file found for trimming".to_string());
+    // yt-dlp wrote `<name>_temp.<ext>`; the trimmed clip takes the name without the suffix
+    let stem = temp_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let final_stem = stem.strip_suffix("_temp").unwrap_or(&stem);
+    let mut final_name = std::ffi::OsString::from(final_stem);
+    if let Some(ext) = temp_path.extension() {
+        final_name.push(".");
+        final_name.push(ext);
+    }
+    let final_path = temp_path.with_file_name(final_name);
+    if final_path == temp_path {
+        return Err(format!("Cannot trim {} in place", temp_path.display()));
     }
 
-    let temp_file = &temp_files[0];
-    let temp_path = temp_file.path();
-    let file_name_str = temp_file.file_name().to_string_lossy().to_string();
-
-    // Create the final output filename (remove "_temp")
-    let final_name = file_name_str.replace("_temp", "");
-    let final_path = folder_path.join(final_name);
-
-    let mut ffmpeg_cmd = Command::new(&ffmpeg_path);
+    let mut ffmpeg_cmd = binary_manager::tool_command(&ffmpeg_path);
+    ffmpeg_cmd.arg("-hide_banner").arg("-y");
 
     // Add input file
-    ffmpeg_cmd.arg("-i").arg(&temp_path);
+    ffmpeg_cmd.arg("-i").arg(temp_path);
 
     // Add trimming parameters
     if let Some(start) = start_time {
@@ -941,111 +1582,230 @@ async fn perform_trimming<R: Runtime>(
     // Set output file
     ffmpeg_cmd.arg(&final_path);
 
-    // Hide FFmpeg output for cleaner logs
-    ffmpeg_cmd.arg("-hide_banner").arg("-loglevel").arg("error");
-
-    eprintln!("Executing FFmpeg trimming: {:?}", ffmpeg_cmd);
+    // Progress is measured against the clip, not the whole download
+    let clip_duration = match end_time {
+        Some(end) => Some(end - start_time.unwrap_or(0.0)),
+        None => ffmpeg::probe_duration(&ffmpeg_path, temp_path).map(|total| total - start_time.unwrap_or(0.0)),
+    };
 
     {
         let mut progress = progress_state.lock().unwrap();
         progress.status = "trimming".to_string();
         progress.percentage = 0.0;
         let progress_copy = progress.clone();
-        let _ = window.emit("download-progress", progress_copy);
+        let _ = app.emit("download-progress", progress_copy);
     }
 
-    let ffmpeg_output = ffmpeg_cmd
-        .output()
-        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
-
-    if ffmpeg_output.status.success() {
-        // Remove the temporary file
-        if let Err(e) = fs::remove_file(&temp_path) {
-            eprintln!("Warning: Failed to remove temporary file: {}", e);
-        }
+    let trim_progress = progress_state.clone();
+    let trim_app = app.clone();
+    run_step(app, &progress_state, "Trimming", move || {
+        ffmpeg::run_with_progress(ffmpeg_cmd, clip_duration, |percentage| {
+            let mut progress = trim_progress.lock().unwrap();
+            progress.percentage = percentage;
+            let _ = trim_app.emit("download-progress", progress.clone());
+        })
+        .map_err(|e| format!("FFmpeg trimming failed: {}", e))
+    })
+    .await?;
 
-        {
-            let mut progress = progress_state.lock().unwrap();
-            progress.status = "completed".to_string();
-            progress.percentage = 100.0;
-            let progress_copy = progress.clone();
-            let _ = window.emit("download-progress", progress_copy);
-        }
+    // Remove the temporary file
+    if let Err(e) = fs::remove_file(temp_path) {
+        eprintln!("Warning: Failed to remove temporary file: {}", e);
+    }
 
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&ffmpeg_output.stderr);
-        Err(format!("FFmpeg trimming failed: {}", stderr))
+    {
+        let mut progress = progress_state.lock().unwrap();
+        progress.status = "completed".to_string();
+        progress.percentage = 100.0;
+        let progress_copy = progress.clone();
+        let _ = app.emit("download-progress", progress_copy);
     }
+
+    Ok(final_path)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let progress_state: ProgressState = Arc::new(Mutex::new(DownloadProgress {
-        percentage: 0.0,
-        speed: String::new(),
-        speed_bytes_per_sec: 0,
-        eta: String::new(),
-        status: "idle".to_string(),
-        bytes_downloaded: 0,
-        total_bytes: 0,
-        download_start_time: std::time::SystemTime::now(),
+    crash::install_hook();
+    let builder = tauri::Builder::default();
+    // Must be the first plugin: a second launch (e.g. from the context menu) exits here after
+    // passing its arguments to the running app
+    #[cfg(not(target_os = "android"))]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+        tray::show_main_window(app);
+        shell_integration::handle_args(app, &args);
     }));
-
-    tauri::Builder::default()
+    let app = builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_notification::init())
-        .manage(progress_state)
+        .manage(download_manager::DownloadManagerState::default())
+        .manage(power::PostQueueState::default())
+        .manage(network::NetworkState::default())
         .invoke_handler(tauri::generate_handler![
             select_output_folder,
             start_download,
+            start_direct_download,
+            pause_direct_download,
+            resume_direct_download,
+            cancel_direct_download,
+            settings::get_aria2_settings,
+            settings::set_aria2_settings,
+            settings::get_site_presets,
+            settings::set_site_presets,
+            settings::get_settings,
+            settings::update_settings,
+            settings::export_settings,
+            settings::import_settings,
+            secrets::store_secret,
+            secrets::has_secret,
+            secrets::delete_secret,
+            secrets::import_cookies_file,
+            bot_check::import_browser_cookies,
+            bot_check::open_login_window,
+            bot_check::finish_login,
+            api::get_api_status,
+            api::set_api_settings,
+            api::regenerate_api_token,
+            history::get_history,
+            history::clear_history,
+            history::export_history,
+            history::verify_download,
+            history::get_retry_chain,
+            history::search_history,
+            history::find_duplicate_downloads,
+            tags::tag_download,
+            tags::untag_download,
+            tags::list_tags,
+            stats::get_download_stats,
+            search::search_videos,
+            media_tools::export_animation,
+            media_tools::extract_frame,
+            media_tools::mux_files,
+            media_tools::get_media_info,
+            converter::list_conversion_presets,
+            converter::convert_file,
+            playlist::get_playlist_entries,
+            updater::check_for_app_update,
+            updater::install_app_update,
+            power::get_post_queue_action,
+            power::set_post_queue_action,
+            power::cancel_post_queue_action,
+            premieres::queue_upcoming_stream,
+            presets::list_presets,
+            presets::save_preset,
+            presets::delete_preset,
+            presets::start_download_with_preset,
+            presets::redownload,
+            download_manager::get_speed_history,
+            download_manager::get_queue_progress,
+            download_manager::get_restored_downloads,
+            download_manager::resume_restored_download,
+            download_manager::discard_restored_download,
+            download_manager::retry_with_region,
+            download_manager::retry_download,
+            notifications::open_downloaded_file,
+            notifications::show_in_folder,
+            i18n::set_locale,
+            i18n::get_translations,
+            crash::get_crash_reports,
+            crash::open_crash_report,
+            crash::submit_crash_report,
+            crash::dismiss_crash_report,
+            binary_updates::check_binary_updates,
+            binary_updates::update_yt_dlp,
+            binary_updates::rollback_yt_dlp,
+            binary_manager::get_binary_integrity,
+            binary_manager::repair_binaries,
+            monitor::open_monitor_window,
+            monitor::close_monitor_window,
+            monitor::toggle_progress_overlay,
+            shell_integration::get_context_menu_integration,
+            shell_integration::set_context_menu_integration,
+            shell_integration::get_link_handler,
+            shell_integration::set_link_handler,
+            download_manager::set_download_speed_limit,
+            partials::list_incomplete_downloads,
+            partials::resume_incomplete_downloads,
+            partials::clean_temp_files,
+            network::get_network_status,
+            bandwidth::measure_bandwidth,
+            bandwidth::suggest_quality,
+            download_manager::pause_all_downloads,
+            download_manager::cancel_all_downloads,
+            download_manager::list_downloads,
+            download_manager::set_download_priority,
+            download_manager::move_in_queue,
             test_dependencies,
             get_video_metadata,
+            estimate_download_size,
+            batch::get_metadata_batch,
+            batch::start_batch_download,
+            bookmarks::add_bookmark,
+            bookmarks::get_bookmarks,
+            bookmarks::remove_bookmark,
+            bookmarks::download_bookmarks,
+            queue_transfer::import_queue,
+            queue_transfer::export_queue,
+            link_files::read_link_file,
+            link_files::queue_link_file,
+            metadata_cache::clear_metadata_cache,
+            thumbnails::fetch_thumbnail,
+            naming::list_naming_schemes,
+            podcast::list_podcast_feeds,
+            podcast::save_podcast_feed,
+            podcast::delete_podcast_feed,
+            transcribe::list_whisper_models,
+            transcribe::download_whisper_model,
+            transcribe::delete_whisper_model,
+            transcribe::transcribe_file,
+            yt_dlp_config::get_yt_dlp_config,
+            yt_dlp_config::save_yt_dlp_config,
             check_ffmpeg,
             get_shared_url,
+            get_shared_urls,
+            get_interrupted_downloads,
+            resume_interrupted_download,
+            discard_interrupted_download,
             get_android_videos_dir
         ])
         .setup(move |_app| {
+            crash::set_report_dir(_app.handle());
+            let loaded_settings = settings::load(_app.handle());
+            _app.manage::<settings::SettingsState>(Arc::new(Mutex::new(loaded_settings)));
+            let loaded_history = history::load(_app.handle());
+            _app.manage::<history::HistoryState>(Arc::new(Mutex::new(loaded_history)));
+            let loaded_bookmarks = bookmarks::load(_app.handle());
+            _app.manage::<bookmarks::BookmarksState>(Arc::new(Mutex::new(loaded_bookmarks)));
+            let loaded_metadata = metadata_cache::load(_app.handle());
+            _app.manage::<metadata_cache::MetadataCacheState>(Arc::new(Mutex::new(loaded_metadata)));
+            download_manager::restore(_app.handle());
+            partials::spawn_startup_scan(_app.handle().clone());
+            download_manager::spawn_monitor(_app.handle().clone());
+            network::spawn_monitor(_app.handle().clone());
+            binary_manager::spawn_integrity_check(_app.handle().clone());
+            binary_manager::configure_child_env(_app.handle());
+
             #[cfg(not(target_os = "android"))]
             let app = _app;
             #[cfg(not(target_os = "android"))]
             {
-                let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-                let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-                let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
-
-                let _tray = TrayIconBuilder::new()
-                    .icon(app.default_window_icon().unwrap().clone())
-                    .menu(&menu)
-                    .tooltip("U-Download")
-                    .on_menu_event(|app, event| {
-                        if event.id.as_ref() == "show" {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        } else if event.id.as_ref() == "quit" {
-                            let app_handle = app.clone();
-                            app.dialog()
-                                .message("Are you sure you want to quit U-Download?")
-                                .title("Quit Confirmation")
-                                .kind(tauri_plugin_dialog::MessageDialogKind::Info)
-                                .buttons(tauri_plugin_dialog::MessageDialogButtons::OkCancelCustom(
-                                    "Yes".to_owned(),
-                                    "No".to_owned(),
-                                ))
-                                .show(move |answer| {
-                                    if answer {
-                                        std::thread::spawn(move || {
-                                            app_handle.exit(0);
-                                        });
-                                    }
-                                });
-                        }
-                    })
-                    .build(app)?;
+                app.manage(aria2::Aria2State::default());
+                app.manage(updater::UpdaterState::default());
+                app.manage(api::ApiState::default());
+                api::spawn_startup(app.handle().clone());
+                app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
+                updater::spawn_startup_check(app.handle().clone());
+
+                tray::build(app.handle())?;
+                app.handle().plugin(tauri_plugin_clipboard_manager::init())?;
+                app.handle().plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
+                // A taken shortcut should not stop the app from starting
+                if let Err(e) = hotkey::register(app.handle()) {
+                    eprintln!("⚠️  {}", e);
+                }
+                shell_integration::handle_args(app.handle(), &std::env::args().collect::<Vec<_>>());
             }
             Ok(())
         })
@@ -1062,12 +1822,24 @@ pub fn run() {
             }
             _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!());
+    let app = match app {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("❌ Error while starting tauri application: {}", e);
+            crash::report_fatal(&e);
+            std::process::exit(1);
+        }
+    };
+    app.run(|_app, event| {
+        if let tauri::RunEvent::Exit = event {
+            download_manager::kill_children();
+        }
+    });
 }
 #[cfg(target_os = "android")]
 async fn perform_download_android<R: Runtime>(
-    window: &Window<R>,
+    app: &AppHandle<R>,
     progress_state: ProgressState,
     url: &str,
     download_type: &str,
@@ -1080,6 +1852,11 @@ async fn perform_download_android<R: Runtime>(
     use tokio::fs;
 
     eprintln!("Android YouTube download starting for URL: {}", url);
+    // Streams are saved as fetched, with no ffmpeg to convert them, so only the original two
+    // targets can be produced
+    if !matches!(download_type, "mp4" | "mp3") {
+        return Err(format!("{} downloads are not supported on Android; choose MP4 or MP3", download_type.to_uppercase()));
+    }
 
     // Set initial progress
     {
@@ -1089,7 +1866,30 @@ async fn perform_download_android<R: Runtime>(
         p.bytes_downloaded = 0;
         p.total_bytes = 0;
         p.download_start_time = std::time::SystemTime::now();
-        let _ = window.emit("download-progress", p.clone());
+        let _ = app.emit("download-progress", p.clone());
+    }
+
+    // Pick up a partial file left behind by a killed process before extracting again
+    let previous = android_resume::find_matching(url, download_type, output_folder);
+    if let Some(record) = previous.clone() {
+        let final_path = record.final_path.clone();
+        match android_resume::stream_to_file(app, &progress_state, record).await {
+            Ok(size) => {
+                let mut p = progress_state.lock().unwrap();
+                p.status = "completed".into();
+                p.percentage = 100.0;
+                p.bytes_downloaded = size;
+                p.total_bytes = size;
+                let _ = app.emit("download-progress", p.clone());
+                let filename = Path::new(&final_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or(final_path);
+                eprintln!("✅ Android download resumed and completed: {}", filename);
+                return Ok(filename);
+            }
+            Err(e) => eprintln!("Stored stream URL could not be resumed ({}), re-extracting", e),
+        }
     }
 
     // Method 1: Advanced YouTube API extraction using multiple endpoints
@@ -1229,7 +2029,7 @@ async fn perform_download_android<R: Runtime>(
             .ok_or_else(|| "No streamingData found in player response".to_string())?;
         
         // Select appropriate streams based on download type and quality
-        let (stream_url, is_audio_only) = if download_type == "mp3" {
+        let (stream_url, is_audio_only) = if formats::is_audio(download_type) {
             // Extract audio streams
             let audio_formats = streaming_data
                 .get("adaptiveFormats")
@@ -1517,7 +2317,7 @@ async fn perform_download_android<R: Runtime>(
             .get("streamingData")
             .ok_or_else(|| "No streaming data in API response".to_string())?;
         
-        let formats = if download_type == "mp3" {
+        let formats = if formats::is_audio(download_type) {
             streaming_data.get("adaptiveFormats")
         } else {
             streaming_data.get("formats")
@@ -1534,7 +2334,7 @@ async fn perform_download_android<R: Runtime>(
                     .and_then(|m| m.as_str())
                     .unwrap_or("");
                 
-                let is_suitable = if download_type == "mp3" {
+                let is_suitable = if formats::is_audio(download_type) {
                     mime_type.contains("audio")
                 } else {
                     mime_type.contains("video")
@@ -1626,7 +2426,7 @@ async fn perform_download_android<R: Runtime>(
                             eprintln!("Found {} streams", streams.len());
                             
                             // Enhanced stream selection with quality preferences
-                            let selected_stream = if download_type == "mp3" {
+                            let selected_stream = if formats::is_audio(download_type) {
                                 // Prefer audio streams with highest bitrate
                                 let audio_streams: Vec<_> = streams.iter()
                                     .filter(|s| s.mime.type_() == "audio")
@@ -1712,7 +2512,7 @@ async fn perform_download_android<R: Runtime>(
         let mut p = progress_state.lock().unwrap();
         p.status = "extracting".into();
         p.percentage = 10.0;
-        let _ = window.emit("download-progress", p.clone());
+        let _ = app.emit("download-progress", p.clone());
     }
 
     // Method 1: Advanced YouTube API extraction (Primary)
@@ -1763,79 +2563,81 @@ async fn perform_download_android<R: Runtime>(
         let mut p = progress_state.lock().unwrap();
         p.status = "downloading".into();
         p.percentage = 25.0;
-        let _ = window.emit("download-progress", p.clone());
+        let _ = app.emit("download-progress", p.clone());
     }
 
     // Check if content was already downloaded by yt-dlp crate
     let has_content_bytes = content_bytes.is_some();
-    
-    // Download the content (if not already downloaded by yt-dlp crate)
-    let file_content = if let Some(bytes) = content_bytes {
-        bytes
-    } else {
-        eprintln!("Downloading content from extracted URL...");
-        
-        let client = reqwest::Client::builder()
-            .user_agent("Mozilla/5.0 (Linux; Android 10; SM-G975F) AppleWebKit/537.36")
-            .build()
-            .map_err(|e| format!("Failed to create download client: {}", e))?;
-        
-        let response = client
-            .get(&download_url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download content: {}", e))?;
-        
-        if !response.status().is_success() {
-            return Err(format!("Download failed with status: {}", response.status()));
-        }
-        
-        response
-            .bytes()
-            .await
-            .map_err(|e| format!("Failed to read download content: {}", e))?
-            .to_vec()
-    };
-
-    // Update progress for file writing
-    {
-        let mut p = progress_state.lock().unwrap();
-        p.status = "saving".into();
-        p.percentage = 80.0;
-        let _ = window.emit("download-progress", p.clone());
-    }
 
-    // Save the file
     let out_dir = Path::new(output_folder);
-    let extension = if download_type == "mp3" { 
+    let extension = if formats::is_audio(download_type) { 
         if has_content_bytes { "mp3" } else { "m4a" }
     } else { 
         "mp4" 
     };
     
-    let sanitized_title = video_title
-        .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_")
+    let sanitized_title = storage::sanitize_file_name(&video_title)
         .chars()
         .take(100)  // Limit filename length
         .collect::<String>();
     
     let filename = format!("{}.{}", sanitized_title, extension);
     let file_path = out_dir.join(&filename);
-    
-    eprintln!("Saving file: {}", file_path.display());
-    
-    fs::write(&file_path, &file_content)
-        .await
-        .map_err(|e| format!("Failed to write file {}: {}", file_path.display(), e))?;
+
+    let file_size = if let Some(file_content) = content_bytes {
+        // Update progress for file writing
+        {
+            let mut p = progress_state.lock().unwrap();
+            p.status = "saving".into();
+            p.percentage = 80.0;
+            let _ = app.emit("download-progress", p.clone());
+        }
+
+        eprintln!("Saving file: {}", file_path.display());
+        
+        fs::write(&file_path, &file_content)
+            .await
+            .map_err(|e| format!("Failed to write file {}: {}", file_path.display(), e))?;
+        file_content.len() as u64
+    } else {
+        eprintln!("Downloading content from extracted URL to {}", file_path.display());
+
+        // Stream through a persisted partial file so process death doesn't lose the progress
+        let record = match previous {
+            // The fresh URL may be another format; stream_to_file starts over if so
+            Some(mut record) => {
+                record.stream_url = download_url;
+                record.title = video_title.clone();
+                record.final_path = file_path.to_string_lossy().to_string();
+                record
+            }
+            None => android_resume::InflightDownload {
+                id: android_resume::new_id(),
+                url: url.to_string(),
+                stream_url: download_url,
+                download_type: download_type.to_string(),
+                quality: quality.to_string(),
+                title: video_title.clone(),
+                output_folder: output_folder.to_string(),
+                temp_path: format!("{}.part", file_path.display()),
+                final_path: file_path.to_string_lossy().to_string(),
+                bytes_written: 0,
+                total_bytes: 0,
+                format_id: None,
+            },
+        };
+        android_resume::stream_to_file(app, &progress_state, record).await?
+    };
     
     // Final progress update
     {
         let mut p = progress_state.lock().unwrap();
         p.status = "completed".into();
         p.percentage = 100.0;
-        p.bytes_downloaded = file_content.len() as u64;
-        p.total_bytes = file_content.len() as u64;
-        let _ = window.emit("download-progress", p.clone());
+        p.bytes_downloaded = file_size;
+        p.total_bytes = file_size;
+        p.files = vec![file_path.to_string_lossy().to_string()];
+        let _ = app.emit("download-progress", p.clone());
     }
 
     eprintln!("✅ Android download completed successfully: {}", filename);
@@ -0,0 +1,97 @@
+// Link files dropped onto the window: Windows `.url`, macOS `.webloc` and Linux `.desktop`
+use std::path::Path;
+use tauri::{AppHandle, Runtime};
+
+use crate::download_manager;
+
+/// Largest link file read; real ones are a few hundred bytes
+const MAX_LINK_FILE_BYTES: u64 = 64 * 1024;
+
+/// `URL=` value from an INI-style file (`.url` and `.desktop`), inside the given section
+fn ini_url(contents: &str, section: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line.eq_ignore_ascii_case(section);
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("URL") {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// URL from a `.webloc` property list. XML plists are parsed; binary plists store the URL as a
+/// plain ASCII string, so the first http(s) run of printable bytes is taken.
+fn webloc_url(bytes: &[u8]) -> Option<String> {
+    if bytes.starts_with(b"bplist") {
+        let start = bytes.windows(4).position(|w| w == b"http")?;
+        let url: String = bytes[start..]
+            .iter()
+            .take_while(|b| b.is_ascii_graphic())
+            .map(|b| *b as char)
+            .collect();
+        return Some(url);
+    }
+
+    let xml = String::from_utf8_lossy(bytes);
+    let after_key = &xml[xml.find("<key>URL</key>")?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = after_key[start..].find("</string>")?;
+    Some(unescape_xml(after_key[start..start + end].trim()))
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Target URL of a link file
+pub fn parse_link_file(path: &Path) -> Result<String, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+        .len();
+    if size > MAX_LINK_FILE_BYTES {
+        return Err(format!("{} is too large to be a link file", path.display()));
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let url = match ext.as_str() {
+        "url" => ini_url(&String::from_utf8_lossy(&bytes), "[InternetShortcut]"),
+        "desktop" => ini_url(&String::from_utf8_lossy(&bytes), "[Desktop Entry]"),
+        "webloc" => webloc_url(&bytes),
+        other => return Err(format!("Unsupported link file type: .{}", other)),
+    }
+    .ok_or_else(|| format!("No URL found in {}", path.display()))?;
+
+    match url::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        _ => Err(format!("{} does not point to a web page: {}", path.display(), url)),
+    }
+}
+
+#[tauri::command]
+pub async fn read_link_file(path: String) -> Result<String, String> {
+    parse_link_file(Path::new(&path))
+}
+
+/// Queue the target of a dropped link file with the default format for its site
+#[tauri::command]
+pub async fn queue_link_file<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    outputFolder: Option<String>,
+) -> Result<String, String> {
+    let url = parse_link_file(Path::new(&path))?;
+    let request = download_manager::default_request(&app_handle, url, outputFolder)?;
+    eprintln!("🔗 Queuing {} from link file {}", request.url, path);
+    Ok(download_manager::submit(&app_handle, request))
+}
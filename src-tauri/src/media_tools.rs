@@ -0,0 +1,262 @@
+// Standalone media tools over files that are already on disk, all run with the bundled ffmpeg
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::{binary_manager, ffmpeg};
+
+const MAX_ANIMATION_FPS: u32 = 50;
+const MAX_ANIMATION_WIDTH: u32 = 1920;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    Gif,
+    Webp,
+}
+
+impl AnimationFormat {
+    fn ext(self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Webp => "webp",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolProgress {
+    /// Output file the progress refers to
+    pub output: String,
+    pub percentage: f64,
+}
+
+fn existing_file(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// `name.suffix.ext` next to `input`, numbered if it already exists
+fn output_beside(input: &Path, suffix: &str, ext: &str) -> PathBuf {
+    let stem = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let mut candidate = input.with_file_name(format!("{}.{}.{}", stem, suffix, ext));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = input.with_file_name(format!("{}.{}-{}.{}", stem, suffix, n, ext));
+        n += 1;
+    }
+    candidate
+}
+
+/// `ffmpeg::probe_duration` on a blocking thread
+async fn probe_duration(ffmpeg: &Path, input: &Path) -> Result<Option<f64>, String> {
+    let (ffmpeg, input) = (ffmpeg.to_path_buf(), input.to_path_buf());
+    tokio::task::spawn_blocking(move || ffmpeg::probe_duration(&ffmpeg, &input))
+        .await
+        .map_err(|e| format!("FFmpeg task failed: {}", e))
+}
+
+/// Run an ffmpeg command writing `output` on a blocking thread, emitting `tool-progress`; a failed
+/// run leaves no partial file
+async fn run_tool<R: Runtime>(app: &AppHandle<R>, cmd: Command, duration: Option<f64>, output: &Path) -> Result<(), String> {
+    let app = app.clone();
+    let output = output.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let output_str = output.to_string_lossy().to_string();
+        let result = ffmpeg::run_with_progress(cmd, duration, |percentage| {
+            let _ = app.emit("tool-progress", ToolProgress { output: output_str.clone(), percentage });
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("FFmpeg task failed: {}", e))?
+}
+
+/// Export a clip of a local video as an optimized GIF or animated WebP
+#[tauri::command]
+pub async fn export_animation<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    startTime: f64,
+    endTime: f64,
+    fps: Option<u32>,
+    width: Option<u32>,
+    format: AnimationFormat,
+) -> Result<String, String> {
+    let input = existing_file(&path)?;
+    if startTime < 0.0 || endTime <= startTime {
+        return Err("End time must be after start time".to_string());
+    }
+    let fps = fps.unwrap_or(15).clamp(1, MAX_ANIMATION_FPS);
+    let width = width.unwrap_or(480).clamp(16, MAX_ANIMATION_WIDTH);
+
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+
+    let output = output_beside(&input, "clip", format.ext());
+    let scale = format!("fps={},scale={}:-1:flags=lanczos", fps, width);
+
+    let mut cmd = binary_manager::tool_command(&paths.ffmpeg);
+    cmd.arg("-hide_banner")
+        .arg("-y")
+        .arg("-ss")
+        .arg(startTime.to_string())
+        .arg("-t")
+        .arg((endTime - startTime).to_string())
+        .arg("-i")
+        .arg(&input)
+        .arg("-an");
+    match format {
+        // A palette generated from the clip itself beats GIF's default 256-color web palette
+        AnimationFormat::Gif => {
+            cmd.arg("-filter_complex").arg(format!(
+                "[0:v]{},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+                scale
+            ));
+        }
+        AnimationFormat::Webp => {
+            cmd.arg("-vf")
+                .arg(&scale)
+                .arg("-c:v")
+                .arg("libwebp")
+                .arg("-lossless")
+                .arg("0")
+                .arg("-q:v")
+                .arg("75")
+                .arg("-compression_level")
+                .arg("6");
+        }
+    }
+    cmd.arg("-loop").arg("0").arg(&output);
+
+    let output_str = output.to_string_lossy().to_string();
+    eprintln!("🎬 Exporting {} to {}", input.display(), output_str);
+    run_tool(&app_handle, cmd, Some(endTime - startTime), &output).await?;
+    Ok(output_str)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    Png,
+    Jpeg,
+}
+
+/// Save the frame at `timestamp` seconds as a PNG or JPEG still beside the video
+#[tauri::command]
+pub async fn extract_frame<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    timestamp: f64,
+    format: FrameFormat,
+) -> Result<String, String> {
+    let input = existing_file(&path)?;
+    if timestamp < 0.0 {
+        return Err("Timestamp cannot be negative".to_string());
+    }
+
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+    if let Some(duration) = probe_duration(&paths.ffmpeg, &input).await? {
+        if timestamp > duration {
+            return Err(format!("Timestamp {:.2}s is past the end of the video ({:.2}s)", timestamp, duration));
+        }
+    }
+
+    let ext = match format {
+        FrameFormat::Png => "png",
+        FrameFormat::Jpeg => "jpg",
+    };
+    let output = output_beside(&input, &format!("frame-{}", timestamp.round() as u64), ext);
+
+    // -ss before -i seeks by keyframe then decodes forward, which is fast and still frame-accurate
+    let mut cmd = binary_manager::tool_command(&paths.ffmpeg);
+    cmd.arg("-hide_banner")
+        .arg("-y")
+        .arg("-ss")
+        .arg(timestamp.to_string())
+        .arg("-i")
+        .arg(&input)
+        .arg("-frames:v")
+        .arg("1");
+    if format == FrameFormat::Jpeg {
+        cmd.arg("-q:v").arg("2");
+    }
+    cmd.arg(&output);
+
+    let result = tokio::task::spawn_blocking(move || cmd.output())
+        .await
+        .map_err(|e| format!("FFmpeg task failed: {}", e))?
+        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+    if !result.status.success() || !output.exists() {
+        let _ = std::fs::remove_file(&output);
+        return Err(format!("Frame extraction failed: {}", String::from_utf8_lossy(&result.stderr).trim()));
+    }
+
+    eprintln!("📸 Saved frame to {}", output.display());
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// Combine a video-only and an audio-only file into `output` without re-encoding
+#[tauri::command]
+pub async fn mux_files<R: Runtime>(
+    app_handle: AppHandle<R>,
+    videoPath: String,
+    audioPath: String,
+    output: String,
+) -> Result<String, String> {
+    let video = existing_file(&videoPath)?;
+    let audio = existing_file(&audioPath)?;
+    let output = PathBuf::from(output);
+    let folder = output.parent().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default();
+    let output = PathBuf::from(crate::storage::validate_output_folder(&app_handle, &folder)?)
+        .join(output.file_name().ok_or("Output needs a file name")?);
+    if output.extension().is_none() {
+        return Err("Output file needs an extension such as .mp4 or .mkv".to_string());
+    }
+    if output == video || output == audio {
+        return Err("Output must be a new file, not one of the inputs".to_string());
+    }
+    if output.exists() {
+        return Err(format!("{} already exists", output.display()));
+    }
+
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+
+    let mut cmd = binary_manager::tool_command(&paths.ffmpeg);
+    cmd.arg("-hide_banner")
+        .arg("-i")
+        .arg(&video)
+        .arg("-i")
+        .arg(&audio)
+        .arg("-map")
+        .arg("0:v:0")
+        .arg("-map")
+        .arg("1:a:0")
+        .arg("-c")
+        .arg("copy")
+        // Stop at the end of the shorter track so stray padding does not stretch the file
+        .arg("-shortest")
+        .arg(&output);
+
+    eprintln!("🔗 Merging {} and {}", video.display(), audio.display());
+    let duration = probe_duration(&paths.ffmpeg, &video).await?;
+    run_tool(&app_handle, cmd, duration, &output).await?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn get_media_info<R: Runtime>(app_handle: AppHandle<R>, path: String) -> Result<ffmpeg::MediaInfo, String> {
+    let input = existing_file(&path)?;
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+    ffmpeg::media_info(&paths.ffmpeg, &input)
+}
@@ -0,0 +1,144 @@
+// Cache of yt-dlp `--dump-json` output keyed by URL, in memory and in metadata_cache.json,
+// so looking a video up again does not spawn yt-dlp
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::now_millis;
+use crate::storage;
+
+const CACHE_FILE: &str = "metadata_cache.json";
+/// Formats and counts go stale, so entries expire after a few hours
+const TTL_MS: u64 = 3 * 60 * 60 * 1000;
+/// Oldest entries are evicted past this many
+const MAX_ENTRIES: usize = 200;
+/// Large fields nothing in the app reads; dropping them keeps the cache file small
+const STRIPPED_FIELDS: &[&str] = &["automatic_captions", "subtitles", "heatmap", "_format_sort_fields"];
+/// Signed media URLs: short-lived, and usable by anyone who reads the cache file
+const URL_FIELDS: &[&str] = &[
+    "url",
+    "manifest_url",
+    "fragment_base_url",
+    "requested_formats",
+    "requested_downloads",
+    "requested_subtitles",
+];
+/// Inserts within this long of each other are written to disk together
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+static SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedMetadata {
+    pub fetched_at_ms: u64,
+    pub metadata: serde_json::Value,
+}
+
+pub type MetadataCacheState = Arc<Mutex<HashMap<String, CachedMetadata>>>;
+
+fn key(url: &str) -> String {
+    url.trim().to_string()
+}
+
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, CachedMetadata> {
+    let entries: HashMap<String, CachedMetadata> =
+        match storage::app_data_file(app, CACHE_FILE).and_then(|path| storage::read_json(&path)) {
+            Ok(entries) => entries.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load metadata cache: {}", e);
+                HashMap::new()
+            }
+        };
+    let now = now_millis();
+    entries
+        .into_iter()
+        .filter(|(_, entry)| now.saturating_sub(entry.fetched_at_ms) < TTL_MS)
+        // Files written by older versions still hold stream URLs
+        .map(|(url, mut entry)| {
+            strip(&mut entry.metadata);
+            (url, entry)
+        })
+        .collect()
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, entries: &HashMap<String, CachedMetadata>) {
+    let result = storage::app_data_file(app, CACHE_FILE).and_then(|path| storage::write_json(&path, entries));
+    if let Err(e) = result {
+        eprintln!("⚠️  Failed to save metadata cache: {}", e);
+    }
+}
+
+/// Cached metadata for a URL if it is still fresh
+pub fn get<R: Runtime>(app: &AppHandle<R>, url: &str) -> Option<serde_json::Value> {
+    let state = app.state::<MetadataCacheState>();
+    let entries = state.lock().unwrap();
+    entries
+        .get(&key(url))
+        .filter(|entry| now_millis().saturating_sub(entry.fetched_at_ms) < TTL_MS)
+        .map(|entry| entry.metadata.clone())
+}
+
+/// Drop the fields the cache does not keep
+fn strip(metadata: &mut serde_json::Value) {
+    if let Some(object) = metadata.as_object_mut() {
+        for field in STRIPPED_FIELDS.iter().chain(URL_FIELDS) {
+            object.remove(*field);
+        }
+    }
+    // Per-fragment URLs of HLS/DASH formats make up most of what is left
+    if let Some(formats) = metadata["formats"].as_array_mut() {
+        for format in formats {
+            if let Some(format) = format.as_object_mut() {
+                format.remove("fragments");
+                for field in URL_FIELDS {
+                    format.remove(*field);
+                }
+            }
+        }
+    }
+}
+
+pub fn insert<R: Runtime>(app: &AppHandle<R>, url: &str, metadata: &serde_json::Value) {
+    let mut metadata = metadata.clone();
+    strip(&mut metadata);
+    {
+        let state = app.state::<MetadataCacheState>();
+        let mut entries = state.lock().unwrap();
+        let now = now_millis();
+        entries.retain(|_, entry| now.saturating_sub(entry.fetched_at_ms) < TTL_MS);
+        entries.insert(key(url), CachedMetadata { fetched_at_ms: now, metadata });
+        while entries.len() > MAX_ENTRIES {
+            let Some(oldest) = entries.iter().min_by_key(|(_, e)| e.fetched_at_ms).map(|(k, _)| k.clone()) else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+    }
+    schedule_save(app);
+}
+
+/// Write the cache once inserts settle, rather than rewriting the file for every lookup
+fn schedule_save<R: Runtime>(app: &AppHandle<R>) {
+    if SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        SAVE_PENDING.store(false, Ordering::SeqCst);
+        let snapshot = app.state::<MetadataCacheState>().lock().unwrap().clone();
+        save(&app, &snapshot);
+    });
+}
+
+#[tauri::command]
+pub async fn clear_metadata_cache<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    let state = app_handle.state::<MetadataCacheState>();
+    let mut entries = state.lock().unwrap();
+    entries.clear();
+    save(&app_handle, &entries);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+// Secondary windows that follow download progress while the main window is hidden. They load the
+// same frontend with a `view` query parameter and listen to the same broadcast events.
+// Desktop only: Android has a single activity window.
+use tauri::{AppHandle, Manager, Runtime};
+#[cfg(not(target_os = "android"))]
+use tauri::{WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+pub const MONITOR_LABEL: &str = "monitor";
+pub const OVERLAY_LABEL: &str = "overlay";
+
+#[cfg(not(target_os = "android"))]
+fn show<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    window
+        .show()
+        .map_err(|e| format!("Failed to show window: {}", e))?;
+    window
+        .set_focus()
+        .map_err(|e| format!("Failed to focus window: {}", e))
+}
+
+/// Open the monitor window, or bring it forward if it is already open
+#[tauri::command]
+pub async fn open_monitor_window<R: Runtime>(
+    app: AppHandle<R>,
+    alwaysOnTop: Option<bool>,
+) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (app, alwaysOnTop);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let always_on_top = alwaysOnTop.unwrap_or(true);
+        if let Some(window) = app.get_webview_window(MONITOR_LABEL) {
+            window
+                .set_always_on_top(always_on_top)
+                .map_err(|e| format!("Failed to update monitor window: {}", e))?;
+            return show(&window);
+        }
+
+        let window = WebviewWindowBuilder::new(
+            &app,
+            MONITOR_LABEL,
+            WebviewUrl::App("index.html?view=monitor".into()),
+        )
+        .title("U-Download — Downloads")
+        .inner_size(380.0, 260.0)
+        .min_inner_size(300.0, 160.0)
+        .always_on_top(always_on_top)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("Failed to open monitor window: {}", e))?;
+        eprintln!("🪟 Opened download monitor");
+        show(&window)
+    }
+}
+
+#[tauri::command]
+pub async fn close_monitor_window<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(MONITOR_LABEL) {
+        window
+            .close()
+            .map_err(|e| format!("Failed to close monitor window: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Show or hide the mini overlay: a frameless, always-on-top strip with the active download's
+/// progress, dragged by its body. Returns whether it is now open.
+pub fn toggle_overlay<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+        window
+            .close()
+            .map_err(|e| format!("Failed to close overlay: {}", e))?;
+        return Ok(false);
+    }
+    #[cfg(target_os = "android")]
+    {
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let mut builder = WebviewWindowBuilder::new(
+            app,
+            OVERLAY_LABEL,
+            WebviewUrl::App("index.html?view=overlay".into()),
+        )
+        .title("U-Download")
+        .inner_size(280.0, 64.0)
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false);
+        // Top-right corner of the primary screen, clear of most title bars
+        if let Ok(Some(monitor)) = app.primary_monitor() {
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            builder = builder.position(size.width - 300.0, 40.0);
+        }
+        builder
+            .build()
+            .map_err(|e| format!("Failed to open overlay: {}", e))?;
+        eprintln!("🪟 Opened progress overlay");
+        Ok(true)
+    }
+}
+
+#[tauri::command]
+pub async fn toggle_progress_overlay<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    toggle_overlay(&app)
+}
@@ -0,0 +1,48 @@
+// Ready-made output templates for DownloadOptions::output_template
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NamingScheme {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub template: &'static str,
+    pub example: &'static str,
+}
+
+/// `Show/Season 01/Show - S01E02 - Title.ext`, the layout Jellyfin, Plex and Kodi scan as a series.
+/// The show comes from the series name, else the playlist or channel; episodes without numbers
+/// are numbered by playlist position. A `|default` is inserted verbatim, hence `|01`.
+pub const MEDIA_SERVER_TEMPLATE: &str = "%(series,playlist_title,channel,uploader)s/Season %(season_number|01)02d/\
+%(series,playlist_title,channel,uploader)s - S%(season_number|01)02dE%(episode_number,playlist_index|00)02d - %(title)s.%(ext)s";
+
+pub const SCHEMES: &[NamingScheme] = &[
+    NamingScheme {
+        id: "title",
+        label: "Title only",
+        template: "%(title)s.%(ext)s",
+        example: "Episode title.mp4",
+    },
+    NamingScheme {
+        id: "channel_folders",
+        label: "Folder per channel",
+        template: "%(channel,uploader)s/%(title)s.%(ext)s",
+        example: "Channel/Episode title.mp4",
+    },
+    NamingScheme {
+        id: "dated",
+        label: "Dated",
+        template: "%(upload_date>%Y-%m-%d)s - %(title)s.%(ext)s",
+        example: "2024-05-01 - Episode title.mp4",
+    },
+    NamingScheme {
+        id: "media_server",
+        label: "Jellyfin / Plex / Kodi series",
+        template: MEDIA_SERVER_TEMPLATE,
+        example: "Show/Season 01/Show - S01E02 - Episode title.mkv",
+    },
+];
+
+#[tauri::command]
+pub async fn list_naming_schemes() -> Result<Vec<NamingScheme>, String> {
+    Ok(SCHEMES.to_vec())
+}
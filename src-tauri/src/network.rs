@@ -0,0 +1,366 @@
+// Connectivity monitor: holds running downloads so they do not fail while the OS reports no
+// network, and restarts them when it comes back. A download that fails on a network error is
+// held too, if the OS confirms the network is gone. Where the OS does not say (or downloads go
+// through a system proxy), nothing is held and failures are reported as usual. On desktop it
+// also watches for metered connections and pauses or throttles downloads according to the
+// metered settings.
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::download_manager::{self, DownloadManagerState, DownloadRequest};
+use crate::settings::{self, MeteredAction};
+
+const CHECK_INTERVAL_ONLINE: Duration = Duration::from_secs(15);
+const CHECK_INTERVAL_OFFLINE: Duration = Duration::from_secs(3);
+/// Asking the OS about metering spawns a process, so it is done every few connectivity checks
+const METERED_CHECK_EVERY: u32 = 4;
+
+pub struct NetworkState {
+    online: AtomicBool,
+    metered: AtomicBool,
+}
+
+impl Default for NetworkState {
+    fn default() -> Self {
+        Self { online: AtomicBool::new(true), metered: AtomicBool::new(false) }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NetworkStatus {
+    pub online: bool,
+    pub metered: bool,
+}
+
+/// Whether the OS reports that no network is usable. `false` when it does not say, or when
+/// downloads go through a system proxy, which may reach the internet when the OS thinks nothing does.
+pub async fn os_reports_offline() -> bool {
+    if crate::binary_manager::system_proxy_in_use() {
+        return false;
+    }
+    tokio::task::spawn_blocking(detect_online).await.ok().flatten() == Some(false)
+}
+
+/// Whether any connection has network access, from the Windows connection profiles
+#[cfg(target_os = "windows")]
+fn detect_online() -> Option<bool> {
+    let script = "[void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+                  @([Windows.Networking.Connectivity.NetworkInformation]::GetConnectionProfiles() | \
+                  Where-Object { $_.GetNetworkConnectivityLevel() -ne 'None' }).Count";
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()
+        .ok()?;
+    let connected: u32 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Some(connected > 0)
+}
+
+/// NetworkManager's global `Connectivity` property: 0 unknown, 1 none, 2 portal, 3 limited,
+/// 4 full. Portal and limited still count as online; a proxy may be what carries the traffic.
+#[cfg(target_os = "linux")]
+fn detect_online() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Connectivity",
+        ])
+        .output()
+        .ok()?;
+    // Prints "u 4"
+    match String::from_utf8_lossy(&output.stdout).split_whitespace().nth(1)? {
+        "1" => Some(false),
+        "2" | "3" | "4" => Some(true),
+        _ => None,
+    }
+}
+
+/// Whether the Network framework has a usable path; `None` until its first update arrives
+#[cfg(target_os = "macos")]
+fn detect_online() -> Option<bool> {
+    path_monitor::satisfied()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn detect_online() -> Option<bool> {
+    None
+}
+
+pub fn is_online<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<NetworkState>().online.load(Ordering::Relaxed)
+}
+
+pub fn is_metered<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<NetworkState>().metered.load(Ordering::Relaxed)
+}
+
+/// Whether the OS marks the active connection as metered; `None` where it does not say
+#[cfg(target_os = "windows")]
+fn detect_metered() -> Option<bool> {
+    let script = "[void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+                  $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+                  if ($p) { $p.GetConnectionCost().NetworkCostType }";
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()
+        .ok()?;
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "Fixed" | "Variable" => Some(true),
+        "Unrestricted" => Some(false),
+        _ => None,
+    }
+}
+
+/// NetworkManager's global `Metered` property: 1 yes, 2 no, 3 guessed yes, 4 guessed no
+#[cfg(target_os = "linux")]
+fn detect_metered() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    // Prints "u 4"
+    match String::from_utf8_lossy(&output.stdout).split_whitespace().nth(1)? {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the Network framework calls the current path expensive (cellular, personal hotspot)
+/// or constrained (Low Data Mode); `None` until its first update arrives
+#[cfg(target_os = "macos")]
+fn detect_metered() -> Option<bool> {
+    path_monitor::metered()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn detect_metered() -> Option<bool> {
+    None
+}
+
+/// An `nw_path_monitor` started on first use and kept for the app's lifetime, for both
+/// reachability and metering. Its update handler is a block with no captures, laid out by hand
+/// as a global block.
+#[cfg(target_os = "macos")]
+mod path_monitor {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::Once;
+
+    const UNKNOWN: u8 = 0;
+    const NO: u8 = 1;
+    const YES: u8 = 2;
+    static SATISFIED: AtomicU8 = AtomicU8::new(UNKNOWN);
+    static METERED: AtomicU8 = AtomicU8::new(UNKNOWN);
+    static START: Once = Once::new();
+
+    type NwObject = *mut c_void;
+    const NW_PATH_STATUS_SATISFIED: i32 = 1;
+    const BLOCK_IS_GLOBAL: i32 = 1 << 28;
+
+    #[repr(C)]
+    struct BlockDescriptor {
+        reserved: usize,
+        size: usize,
+    }
+
+    #[repr(C)]
+    struct Block {
+        isa: *const c_void,
+        flags: i32,
+        reserved: i32,
+        invoke: unsafe extern "C" fn(*const Block, NwObject),
+        descriptor: *const BlockDescriptor,
+    }
+
+    static DESCRIPTOR: BlockDescriptor = BlockDescriptor { reserved: 0, size: std::mem::size_of::<Block>() };
+
+    #[link(name = "Network", kind = "framework")]
+    extern "C" {
+        fn nw_path_monitor_create() -> NwObject;
+        fn nw_path_monitor_set_update_handler(monitor: NwObject, handler: *const Block);
+        fn nw_path_monitor_set_queue(monitor: NwObject, queue: *mut c_void);
+        fn nw_path_monitor_start(monitor: NwObject);
+        fn nw_path_get_status(path: NwObject) -> i32;
+        fn nw_path_is_expensive(path: NwObject) -> bool;
+        fn nw_path_is_constrained(path: NwObject) -> bool;
+    }
+
+    // libSystem
+    extern "C" {
+        static _NSConcreteGlobalBlock: c_void;
+        fn dispatch_get_global_queue(identifier: isize, flags: usize) -> *mut c_void;
+    }
+
+    unsafe extern "C" fn on_update(_block: *const Block, path: NwObject) {
+        let satisfied = nw_path_get_status(path) == NW_PATH_STATUS_SATISFIED;
+        let metered = satisfied && (nw_path_is_expensive(path) || nw_path_is_constrained(path));
+        SATISFIED.store(if satisfied { YES } else { NO }, Ordering::Relaxed);
+        METERED.store(if metered { YES } else { NO }, Ordering::Relaxed);
+    }
+
+    fn start() {
+        let handler: &'static Block = Box::leak(Box::new(Block {
+            isa: std::ptr::addr_of!(_NSConcreteGlobalBlock),
+            flags: BLOCK_IS_GLOBAL,
+            reserved: 0,
+            invoke: on_update,
+            descriptor: &DESCRIPTOR,
+        }));
+        unsafe {
+            let monitor = nw_path_monitor_create();
+            nw_path_monitor_set_update_handler(monitor, handler);
+            nw_path_monitor_set_queue(monitor, dispatch_get_global_queue(0, 0));
+            nw_path_monitor_start(monitor);
+        }
+    }
+
+    fn answer(state: &AtomicU8) -> Option<bool> {
+        START.call_once(start);
+        match state.load(Ordering::Relaxed) {
+            YES => Some(true),
+            NO => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn satisfied() -> Option<bool> {
+        answer(&SATISFIED)
+    }
+
+    pub fn metered() -> Option<bool> {
+        answer(&METERED)
+    }
+}
+
+/// Give a download the metered bandwidth cap when it starts on a throttled metered connection
+pub fn apply_metered_throttle<R: Runtime>(app: &AppHandle<R>, mut request: DownloadRequest) -> DownloadRequest {
+    let metered = settings::current(app).metered;
+    if metered.action == MeteredAction::Throttle
+        && is_metered(app)
+        && !request.options.allow_metered
+        && request.options.speed_limit.is_none()
+    {
+        request.options.speed_limit = Some(metered.throttle_bytes_per_sec);
+    }
+    request
+}
+
+async fn stop_held<R: Runtime>(app: &AppHandle<R>, pids: Vec<u32>, gids: Vec<String>) {
+    for pid in pids {
+        download_manager::kill_tree(pid);
+    }
+    #[cfg(not(target_os = "android"))]
+    if !gids.is_empty() {
+        if let Ok(rpc) = crate::aria2::rpc(app).await {
+            for gid in &gids {
+                let _ = rpc.pause(gid).await;
+            }
+        }
+    }
+    #[cfg(target_os = "android")]
+    let _ = (app, gids);
+}
+
+/// Record a change and act on it: hold running downloads when the network goes (or turns
+/// metered, if set to pause), requeue them when that ends
+async fn update<R: Runtime>(app: &AppHandle<R>, online: bool, metered: bool) {
+    let state = app.state::<NetworkState>();
+    let was_online = state.online.swap(online, Ordering::Relaxed);
+    let was_metered = state.metered.swap(metered, Ordering::Relaxed);
+    if was_online == online && was_metered == metered {
+        return;
+    }
+    if was_online != online {
+        eprintln!("{}", if online { "📶 Network is back" } else { "📡 Network connection lost" });
+    }
+    if was_metered != metered {
+        eprintln!("{}", if metered { "💸 Connection is metered" } else { "📶 Connection is no longer metered" });
+    }
+    let _ = app.emit("network-status", NetworkStatus { online, metered });
+
+    let manager = app.state::<DownloadManagerState>();
+    if !online {
+        let (pids, gids) = manager.lock().unwrap().hold_for_network(false);
+        stop_held(app, pids, gids).await;
+        return;
+    }
+
+    let action = settings::current(app).metered.action;
+    let gids = manager.lock().unwrap().release_after_network();
+    #[cfg(not(target_os = "android"))]
+    if !gids.is_empty() {
+        if let Ok(rpc) = crate::aria2::rpc(app).await {
+            for gid in &gids {
+                let _ = rpc.unpause(gid).await;
+            }
+        }
+    }
+    #[cfg(target_os = "android")]
+    let _ = gids;
+
+    if metered && action == MeteredAction::Pause {
+        let (pids, gids) = manager.lock().unwrap().hold_for_network(true);
+        stop_held(app, pids, gids).await;
+    }
+    // yt-dlp picks the cap up when a download starts; aria2 transfers change in place
+    #[cfg(not(target_os = "android"))]
+    if action == MeteredAction::Throttle && was_metered != metered {
+        let throttle = settings::current(app).metered.throttle_bytes_per_sec;
+        let limit = if metered { throttle } else { 0 };
+        let gids = manager.lock().unwrap().throttle_targets();
+        if !gids.is_empty() {
+            if let Ok(rpc) = crate::aria2::rpc(app).await {
+                for gid in &gids {
+                    let _ = rpc.change_option(gid, serde_json::json!({ "max-download-limit": limit.to_string() })).await;
+                }
+            }
+        }
+    }
+    download_manager::schedule(app);
+}
+
+/// A download just failed on a network error and the OS agrees the network is down
+pub fn set_offline<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let metered = is_metered(&app);
+        update(&app, false, metered).await
+    });
+}
+
+/// Poll connectivity in the background; more often while offline so downloads resume promptly
+pub fn spawn_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut checks: u32 = 0;
+        loop {
+            let online = !os_reports_offline().await;
+            let mut metered = is_metered(&app);
+            if online && checks.is_multiple_of(METERED_CHECK_EVERY) {
+                metered = tokio::task::spawn_blocking(detect_metered)
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or(false);
+            }
+            checks = checks.wrapping_add(1);
+            update(&app, online, metered).await;
+            tokio::time::sleep(if online { CHECK_INTERVAL_ONLINE } else { CHECK_INTERVAL_OFFLINE }).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_network_status<R: Runtime>(app_handle: AppHandle<R>) -> Result<NetworkStatus, String> {
+    Ok(NetworkStatus { online: is_online(&app_handle), metered: is_metered(&app_handle) })
+}
@@ -0,0 +1,78 @@
+// Kodi-style `.nfo` sidecars written next to downloaded videos from yt-dlp's info JSON, so
+// Jellyfin, Kodi and Plex (with the XBMCnfo agent) show titles, plots and dates
+use std::path::{Path, PathBuf};
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// `YYYYMMDD` to `YYYY-MM-DD`
+fn iso_date(date: &str) -> Option<String> {
+    (date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()))
+        .then(|| format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))
+}
+
+fn element(out: &mut String, name: &str, value: Option<&str>) {
+    if let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) {
+        out.push_str(&format!("  <{name}>{}</{name}>\n", escape(value)));
+    }
+}
+
+/// NFO document for one video. Entries of a series or playlist become episodes, anything else a movie.
+pub fn render(info: &serde_json::Value) -> String {
+    let text = |key: &str| info[key].as_str();
+    let season = info["season_number"].as_u64();
+    let episode = info["episode_number"].as_u64().or(info["playlist_index"].as_u64());
+    let show = text("series").or(text("playlist_title"));
+    let is_episode = show.is_some() && episode.is_some();
+    let root = if is_episode { "episodedetails" } else { "movie" };
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    out.push_str(&format!("<{}>\n", root));
+    element(&mut out, "title", text("title"));
+    if is_episode {
+        element(&mut out, "showtitle", show);
+        element(&mut out, "season", Some(&season.unwrap_or(1).to_string()));
+        element(&mut out, "episode", episode.map(|e| e.to_string()).as_deref());
+    }
+    element(&mut out, "plot", text("description"));
+    element(&mut out, "studio", text("channel").or(text("uploader")));
+    element(&mut out, "director", text("uploader"));
+    let date = text("upload_date").and_then(iso_date);
+    element(&mut out, if is_episode { "aired" } else { "premiered" }, date.as_deref());
+    element(&mut out, "year", date.as_deref().map(|d| &d[..4]));
+    if let Some(duration) = info["duration"].as_f64() {
+        element(&mut out, "runtime", Some(&((duration / 60.0).round() as u64).to_string()));
+    }
+    element(&mut out, "thumb", text("thumbnail"));
+    if let (Some(id), Some(site)) = (text("id"), text("extractor_key")) {
+        out.push_str(&format!(
+            "  <uniqueid type=\"{}\" default=\"true\">{}</uniqueid>\n",
+            escape(&site.to_lowercase()),
+            escape(id)
+        ));
+    }
+    for genre in info["categories"].as_array().into_iter().flatten().filter_map(|g| g.as_str()) {
+        element(&mut out, "genre", Some(genre));
+    }
+    for tag in info["tags"].as_array().into_iter().flatten().filter_map(|t| t.as_str()) {
+        element(&mut out, "tag", Some(tag));
+    }
+    out.push_str(&format!("</{}>\n", root));
+    out
+}
+
+/// `video.nfo` beside `video.mkv`
+pub fn sidecar_path(media: &Path) -> PathBuf {
+    media.with_extension("nfo")
+}
+
+pub fn write(media: &Path, info: &serde_json::Value) -> Result<PathBuf, String> {
+    let path = sidecar_path(media);
+    std::fs::write(&path, render(info)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
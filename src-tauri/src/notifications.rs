@@ -0,0 +1,155 @@
+// Desktop notifications for download events, filtered by the per-event notification settings.
+// Action buttons ("Open file", "Show in folder", "Retry") are only shown on Android and iOS: the
+// desktop notification plugin neither draws them nor reports clicks, so desktop notifications
+// are informational only.
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::i18n::t;
+use crate::settings::{self, NotificationSettings};
+
+/// Longest a notification waits for its thumbnail before showing without one
+const THUMBNAIL_TIMEOUT: Duration = Duration::from_secs(5);
+const THUMBNAIL_SIZE: u64 = 256;
+const MAX_BODY_CHARS: usize = 200;
+
+/// The download a notification's action buttons act on, sent back with the action. The file
+/// is looked up by id when the action runs, so a notification can only open what it reported.
+#[derive(Debug, Clone)]
+pub struct NotificationTarget {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    Started,
+    Completed,
+    Failed,
+    BatchComplete,
+    /// A URL queued from outside the window (shortcut, context menu, link handler). This is the
+    /// only feedback there, so it is not separately switchable.
+    Queued,
+}
+
+impl NotificationEvent {
+    /// Action type the frontend registers buttons for: "Open file" / "Show in folder" on
+    /// `download-complete`, "Retry" on `download-failed`
+    fn action_type(self) -> Option<&'static str> {
+        match self {
+            Self::Completed => Some("download-complete"),
+            Self::Failed => Some("download-failed"),
+            Self::Started | Self::BatchComplete | Self::Queued => None,
+        }
+    }
+
+    fn enabled(self, settings: &NotificationSettings) -> bool {
+        match self {
+            Self::Started => settings.on_start,
+            Self::Completed => settings.on_complete,
+            Self::Failed => settings.on_error,
+            Self::BatchComplete => settings.on_batch_complete,
+            Self::Queued => true,
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_BODY_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_BODY_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Show a notification for `event` if the settings allow it. `video_url` supplies the thumbnail.
+/// Runs in the background so a slow thumbnail fetch never holds up the download queue.
+pub fn notify<R: Runtime>(
+    app: &AppHandle<R>,
+    event: NotificationEvent,
+    title: &str,
+    body: &str,
+    video_url: Option<&str>,
+    target: Option<NotificationTarget>,
+) {
+    let settings = settings::current(app);
+    if !settings.general.notifications || !event.enabled(&settings.notifications) {
+        return;
+    }
+
+    let app = app.clone();
+    let title = truncate(title);
+    let body = truncate(body);
+    let video_url = video_url.filter(|_| settings.notifications.show_thumbnail).map(str::to_string);
+    tauri::async_runtime::spawn(async move {
+        let icon = match video_url {
+            Some(url) => tokio::time::timeout(
+                THUMBNAIL_TIMEOUT,
+                crate::thumbnails::cached_path(&app, &url, Some(THUMBNAIL_SIZE)),
+            )
+            .await
+            .ok()
+            .and_then(Result::ok),
+            None => None,
+        };
+
+        let mut builder = app.notification().builder().title(title).body(body);
+        if let Some(icon) = icon {
+            builder = builder.icon(icon.to_string_lossy().to_string());
+        }
+        // Desktop ignores these
+        if let (Some(action_type), Some(target)) = (event.action_type(), target) {
+            builder = builder.action_type_id(action_type).extra("id", target.id);
+        }
+        if let Err(e) = builder.show() {
+            eprintln!("⚠️  Failed to show notification: {}", e);
+        }
+    });
+}
+
+/// Report a download queued from outside the window: the URL, or why it could not be queued
+pub fn notify_queued<R: Runtime>(app: &AppHandle<R>, result: Result<String, String>) {
+    match result {
+        Ok(url) => notify(app, NotificationEvent::Queued, &t("queued"), &url, Some(&url), None),
+        Err(e) => {
+            eprintln!("⚠️  Failed to queue download: {}", e);
+            notify(app, NotificationEvent::Queued, &t("queue-failed"), &e, None, None);
+        }
+    }
+}
+
+/// The main file of finished download `id`, from its history entry
+fn downloaded_file<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<PathBuf, String> {
+    let entry = crate::history::find(app, id).ok_or_else(|| format!("Unknown download id: {}", id))?;
+    if entry.status != crate::download_manager::JobStatus::Completed {
+        return Err("This download did not finish".to_string());
+    }
+    let file = entry.files.first().ok_or("This download saved no files")?;
+    let path = PathBuf::from(&file.path);
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(format!("{} no longer exists", path.display()))
+    }
+}
+
+/// "Open file" action: open a finished download in its default app
+#[tauri::command]
+pub async fn open_downloaded_file<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    let path = downloaded_file(&app, &id)?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// "Show in folder" action: reveal a finished download in the file manager
+#[tauri::command]
+pub async fn show_in_folder<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    let path = downloaded_file(&app, &id)?;
+    app.opener()
+        .reveal_item_in_dir(&path)
+        .map_err(|e| format!("Failed to show {}: {}", path.display(), e))
+}
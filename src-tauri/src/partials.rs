@@ -0,0 +1,307 @@
+// Leftovers from interrupted downloads: yt-dlp `.part`/`.ytdl` files and aria2 `.aria2` control files,
+// plus the `_temp` and working files post-processing leaves behind when it is cut short
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::download_manager::{self, DownloadManagerState, JobStatus};
+
+/// A restored download whose partial files are still on disk
+#[derive(Debug, Serialize, Clone)]
+pub struct IncompleteDownload {
+    pub id: String,
+    pub url: String,
+    pub output_folder: String,
+    pub partial_files: Vec<String>,
+    pub partial_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct IncompleteScan {
+    /// Can be continued from where they stopped
+    pub resumable: Vec<IncompleteDownload>,
+    /// Partial files in the same folders that no saved download accounts for
+    pub orphaned: Vec<String>,
+}
+
+pub fn is_partial(name: &str) -> bool {
+    name.ends_with(".part") || name.ends_with(".ytdl") || name.ends_with(".aria2") || name.contains(".part-Frag")
+}
+
+/// Partial files in `folder`, not recursive
+pub fn scan_folder(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(folder) else { return Vec::new() };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| is_partial(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// Partial files belonging to a destination yt-dlp announced: `<dest>.part`, `<dest>.part-Frag3`, `<dest>.ytdl`…
+fn partials_for(destination: &Path, candidates: &[PathBuf]) -> Vec<PathBuf> {
+    let Some(name) = destination.file_name().map(|n| n.to_string_lossy().to_string()) else { return Vec::new() };
+    candidates
+        .iter()
+        .filter(|path| path.parent() == destination.parent())
+        .filter(|path| {
+            path.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.len() > name.len() && n.starts_with(&name))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Folders downloads have gone to: the defaults, the queue and history
+fn known_folders<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let settings = crate::settings::current(app);
+    let mut folders: Vec<String> = [
+        settings.general.default_output_folder,
+        settings.folders.music,
+        settings.folders.videos,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    folders.extend(app.state::<DownloadManagerState>().lock().unwrap().output_folders());
+    folders.extend(
+        app.state::<crate::history::HistoryState>()
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.output_folder.clone()),
+    );
+    folders.retain(|folder| !folder.trim().is_empty());
+    folders.sort();
+    folders.dedup();
+    folders
+}
+
+pub fn scan<R: Runtime>(app: &AppHandle<R>) -> IncompleteScan {
+    let jobs: Vec<(String, String, String, Vec<String>)> = {
+        let state = app.state::<DownloadManagerState>();
+        let manager = state.lock().unwrap();
+        manager
+            .interrupted()
+            .map(|job| {
+                let destinations = job.progress.lock().unwrap().destinations.clone();
+                (job.id.clone(), job.request.url.clone(), job.request.output_folder.clone(), destinations)
+            })
+            .collect()
+    };
+
+    let folders = known_folders(app);
+    let candidates: Vec<PathBuf> = folders.iter().flat_map(|folder| scan_folder(Path::new(folder))).collect();
+
+    let mut scan = IncompleteScan::default();
+    let mut claimed: Vec<PathBuf> = Vec::new();
+    for (id, url, output_folder, destinations) in jobs {
+        let partials: Vec<PathBuf> = destinations
+            .iter()
+            .flat_map(|destination| partials_for(Path::new(destination), &candidates))
+            .collect();
+        if partials.is_empty() {
+            continue;
+        }
+        let partial_bytes = partials
+            .iter()
+            .filter_map(|path| std::fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .sum();
+        claimed.extend(partials.iter().cloned());
+        scan.resumable.push(IncompleteDownload {
+            id,
+            url,
+            output_folder,
+            partial_files: partials.iter().map(|path| path.to_string_lossy().to_string()).collect(),
+            partial_bytes,
+        });
+    }
+    scan.orphaned = candidates
+        .into_iter()
+        .filter(|path| !claimed.contains(path))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    scan
+}
+
+/// Look for resumable partial files once the saved queue is back, and tell the frontend about them
+pub fn spawn_startup_scan<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let found = scan(&app);
+        if !found.resumable.is_empty() {
+            eprintln!("📂 Found {} download(s) that can be resumed", found.resumable.len());
+            let _ = app.emit("incomplete-downloads", found);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_incomplete_downloads<R: Runtime>(app_handle: AppHandle<R>) -> Result<IncompleteScan, String> {
+    let app = app_handle.clone();
+    tokio::task::spawn_blocking(move || scan(&app))
+        .await
+        .map_err(|e| format!("Scan failed: {}", e))
+}
+
+/// Queue interrupted downloads again with `--continue`; all resumable ones when `ids` is unset
+#[tauri::command]
+pub async fn resume_incomplete_downloads<R: Runtime>(
+    app_handle: AppHandle<R>,
+    ids: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let app = app_handle.clone();
+    let resumable = tokio::task::spawn_blocking(move || scan(&app))
+        .await
+        .map_err(|e| format!("Scan failed: {}", e))?
+        .resumable;
+
+    let mut resumed = Vec::new();
+    {
+        let state = app_handle.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        for incomplete in resumable {
+            if ids.as_ref().is_some_and(|ids| !ids.contains(&incomplete.id)) {
+                continue;
+            }
+            let Some(job) = manager.job_mut(&incomplete.id).filter(|job| job.status == JobStatus::Interrupted) else {
+                continue;
+            };
+            job.request.options.continue_partial = true;
+            job.status = JobStatus::Queued;
+            job.progress.lock().unwrap().status = "queued".to_string();
+            eprintln!("⏯️  Resuming {} from {} partial bytes", incomplete.url, incomplete.partial_bytes);
+            resumed.push(incomplete.id);
+        }
+    }
+    download_manager::schedule(&app_handle);
+    Ok(resumed)
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CleanupReport {
+    pub files: Vec<String>,
+    pub bytes_reclaimed: u64,
+    /// Nothing was deleted; `files` lists what would have been
+    pub dry_run: bool,
+}
+
+/// Files yt-dlp announced for downloads in the queue and the history
+fn recorded_destinations<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let mut destinations = app.state::<DownloadManagerState>().lock().unwrap().destinations();
+    destinations.extend(
+        app.state::<crate::history::HistoryState>()
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|entry| entry.destinations.clone()),
+    );
+    destinations.sort();
+    destinations.dedup();
+    destinations.into_iter().map(PathBuf::from).collect()
+}
+
+/// Whether this app left `path` behind: `name.udl-tmp.ext` from in-place ffmpeg steps, or a
+/// partial or `name_temp.ext` trimming input of a download it recorded. Files that merely look
+/// like leftovers may belong to another program and are not touched.
+fn is_leftover(path: &Path, destinations: &[PathBuf]) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if name.contains(".udl-tmp.") {
+        return true;
+    }
+    if !is_partial(&name) && !name.contains("_temp.") {
+        return false;
+    }
+    let candidate = [path.to_path_buf()];
+    destinations
+        .iter()
+        .any(|destination| destination == path || !partials_for(destination, &candidate).is_empty())
+}
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| size_of(&e.path())).sum())
+            .unwrap_or(0)
+    } else {
+        std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0)
+    }
+}
+
+fn older_than(path: &Path, cutoff: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|modified| modified < cutoff)
+}
+
+/// Remove stale leftovers older than `max_age`. Partials that a restored download can still
+/// resume are kept, as is anything in use by a running download.
+pub fn clean<R: Runtime>(app: &AppHandle<R>, max_age: Duration, dry_run: bool) -> CleanupReport {
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let resumable: Vec<String> = scan(app).resumable.into_iter().flat_map(|d| d.partial_files).collect();
+    let destinations = recorded_destinations(app);
+
+    let mut stale: Vec<PathBuf> = Vec::new();
+    for folder in known_folders(app) {
+        let Ok(entries) = std::fs::read_dir(&folder) else { continue };
+        stale.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+                .map(|entry| entry.path())
+                .filter(|path| is_leftover(path, &destinations)),
+        );
+    }
+    // Secret files, subtitle copies and other scratch files this app puts in the system temp dir
+    if let Ok(entries) = std::fs::read_dir(std::env::temp_dir()) {
+        stale.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with("udl-"))
+                .map(|entry| entry.path()),
+        );
+    }
+
+    let mut report = CleanupReport { dry_run, ..Default::default() };
+    for path in stale {
+        let display = path.to_string_lossy().to_string();
+        if resumable.contains(&display) || !older_than(&path, cutoff) {
+            continue;
+        }
+        let bytes = size_of(&path);
+        if !dry_run {
+            let removed = if path.is_dir() { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+            if let Err(e) = removed {
+                eprintln!("⚠️  Could not remove {}: {}", display, e);
+                continue;
+            }
+        }
+        report.bytes_reclaimed += bytes;
+        report.files.push(display);
+    }
+    eprintln!(
+        "🧹 {} {} leftover file(s), {} bytes",
+        if dry_run { "Found" } else { "Removed" },
+        report.files.len(),
+        report.bytes_reclaimed
+    );
+    report
+}
+
+/// Delete temp files and partial downloads older than `olderThanDays` (7 by default, at least 1
+/// so files a running download is still writing are never touched)
+#[tauri::command]
+pub async fn clean_temp_files<R: Runtime>(
+    app_handle: AppHandle<R>,
+    olderThanDays: Option<u32>,
+    dryRun: Option<bool>,
+) -> Result<CleanupReport, String> {
+    let max_age = Duration::from_secs(u64::from(olderThanDays.unwrap_or(7).max(1)) * 24 * 60 * 60);
+    let app = app_handle.clone();
+    tokio::task::spawn_blocking(move || clean(&app, max_age, dryRun.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Cleanup failed: {}", e))
+}
@@ -0,0 +1,87 @@
+// Playlist inspection, so the user can pick which entries to download
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::binary_manager;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistEntry {
+    /// 1-based position in the playlist, as used by `--playlist-items`
+    pub index: u32,
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub duration: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistInfo {
+    pub title: String,
+    pub uploader: String,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// Check a `--playlist-items` selection such as `1,3,5-7` or `10:20`
+pub fn validate_items(items: &str) -> Result<(), String> {
+    let valid_index = |s: &str| !s.is_empty() && s.trim_start_matches('-').chars().all(|c| c.is_ascii_digit());
+    for part in items.split(',').map(str::trim) {
+        let valid = match part.split_once(['-', ':']) {
+            // A leading '-' is a negative index (counted from the end), not a range
+            Some(("", _)) => valid_index(part),
+            Some((start, end)) => valid_index(start.trim()) && (end.trim().is_empty() || valid_index(end.trim())),
+            None => valid_index(part),
+        };
+        if !valid {
+            return Err(format!("Invalid playlist selection \"{}\"; use indices and ranges like 1,3,5-7", part));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_playlist_entries<R: Runtime>(app_handle: AppHandle<R>, url: String) -> Result<PlaylistInfo, String> {
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+
+    let output = binary_manager::tool_command(&paths.yt_dlp)
+        .arg("--flat-playlist")
+        .arg("--dump-single-json")
+        .arg("--no-warnings")
+        .arg("--")
+        .arg(&url)
+        .output()
+        .map_err(|e| format!("Failed to get playlist info: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to get playlist info: {}", stderr.trim()));
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse playlist info: {}", e))?;
+    let Some(entries) = json["entries"].as_array() else {
+        return Err("Not a playlist".to_string());
+    };
+
+    let entries = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| PlaylistEntry {
+            index: entry["playlist_index"].as_u64().unwrap_or(i as u64 + 1) as u32,
+            id: entry["id"].as_str().unwrap_or("").to_string(),
+            title: entry["title"].as_str().unwrap_or("Unknown Title").to_string(),
+            url: entry["url"].as_str().unwrap_or("").to_string(),
+            duration: entry["duration"].as_f64().unwrap_or(0.0),
+        })
+        .collect();
+
+    Ok(PlaylistInfo {
+        title: json["title"].as_str().unwrap_or("Unknown Playlist").to_string(),
+        uploader: json["uploader"]
+            .as_str()
+            .or_else(|| json["channel"].as_str())
+            .unwrap_or("Unknown Uploader")
+            .to_string(),
+        entries,
+    })
+}
@@ -0,0 +1,206 @@
+// Local podcast feeds: an RSS file in a download folder that lists its audio files as episodes,
+// rebuilt whenever a download into that folder finishes. Subscribe to the feed file (or serve the
+// folder over HTTP and set a base URL) to listen in any podcast app.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Runtime};
+
+use crate::{formats, stats, storage};
+
+const FEEDS_FILE: &str = "podcast_feeds.json";
+const FEED_FILE_NAME: &str = "feed.xml";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PodcastFeed {
+    pub name: String,
+    /// Channel or playlist the episodes come from, shown as the feed's link
+    pub source_url: String,
+    /// Folder the episodes are downloaded into; the feed is written here as feed.xml
+    pub folder: String,
+    /// Public URL of `folder` when it is served over HTTP; `file://` links are used otherwise
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl PodcastFeed {
+    pub fn feed_path(&self) -> PathBuf {
+        Path::new(&self.folder).join(FEED_FILE_NAME)
+    }
+
+    fn episode_url(&self, file: &Path) -> String {
+        let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match &self.base_url {
+            Some(base) => format!("{}/{}", base.trim_end_matches('/'), percent_encode(&name)),
+            None => url::Url::from_file_path(file).map(|u| u.to_string()).unwrap_or(name),
+        }
+    }
+}
+
+fn percent_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn mime_type(ext: &str) -> &'static str {
+    match ext {
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "wav" => "audio/wav",
+        _ => "application/octet-stream",
+    }
+}
+
+struct Episode {
+    path: PathBuf,
+    title: String,
+    size: u64,
+    modified_ms: u64,
+    mime: &'static str,
+}
+
+/// Audio files in the feed folder, newest first
+fn episodes(folder: &Path) -> Result<Vec<Episode>, String> {
+    let entries = std::fs::read_dir(folder).map_err(|e| format!("Cannot read {}: {}", folder.display(), e))?;
+    let mut episodes: Vec<Episode> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let ext = path.extension()?.to_string_lossy().to_lowercase();
+            if !formats::is_audio(&ext) {
+                return None;
+            }
+            let meta = entry.metadata().ok()?;
+            let modified_ms = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as u64);
+            Some(Episode {
+                title: path.file_stem()?.to_string_lossy().to_string(),
+                size: meta.len(),
+                modified_ms,
+                mime: mime_type(&ext),
+                path,
+            })
+        })
+        .collect();
+    episodes.sort_by_key(|e| std::cmp::Reverse(e.modified_ms));
+    Ok(episodes)
+}
+
+fn render(feed: &PodcastFeed, episodes: &[Episode]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n<channel>\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape(&feed.name)));
+    xml.push_str(&format!("  <link>{}</link>\n", escape(&feed.source_url)));
+    xml.push_str(&format!("  <description>{}</description>\n", escape(&format!("Downloaded from {}", feed.source_url))));
+    xml.push_str(&format!("  <lastBuildDate>{}</lastBuildDate>\n", stats::format_rfc2822(crate::download_manager::now_millis())));
+    for episode in episodes {
+        let url = feed.episode_url(&episode.path);
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&episode.title)));
+        xml.push_str(&format!(
+            "    <enclosure url=\"{}\" length=\"{}\" type=\"{}\"/>\n",
+            escape(&url),
+            episode.size,
+            episode.mime
+        ));
+        xml.push_str(&format!("    <guid isPermaLink=\"false\">{}</guid>\n", escape(&url)));
+        xml.push_str(&format!("    <pubDate>{}</pubDate>\n", stats::format_rfc2822(episode.modified_ms)));
+        xml.push_str("  </item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Rewrite a feed's feed.xml from the audio files currently in its folder
+pub fn regenerate(feed: &PodcastFeed) -> Result<PathBuf, String> {
+    let episodes = episodes(Path::new(&feed.folder))?;
+    let path = feed.feed_path();
+    let tmp = path.with_extension("xml.tmp");
+    std::fs::write(&tmp, render(feed, &episodes)).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn load_all<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<PodcastFeed>, String> {
+    let path = storage::app_data_file(app, FEEDS_FILE)?;
+    Ok(storage::read_json(&path)?.unwrap_or_default())
+}
+
+fn save_all<R: Runtime>(app: &AppHandle<R>, feeds: &[PodcastFeed]) -> Result<(), String> {
+    let path = storage::app_data_file(app, FEEDS_FILE)?;
+    storage::write_json(&path, &feeds)
+}
+
+/// Refresh the feeds for a folder after an audio download into it finished
+pub fn on_download_finished<R: Runtime>(app: &AppHandle<R>, download_type: &str, output_folder: &str) {
+    if !formats::is_audio(download_type) {
+        return;
+    }
+    let feeds = match load_all(app) {
+        Ok(feeds) => feeds,
+        Err(e) => {
+            eprintln!("⚠️  Failed to load podcast feeds: {}", e);
+            return;
+        }
+    };
+    for feed in feeds.iter().filter(|feed| Path::new(&feed.folder) == Path::new(output_folder)) {
+        match regenerate(feed) {
+            Ok(path) => eprintln!("🎙️ Updated podcast feed {}", path.display()),
+            Err(e) => eprintln!("⚠️  Failed to update podcast feed \"{}\": {}", feed.name, e),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_podcast_feeds<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<PodcastFeed>, String> {
+    load_all(&app_handle)
+}
+
+/// Create or replace a feed (names are case-insensitive) and write it right away
+#[tauri::command]
+pub async fn save_podcast_feed<R: Runtime>(app_handle: AppHandle<R>, feed: PodcastFeed) -> Result<String, String> {
+    if feed.name.trim().is_empty() {
+        return Err("Feed name cannot be empty".to_string());
+    }
+    if !Path::new(&feed.folder).is_dir() {
+        return Err(format!("Folder does not exist: {}", feed.folder));
+    }
+    if let Some(base) = &feed.base_url {
+        match url::Url::parse(base) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => return Err(format!("Base URL must be an http(s) URL: {}", base)),
+        }
+    }
+
+    let path = regenerate(&feed)?;
+    let mut feeds = load_all(&app_handle)?;
+    feeds.retain(|f| !f.name.eq_ignore_ascii_case(&feed.name));
+    feeds.push(feed);
+    save_all(&app_handle, &feeds)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn delete_podcast_feed<R: Runtime>(app_handle: AppHandle<R>, name: String) -> Result<(), String> {
+    let mut feeds = load_all(&app_handle)?;
+    let before = feeds.len();
+    feeds.retain(|f| !f.name.eq_ignore_ascii_case(&name));
+    if feeds.len() == before {
+        return Err(format!("No podcast feed named \"{}\"", name));
+    }
+    save_all(&app_handle, &feeds)
+}
@@ -0,0 +1,186 @@
+// What to do once the download queue drains: nothing, quit U-Download, or put the
+// machine to sleep / hibernate / shut it down after a cancellable countdown.
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::download_manager::DownloadManagerState;
+
+const COUNTDOWN_SECS: u32 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PostQueueAction {
+    #[default]
+    None,
+    Quit,
+    Sleep,
+    Hibernate,
+    Shutdown,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CountdownEvent {
+    pub action: PostQueueAction,
+    pub seconds_remaining: u32,
+}
+
+#[derive(Default)]
+pub struct PostQueue {
+    action: PostQueueAction,
+    /// Bumped on every start or cancel so a stale countdown task notices and stops
+    countdown_seq: u64,
+    counting_down: bool,
+}
+
+pub type PostQueueState = Arc<Mutex<PostQueue>>;
+
+/// Called by the download manager when the last queued download finishes
+pub fn on_queue_drained<R: Runtime>(app: &AppHandle<R>) {
+    let (action, seq) = {
+        let state = app.state::<PostQueueState>();
+        let mut post_queue = state.lock().unwrap();
+        if post_queue.action == PostQueueAction::None || post_queue.counting_down {
+            return;
+        }
+        post_queue.countdown_seq += 1;
+        post_queue.counting_down = true;
+        (post_queue.action, post_queue.countdown_seq)
+    };
+
+    eprintln!("⏳ Queue finished, running {:?} in {}s unless cancelled", action, COUNTDOWN_SECS);
+    tauri::async_runtime::spawn(run_countdown(app.clone(), action, seq));
+}
+
+async fn run_countdown<R: Runtime>(app: AppHandle<R>, action: PostQueueAction, seq: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    for seconds_remaining in (0..=COUNTDOWN_SECS).rev() {
+        interval.tick().await;
+
+        let cancelled = app.state::<PostQueueState>().lock().unwrap().countdown_seq != seq;
+        let queue_busy = {
+            let state = app.state::<DownloadManagerState>();
+            let summary = state.lock().unwrap().queue_progress();
+            summary.active > 0 || summary.queued > 0
+        };
+        if cancelled || queue_busy {
+            finish_countdown(&app, seq);
+            let _ = app.emit("post-queue-cancelled", action);
+            return;
+        }
+
+        let _ = app.emit("post-queue-countdown", CountdownEvent { action, seconds_remaining });
+    }
+
+    finish_countdown(&app, seq);
+    // One-shot: a new batch of downloads must opt in again
+    app.state::<PostQueueState>().lock().unwrap().action = PostQueueAction::None;
+
+    if let Err(e) = run_action(&app, action) {
+        eprintln!("❌ Post-queue action failed: {}", e);
+        let _ = app.emit("post-queue-error", e);
+    }
+}
+
+fn finish_countdown<R: Runtime>(app: &AppHandle<R>, seq: u64) {
+    let state = app.state::<PostQueueState>();
+    let mut post_queue = state.lock().unwrap();
+    if post_queue.countdown_seq == seq {
+        post_queue.counting_down = false;
+    }
+}
+
+fn run_action<R: Runtime>(app: &AppHandle<R>, action: PostQueueAction) -> Result<(), String> {
+    eprintln!("⚡ Running post-queue action {:?}", action);
+    match action {
+        PostQueueAction::None => Ok(()),
+        PostQueueAction::Quit => {
+            #[cfg(not(target_os = "android"))]
+            crate::aria2::shutdown(&app.state::<crate::aria2::Aria2State>());
+            app.exit(0);
+            Ok(())
+        }
+        _ => {
+            let (program, args) = system_command(action)?;
+            let status = Command::new(program)
+                .args(args)
+                .status()
+                .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{} exited with {}", program, status))
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn system_command(action: PostQueueAction) -> Result<(&'static str, Vec<&'static str>), String> {
+    match action {
+        PostQueueAction::Sleep => Ok(("rundll32.exe", vec!["powrprof.dll,SetSuspendState", "0,1,0"])),
+        PostQueueAction::Hibernate => Ok(("shutdown", vec!["/h"])),
+        PostQueueAction::Shutdown => Ok(("shutdown", vec!["/s", "/t", "0"])),
+        _ => Err(format!("{:?} is not a system action", action)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_command(action: PostQueueAction) -> Result<(&'static str, Vec<&'static str>), String> {
+    match action {
+        PostQueueAction::Sleep => Ok(("pmset", vec!["sleepnow"])),
+        PostQueueAction::Shutdown => Ok(("osascript", vec!["-e", "tell app \"System Events\" to shut down"])),
+        _ => Err(format!("{:?} is not supported on macOS", action)),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "android")))]
+fn system_command(action: PostQueueAction) -> Result<(&'static str, Vec<&'static str>), String> {
+    match action {
+        PostQueueAction::Sleep => Ok(("systemctl", vec!["suspend"])),
+        PostQueueAction::Hibernate => Ok(("systemctl", vec!["hibernate"])),
+        PostQueueAction::Shutdown => Ok(("systemctl", vec!["poweroff"])),
+        _ => Err(format!("{:?} is not a system action", action)),
+    }
+}
+
+#[cfg(target_os = "android")]
+fn system_command(action: PostQueueAction) -> Result<(&'static str, Vec<&'static str>), String> {
+    Err(format!("{:?} is not supported on Android", action))
+}
+
+#[tauri::command]
+pub async fn get_post_queue_action<R: Runtime>(app_handle: AppHandle<R>) -> Result<PostQueueAction, String> {
+    Ok(app_handle.state::<PostQueueState>().lock().unwrap().action)
+}
+
+#[tauri::command]
+pub async fn set_post_queue_action<R: Runtime>(
+    app_handle: AppHandle<R>,
+    action: PostQueueAction,
+) -> Result<PostQueueAction, String> {
+    if !matches!(action, PostQueueAction::None | PostQueueAction::Quit) {
+        system_command(action)?;
+    }
+    let state = app_handle.state::<PostQueueState>();
+    let mut post_queue = state.lock().unwrap();
+    post_queue.action = action;
+    if action == PostQueueAction::None {
+        post_queue.countdown_seq += 1;
+    }
+    Ok(action)
+}
+
+/// Stop a running countdown; the chosen action stays set for the next time the queue drains
+#[tauri::command]
+pub async fn cancel_post_queue_action<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    let state = app_handle.state::<PostQueueState>();
+    let mut post_queue = state.lock().unwrap();
+    if !post_queue.counting_down {
+        return Err("No post-queue action is pending".to_string());
+    }
+    post_queue.countdown_seq += 1;
+    Ok(())
+}
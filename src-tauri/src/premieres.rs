@@ -0,0 +1,194 @@
+// Wait-and-record for premieres and live streams that have not started: the job stays in the
+// list as `scheduled` while a watcher counts down to the announced start, then polls the page
+// until it goes live and hands the job to the scheduler
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::download_manager::{
+    self, DownloadManagerState, DownloadOptions, DownloadRequest, JobStatus,
+};
+use crate::{settings, storage};
+
+/// Countdown events while the start is still far off
+const COUNTDOWN_INTERVAL: Duration = Duration::from_secs(10);
+/// Page checks once the start is near or past, or when no start time was announced
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Checking starts this long before the announced time, since streams often go live early
+const EARLY_POLL_SECS: i64 = 5 * 60;
+/// Give up on streams that are still not live this long after their announced start
+const MAX_DELAY_SECS: i64 = 6 * 60 * 60;
+
+/// Emitted as `premiere-countdown` while a scheduled download waits
+#[derive(Debug, Serialize, Clone)]
+pub struct PremiereCountdown {
+    pub id: String,
+    /// Announced start in Unix seconds, if the site gave one
+    pub starts_at: Option<i64>,
+    /// Negative once the announced start has passed
+    pub seconds_remaining: Option<i64>,
+    /// `waiting` until the start is near, then `checking`
+    pub phase: &'static str,
+}
+
+fn now_secs() -> i64 {
+    (download_manager::now_millis() / 1000) as i64
+}
+
+fn is_upcoming(metadata: &serde_json::Value) -> bool {
+    metadata["live_status"].as_str() == Some("is_upcoming")
+}
+
+fn is_scheduled<R: Runtime>(app: &AppHandle<R>, id: &str) -> bool {
+    app.state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .job(id)
+        .is_some_and(|job| job.status == JobStatus::Scheduled)
+}
+
+fn emit_countdown<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    starts_at: Option<i64>,
+    phase: &'static str,
+) {
+    let _ = app.emit(
+        "premiere-countdown",
+        PremiereCountdown {
+            id: id.to_string(),
+            starts_at,
+            seconds_remaining: starts_at.map(|start| start - now_secs()),
+            phase,
+        },
+    );
+}
+
+fn fail<R: Runtime>(app: &AppHandle<R>, id: &str, message: String) {
+    let progress = {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let Some(job) = manager
+            .job_mut(id)
+            .filter(|job| job.status == JobStatus::Scheduled)
+        else {
+            return;
+        };
+        job.status = JobStatus::Failed;
+        let mut progress = job.progress.lock().unwrap();
+        progress.status = "error".to_string();
+        progress.clone()
+    };
+    eprintln!("⏰ Gave up waiting for {}: {}", id, message);
+    let _ = app.emit("download-progress", progress);
+    let _ = app.emit(
+        "download-error",
+        crate::errors::DownloadErrorEvent::new(id, &message, None),
+    );
+    download_manager::persist(app);
+}
+
+/// Count down, then poll until the stream is live (or has ended, for premieres that were missed)
+fn spawn_watcher<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    url: String,
+    mut starts_at: Option<i64>,
+) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if !is_scheduled(&app, &id) {
+                return;
+            }
+            if starts_at.is_some_and(|start| start - now_secs() > EARLY_POLL_SECS) {
+                emit_countdown(&app, &id, starts_at, "waiting");
+                tokio::time::sleep(COUNTDOWN_INTERVAL).await;
+                continue;
+            }
+
+            emit_countdown(&app, &id, starts_at, "checking");
+            match crate::fetch_fresh_metadata_json(&app, &url).await {
+                Ok(metadata) if !is_upcoming(&metadata) => {
+                    let released = app
+                        .state::<DownloadManagerState>()
+                        .lock()
+                        .unwrap()
+                        .release_scheduled(&id);
+                    if released {
+                        eprintln!("🔴 {} is live, starting the recording", url);
+                        download_manager::schedule(&app);
+                    }
+                    return;
+                }
+                // Streams get rescheduled; follow the new start time
+                Ok(metadata) => starts_at = metadata["release_timestamp"].as_i64().or(starts_at),
+                Err(e) => eprintln!("⚠️  Failed to check {}: {}", url, e),
+            }
+
+            if starts_at.is_some_and(|start| now_secs() - start > MAX_DELAY_SECS) {
+                fail(
+                    &app,
+                    &id,
+                    format!(
+                        "The stream did not start within {} hours of its announced time",
+                        MAX_DELAY_SECS / 3600
+                    ),
+                );
+                return;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Queue an upcoming premiere or live stream to be recorded as soon as it starts
+#[tauri::command]
+pub async fn queue_upcoming_stream<R: Runtime>(
+    app_handle: AppHandle<R>,
+    url: String,
+    downloadType: Option<String>,
+    quality: Option<String>,
+    outputFolder: Option<String>,
+    options: Option<DownloadOptions>,
+) -> Result<String, String> {
+    let mut options = options.unwrap_or_default();
+    options.validate()?;
+    let metadata = crate::fetch_fresh_metadata_json(&app_handle, &url).await?;
+    if !is_upcoming(&metadata) {
+        return Err(
+            "This video is not an upcoming premiere or stream; download it normally".to_string(),
+        );
+    }
+    // A stream runs as long as it runs
+    options.timeout_minutes.get_or_insert(0);
+
+    let current = settings::current(&app_handle);
+    let (download_type, quality) = current.resolve_format(&url, downloadType, quality);
+    let output_folder = current.resolve_output_folder(outputFolder, &download_type, None)?;
+    let output_folder = storage::validate_output_folder(&app_handle, &output_folder)?;
+    let starts_at = metadata["release_timestamp"].as_i64();
+
+    let id = {
+        let state = app_handle.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        let id = manager.enqueue_scheduled(DownloadRequest {
+            url: url.clone(),
+            download_type,
+            quality,
+            output_folder,
+            start_time: None,
+            end_time: None,
+            filename: None,
+            options,
+        });
+        if let (Some(job), Some(title)) = (manager.job(&id), metadata["title"].as_str()) {
+            job.progress.lock().unwrap().title = title.to_string();
+        }
+        id
+    };
+    download_manager::persist(&app_handle);
+    eprintln!("⏰ Waiting for {} (starts at {:?})", url, starts_at);
+    emit_countdown(&app_handle, &id, starts_at, "waiting");
+    spawn_watcher(app_handle, id.clone(), url, starts_at);
+    Ok(id)
+}
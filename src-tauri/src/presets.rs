@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::download_manager::{self, DownloadManagerState, DownloadOptions, DownloadRequest, JobStatus};
+use crate::history::{self, HistoryEntry};
+use crate::{settings, storage};
+
+const PRESETS_FILE: &str = "presets.json";
+
+/// A named combination of download settings, e.g. "Podcast audio" or "Archive 1080p"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub download_type: String,
+    pub quality: String,
+    /// Used when the download does not name a folder itself
+    #[serde(default)]
+    pub output_folder: Option<String>,
+    /// Category folder to use instead of the one implied by the download type, e.g. music videos to Music
+    #[serde(default)]
+    pub category: Option<settings::MediaCategory>,
+    #[serde(default, flatten)]
+    pub options: DownloadOptions,
+}
+
+impl Preset {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if !settings::DOWNLOAD_TYPES.contains(&self.download_type.as_str()) {
+            return Err(format!("Unsupported download type: {}", self.download_type));
+        }
+        if !settings::QUALITIES.contains(&self.quality.as_str()) {
+            return Err(format!("Unsupported quality: {}", self.quality));
+        }
+        self.options.validate()
+    }
+}
+
+pub fn load_all<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<Preset>, String> {
+    let path = storage::app_data_file(app, PRESETS_FILE)?;
+    Ok(storage::read_json(&path)?.unwrap_or_default())
+}
+
+pub fn save_all<R: Runtime>(app: &AppHandle<R>, presets: &[Preset]) -> Result<(), String> {
+    let path = storage::app_data_file(app, PRESETS_FILE)?;
+    storage::write_json(&path, &presets)
+}
+
+pub fn find<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<Preset, String> {
+    load_all(app)?
+        .into_iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("No preset named \"{}\"", name))
+}
+
+/// Download request for `url` with a preset's settings. An explicit `output_folder` wins over the
+/// preset's own folder.
+pub fn request_for<R: Runtime>(
+    app: &AppHandle<R>,
+    preset: Preset,
+    url: String,
+    output_folder: Option<String>,
+) -> Result<DownloadRequest, String> {
+    let output_folder = settings::current(app).resolve_output_folder(
+        output_folder.filter(|folder| !folder.trim().is_empty()).or(preset.output_folder),
+        &preset.download_type,
+        preset.category,
+    )?;
+    let output_folder = storage::validate_output_folder(app, &output_folder)?;
+    Ok(DownloadRequest {
+        url,
+        download_type: preset.download_type,
+        quality: preset.quality,
+        output_folder,
+        start_time: None,
+        end_time: None,
+        filename: None,
+        options: preset.options,
+    })
+}
+
+#[tauri::command]
+pub async fn list_presets<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<Preset>, String> {
+    load_all(&app_handle)
+}
+
+/// Create a preset, or replace the one with the same name (names are case-insensitive)
+#[tauri::command]
+pub async fn save_preset<R: Runtime>(app_handle: AppHandle<R>, preset: Preset) -> Result<Vec<Preset>, String> {
+    preset.validate()?;
+    let mut presets = load_all(&app_handle)?;
+    match presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    save_all(&app_handle, &presets)?;
+    Ok(presets)
+}
+
+#[tauri::command]
+pub async fn delete_preset<R: Runtime>(app_handle: AppHandle<R>, name: String) -> Result<Vec<Preset>, String> {
+    let mut presets = load_all(&app_handle)?;
+    let before = presets.len();
+    presets.retain(|p| !p.name.eq_ignore_ascii_case(&name));
+    if presets.len() == before {
+        return Err(format!("No preset named \"{}\"", name));
+    }
+    save_all(&app_handle, &presets)?;
+    Ok(presets)
+}
+
+#[tauri::command]
+pub async fn start_download_with_preset<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: State<'_, DownloadManagerState>,
+    url: String,
+    preset: String,
+    outputFolder: Option<String>,
+    startTime: Option<f64>,
+    endTime: Option<f64>,
+) -> Result<String, String> {
+    let preset = find(&app_handle, &preset)?;
+    eprintln!("🎛️ Starting download with preset \"{}\"", preset.name);
+    let mut request = request_for(&app_handle, preset, url, outputFolder)?;
+    request.start_time = startTime;
+    request.end_time = endTime;
+    let id = manager.lock().unwrap().enqueue(request);
+
+    download_manager::schedule(&app_handle);
+    Ok(id)
+}
+
+/// Output template for a re-download into the folder of the original, so the new file gets its
+/// own name instead of yt-dlp skipping it as already downloaded: `Title [1080].mp4`, or
+/// `Title (2).mp4` when the format and quality are unchanged. `copies` counts the files already
+/// downloaded with these settings.
+fn distinct_template(request: &DownloadRequest, original: &HistoryEntry, copies: usize) -> Option<String> {
+    if request.output_folder != original.output_folder || request.download_type != original.download_type {
+        return None;
+    }
+    let template = request.options.output_template.as_deref().unwrap_or("%(title)s.%(ext)s");
+    let stem = template.trim_end_matches(".%(ext)s");
+    let suffix = match (request.quality != original.quality, copies) {
+        (true, 0) => format!("[{}]", request.quality),
+        (true, copies) => format!("[{}] ({})", request.quality, copies + 1),
+        (false, copies) => format!("({})", copies + 1),
+    };
+    Some(format!("{} {}.%(ext)s", stem, suffix))
+}
+
+/// Download a video from history again with a different preset, e.g. 1080p after a 720p grab
+/// or MP3 after a video
+#[tauri::command]
+pub async fn redownload<R: Runtime>(app_handle: AppHandle<R>, historyId: String, preset: String) -> Result<String, String> {
+    let original = history::find(&app_handle, &historyId).ok_or_else(|| format!("No download with id {} in history", historyId))?;
+    let preset = find(&app_handle, &preset)?;
+    // Without a folder of its own the preset downloads next to the original
+    let output_folder = preset.output_folder.is_none().then(|| original.output_folder.clone());
+    let mut request = request_for(&app_handle, preset, original.url.clone(), output_folder)?;
+
+    let earlier_copies = history::all(&app_handle)
+        .iter()
+        .filter(|entry| {
+            entry.status == JobStatus::Completed
+                && entry.url == original.url
+                && entry.output_folder == request.output_folder
+                && entry.download_type == request.download_type
+                && entry.quality == request.quality
+        })
+        .count();
+    if let Some(template) = distinct_template(&request, &original, earlier_copies) {
+        request.options.output_template = Some(template);
+    }
+
+    eprintln!("🔂 Re-downloading {} as {} / {}", original.url, request.download_type, request.quality);
+    Ok(download_manager::submit(&app_handle, request))
+}
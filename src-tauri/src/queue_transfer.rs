@@ -0,0 +1,273 @@
+// Moving queues between machines and download managers: export unfinished jobs as JSON, and
+// import job lists from that JSON, JDownloader crawljob files or plain URL lists
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::{self, now_millis, DownloadManagerState, DownloadOptions, DownloadRequest};
+use crate::{presets, settings, storage};
+
+const MAX_IMPORT_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_IMPORT_JOBS: usize = 1000;
+const EXPORT_VERSION: u32 = 1;
+
+/// One job in an import file. Only the URL is required; everything else falls back to the
+/// job's preset, then the import's preset, then settings. JDownloader's crawljob keys are
+/// accepted as aliases.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ImportedJob {
+    #[serde(alias = "text")]
+    pub url: String,
+    pub preset: Option<String>,
+    pub download_type: Option<String>,
+    pub quality: Option<String>,
+    #[serde(alias = "downloadFolder")]
+    pub output_folder: Option<String>,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub filename: Option<String>,
+    pub options: Option<DownloadOptions>,
+    pub priority: i32,
+    /// JDownloader marks skipped links with `enabled=false`
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ImportDocument {
+    Jobs(Vec<ImportedJob>),
+    Wrapped { jobs: Vec<ImportedJob> },
+}
+
+/// A job as written by `export_queue`; the field names match `ImportedJob`
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportedJob {
+    pub url: String,
+    pub download_type: String,
+    pub quality: String,
+    pub output_folder: String,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub filename: Option<String>,
+    pub options: DownloadOptions,
+    pub priority: i32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueExport {
+    pub version: u32,
+    pub exported_at_ms: u64,
+    pub jobs: Vec<ExportedJob>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueImport {
+    /// Ids of the queued downloads, in file order
+    pub ids: Vec<String>,
+    /// URLs left out because they were disabled or already in the queue
+    pub skipped: Vec<String>,
+}
+
+/// A JDownloader crawljob property file: `key=value` lines, a new job at every `text=`
+fn parse_crawljob(contents: &str) -> Vec<ImportedJob> {
+    let mut jobs: Vec<ImportedJob> = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "text" => jobs.push(ImportedJob { url: value, ..Default::default() }),
+            key => {
+                let Some(job) = jobs.last_mut() else { continue };
+                match key {
+                    "downloadFolder" => job.output_folder = Some(value),
+                    "filename" => job.filename = Some(value),
+                    "enabled" => job.enabled = Some(!value.eq_ignore_ascii_case("false")),
+                    _ => {}
+                }
+            }
+        }
+    }
+    jobs
+}
+
+/// Jobs from any supported format: JSON (a job list, or an object with `jobs`), a crawljob file,
+/// or one URL per line
+fn parse_import(contents: &str) -> Result<Vec<ImportedJob>, String> {
+    let trimmed = contents.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return match serde_json::from_str(trimmed).map_err(|e| format!("Invalid queue file: {}", e))? {
+            ImportDocument::Jobs(jobs) | ImportDocument::Wrapped { jobs } => Ok(jobs),
+        };
+    }
+    if trimmed.lines().any(|line| line.trim_start().starts_with("text=")) {
+        return Ok(parse_crawljob(trimmed));
+    }
+    Ok(trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| ImportedJob { url: line.to_string(), ..Default::default() })
+        .collect())
+}
+
+/// Turn an imported job into a checked request
+fn resolve<R: Runtime>(
+    app: &AppHandle<R>,
+    job: ImportedJob,
+    default_preset: Option<&str>,
+    default_folder: Option<&String>,
+) -> Result<DownloadRequest, String> {
+    match url::Url::parse(&job.url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err("not a valid http(s) URL".to_string()),
+    }
+    let output_folder = job.output_folder.filter(|folder| !folder.trim().is_empty()).or(default_folder.cloned());
+    let preset = job.preset.as_deref().or(default_preset);
+    let format_chosen = preset.is_some() || job.download_type.is_some();
+
+    let mut request = match preset {
+        Some(name) => presets::request_for(app, presets::find(app, name)?, job.url, output_folder)?,
+        None => download_manager::default_request(app, job.url, output_folder)?,
+    };
+    if job.download_type.is_some() || job.quality.is_some() {
+        let current = settings::current(app);
+        let (download_type, quality) = current.resolve_format(
+            &request.url,
+            job.download_type.or(Some(request.download_type)),
+            job.quality.or(Some(request.quality)),
+        );
+        let known = settings::DOWNLOAD_TYPES.contains(&download_type.as_str())
+            || matches!(download_type.as_str(), "direct" | "metalink");
+        if !known {
+            return Err(format!("unsupported download type: {}", download_type));
+        }
+        request.download_type = download_type;
+        request.quality = quality;
+    }
+    if let Some(options) = job.options {
+        request.options = options;
+    }
+    if !format_chosen {
+        crate::classify::mark_unchosen(app, &mut request.options);
+    }
+    request.options.validate()?;
+    if let (Some(start), Some(end)) = (job.start_time, job.end_time) {
+        if start >= end {
+            return Err("the start time must be before the end time".to_string());
+        }
+    }
+    request.start_time = job.start_time;
+    request.end_time = job.end_time;
+    if let Some(name) = job.filename.filter(|name| !name.trim().is_empty()) {
+        request.filename = Some(storage::validate_file_name(&name)?);
+    }
+    request.output_folder = storage::validate_output_folder(app, &request.output_folder)?;
+    Ok(request)
+}
+
+/// Queue every job in an exported queue, crawljob or URL list. Nothing is queued unless every
+/// job checks out; the error names the first bad one.
+#[tauri::command]
+pub async fn import_queue<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    preset: Option<String>,
+    outputFolder: Option<String>,
+) -> Result<QueueImport, String> {
+    let size = std::fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?.len();
+    if size > MAX_IMPORT_BYTES {
+        return Err(format!("{} is too large to be a queue file", path));
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let jobs = parse_import(&contents)?;
+    if jobs.len() > MAX_IMPORT_JOBS {
+        return Err(format!("At most {} jobs can be imported at once", MAX_IMPORT_JOBS));
+    }
+
+    let queued: Vec<String> = app_handle
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .snapshot()
+        .into_iter()
+        .map(|job| job.request.url)
+        .collect();
+    let mut skipped = Vec::new();
+    let mut requests = Vec::new();
+    for (index, mut job) in jobs.into_iter().enumerate() {
+        job.url = job.url.trim().to_string();
+        if job.enabled == Some(false)
+            || queued.contains(&job.url)
+            || requests.iter().any(|(request, _): &(DownloadRequest, i32)| request.url == job.url)
+        {
+            skipped.push(job.url);
+            continue;
+        }
+        let url = job.url.clone();
+        let priority = job.priority;
+        let request = resolve(&app_handle, job, preset.as_deref(), outputFolder.as_ref())
+            .map_err(|e| format!("Job {} ({}): {}", index + 1, url, e))?;
+        requests.push((request, priority));
+    }
+    if requests.is_empty() {
+        return Err("The file has no new downloads to queue".to_string());
+    }
+
+    let ids = {
+        let state = app_handle.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        requests
+            .into_iter()
+            .map(|(request, priority)| {
+                let id = manager.enqueue(request);
+                if let Some(job) = manager.job_mut(&id) {
+                    job.priority = priority;
+                }
+                id
+            })
+            .collect::<Vec<_>>()
+    };
+    eprintln!("📥 Imported {} download(s) from {} ({} skipped)", ids.len(), path, skipped.len());
+    download_manager::schedule(&app_handle);
+    Ok(QueueImport { ids, skipped })
+}
+
+/// Every unfinished job (queued, running, waiting or interrupted) as a JSON document that
+/// `import_queue` reads back. Written to `path` when one is given.
+#[tauri::command]
+pub async fn export_queue<R: Runtime>(app_handle: AppHandle<R>, path: Option<String>) -> Result<String, String> {
+    let jobs: Vec<ExportedJob> = app_handle
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .snapshot()
+        .into_iter()
+        .map(|job| {
+            let mut options = job.request.options;
+            // Meaningless once the history it points into is left behind
+            options.retry_of = None;
+            ExportedJob {
+                url: job.request.url,
+                download_type: job.request.download_type,
+                quality: job.request.quality,
+                output_folder: job.request.output_folder,
+                start_time: job.request.start_time,
+                end_time: job.request.end_time,
+                filename: job.request.filename,
+                options,
+                priority: job.priority,
+            }
+        })
+        .collect();
+    let export = QueueExport { version: EXPORT_VERSION, exported_at_ms: now_millis(), jobs };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize queue: {}", e))?;
+
+    if let Some(path) = path.filter(|path| !path.trim().is_empty()) {
+        std::fs::write(&path, &json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        eprintln!("📤 Exported {} download(s) to {}", export.jobs.len(), path);
+    }
+    Ok(json)
+}
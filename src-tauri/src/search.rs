@@ -0,0 +1,97 @@
+// Site search through yt-dlp's search extractors (`ytsearchN:query` and friends)
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::binary_manager;
+
+const MAX_RESULTS: u32 = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    /// Seconds; 0 when the site does not report it in flat results
+    pub duration: f64,
+    pub uploader: String,
+    pub thumbnail_url: String,
+}
+
+/// yt-dlp search prefix for a site name
+fn search_prefix(site: &str) -> Result<&'static str, String> {
+    match site {
+        "youtube" => Ok("ytsearch"),
+        "youtube_recent" => Ok("ytsearchdate"),
+        "soundcloud" => Ok("scsearch"),
+        "bilibili" => Ok("bilisearch"),
+        "niconico" => Ok("nicosearch"),
+        other => Err(format!("Search is not supported for {}", other)),
+    }
+}
+
+fn parse_entry(entry: &serde_json::Value) -> Option<SearchResult> {
+    let url = entry["webpage_url"]
+        .as_str()
+        .or_else(|| entry["url"].as_str())
+        .filter(|u| u.starts_with("http"))?
+        .to_string();
+
+    // Flat results carry a list of thumbnails rather than a single `thumbnail`
+    let thumbnail_url = entry["thumbnail"]
+        .as_str()
+        .or_else(|| entry["thumbnails"].as_array()?.last()?["url"].as_str())
+        .unwrap_or("")
+        .to_string();
+
+    Some(SearchResult {
+        title: entry["title"].as_str().unwrap_or("Unknown Title").to_string(),
+        url,
+        duration: entry["duration"].as_f64().unwrap_or(0.0),
+        uploader: entry["uploader"]
+            .as_str()
+            .or_else(|| entry["channel"].as_str())
+            .unwrap_or("Unknown Uploader")
+            .to_string(),
+        thumbnail_url,
+    })
+}
+
+#[tauri::command]
+pub async fn search_videos<R: Runtime>(
+    app_handle: AppHandle<R>,
+    query: String,
+    count: Option<u32>,
+    site: Option<String>,
+) -> Result<Vec<SearchResult>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let count = count.unwrap_or(10).clamp(1, MAX_RESULTS);
+    let prefix = search_prefix(site.as_deref().unwrap_or("youtube"))?;
+
+    let paths = binary_manager::resolve_paths(&app_handle)?;
+    binary_manager::ensure_executable(&paths)?;
+
+    // --flat-playlist lists the results without resolving every video, which keeps search fast
+    let output = binary_manager::tool_command(&paths.yt_dlp)
+        .arg("--flat-playlist")
+        .arg("--dump-single-json")
+        .arg("--no-warnings")
+        .arg("--")
+        .arg(format!("{}{}:{}", prefix, count, query))
+        .output()
+        .map_err(|e| format!("Failed to run search: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Search failed: {}", stderr.trim()));
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse search results: {}", e))?;
+
+    Ok(json["entries"]
+        .as_array()
+        .map(|entries| entries.iter().filter_map(parse_entry).collect())
+        .unwrap_or_default())
+}
@@ -0,0 +1,184 @@
+// Sensitive values (cookie files, auth tokens) live in the OS keychain: Windows Credential
+// Manager, macOS Keychain or the Secret Service (libsecret) on Linux. Nothing is written
+// to app data except short-lived temp files handed to yt-dlp.
+#![cfg_attr(target_os = "android", allow(dead_code, unused_imports))]
+
+use std::path::PathBuf;
+
+const SERVICE: &str = "com.udownload.application";
+/// Windows caps a credential blob at 2560 bytes and stores it as UTF-16, which takes at most
+/// twice the UTF-8 length, so long values are stored in pieces of this many UTF-8 bytes
+const CHUNK_SIZE: usize = 1200;
+
+/// Name of the secret holding the Netscape-format cookie file passed to yt-dlp
+pub const COOKIES: &str = "cookies";
+
+#[cfg(not(target_os = "android"))]
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+#[cfg(not(target_os = "android"))]
+fn read_entry(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from keychain: {}", name, e)),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn delete_entry(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete {} from keychain: {}", name, e)),
+    }
+}
+
+fn chunk_name(name: &str, index: usize) -> String {
+    format!("{}.{}", name, index)
+}
+
+/// Split on char boundaries so every piece is valid UTF-8
+fn split_chunks(value: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let mut end = rest.len().min(CHUNK_SIZE);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Store a secret; the base entry holds the chunk count and `name.N` holds the pieces
+pub fn set(name: &str, value: &str) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = (name, value);
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        delete(name)?;
+        let chunks = split_chunks(value);
+        for (index, chunk) in chunks.iter().enumerate() {
+            entry(&chunk_name(name, index))?
+                .set_password(chunk)
+                .map_err(|e| format!("Failed to save {} to keychain: {}", name, e))?;
+        }
+        entry(name)?
+            .set_password(&chunks.len().to_string())
+            .map_err(|e| format!("Failed to save {} to keychain: {}", name, e))
+    }
+}
+
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = name;
+        Ok(None)
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        let Some(count) = read_entry(name)? else { return Ok(None) };
+        let count: usize = count
+            .parse()
+            .map_err(|_| format!("Keychain entry {} is corrupted", name))?;
+        let mut value = String::new();
+        for index in 0..count {
+            let chunk = read_entry(&chunk_name(name, index))?
+                .ok_or_else(|| format!("Keychain entry {} is missing piece {}", name, index))?;
+            value.push_str(&chunk);
+        }
+        Ok(Some(value))
+    }
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = name;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        if let Some(count) = read_entry(name)?.and_then(|c| c.parse::<usize>().ok()) {
+            for index in 0..count {
+                delete_entry(&chunk_name(name, index))?;
+            }
+        }
+        delete_entry(name)
+    }
+}
+
+/// A secret written to a private temp file for a child process; removed on drop
+pub struct TempSecretFile {
+    pub path: PathBuf,
+}
+
+impl TempSecretFile {
+    pub fn write(name: &str, contents: &str) -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!(
+            "udl-{}-{}-{}.txt",
+            name,
+            std::process::id(),
+            crate::download_manager::now_millis()
+        ));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&path)
+            .map_err(|e| format!("Failed to create temp file for {}: {}", name, e))?;
+        std::io::Write::write_all(&mut file, contents.as_bytes())
+            .map_err(|e| format!("Failed to write temp file for {}: {}", name, e))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempSecretFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[tauri::command]
+pub async fn store_secret(name: String, value: String) -> Result<(), String> {
+    set(&name, &value)
+}
+
+#[tauri::command]
+pub async fn has_secret(name: String) -> Result<bool, String> {
+    Ok(get(&name)?.is_some())
+}
+
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<(), String> {
+    delete(&name)
+}
+
+/// Move a cookies.txt export into the keychain, optionally deleting the plaintext original
+#[tauri::command]
+pub async fn import_cookies_file(path: String, deleteOriginal: bool) -> Result<(), String> {
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read cookie file {}: {}", path, e))?;
+    if !contents.lines().any(|line| line.split('\t').count() >= 7) {
+        return Err("Not a Netscape-format cookie file".to_string());
+    }
+    set(COOKIES, &contents)?;
+    if deleteOriginal {
+        std::fs::remove_file(&path).map_err(|e| format!("Cookies saved, but failed to delete {}: {}", path, e))?;
+    }
+    eprintln!("🔐 Stored cookies in the OS keychain");
+    Ok(())
+}
@@ -0,0 +1,1017 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::formats::{CodecPreference, Container};
+use crate::presets::{self, Preset};
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Schema version of `settings.json`; bump it and add a step to `migrate` when the layout changes
+pub const SETTINGS_VERSION: u32 = 1;
+/// Format version written by `export_settings`
+const BUNDLE_VERSION: u32 = 1;
+
+/// aria2c segmentation parameters (`-x`, `-s`, `-k`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Aria2Settings {
+    /// Maximum connections per server (aria2c caps this at 16)
+    pub connections: u32,
+    /// Number of segments a single file is split into
+    pub split: u32,
+    /// Minimum segment size in MiB
+    pub chunk_size_mb: u32,
+}
+
+impl Default for Aria2Settings {
+    fn default() -> Self {
+        Self { connections: 16, split: 16, chunk_size_mb: 1 }
+    }
+}
+
+impl Aria2Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=16).contains(&self.connections) {
+            return Err(format!("Connections must be between 1 and 16 (got {})", self.connections));
+        }
+        if !(1..=64).contains(&self.split) {
+            return Err(format!("Split count must be between 1 and 64 (got {})", self.split));
+        }
+        if !(1..=1024).contains(&self.chunk_size_mb) {
+            return Err(format!("Chunk size must be between 1 and 1024 MiB (got {})", self.chunk_size_mb));
+        }
+        Ok(())
+    }
+
+    /// Value for yt-dlp's `--external-downloader-args`
+    pub fn external_downloader_args(&self) -> String {
+        format!("-x {} -s {} -k {}M", self.connections, self.split, self.chunk_size_mb)
+    }
+
+    /// Per-download options for aria2c's RPC `addUri`/`addMetalink`
+    pub fn rpc_options(&self) -> serde_json::Value {
+        serde_json::json!({
+            "max-connection-per-server": self.connections.to_string(),
+            "split": self.split.to_string(),
+            "min-split-size": format!("{}M", self.chunk_size_mb),
+        })
+    }
+}
+
+pub const DOWNLOAD_TYPES: &[&str] = &["mp4", "mp3", "m4a", "opus", "flac", "wav"];
+pub const QUALITIES: &[&str] = &["360", "480", "720", "1080", "1440", "2160", "4320", "best"];
+
+/// Default format and quality for URLs from one site, e.g. always mp3 for soundcloud.com
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitePreset {
+    /// Host to match; subdomains match too, so `youtube.com` covers `m.youtube.com`
+    pub domain: String,
+    pub download_type: String,
+    pub quality: String,
+}
+
+impl SitePreset {
+    pub fn validate(&self) -> Result<(), String> {
+        if normalize_domain(&self.domain).is_empty() {
+            return Err("Site preset domain cannot be empty".to_string());
+        }
+        if !DOWNLOAD_TYPES.contains(&self.download_type.as_str()) {
+            return Err(format!("Unsupported download type for {}: {}", self.domain, self.download_type));
+        }
+        if !QUALITIES.contains(&self.quality.as_str()) {
+            return Err(format!("Unsupported quality for {}: {}", self.domain, self.quality));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        let domain = normalize_domain(&self.domain);
+        host == domain || host.ends_with(&format!(".{}", domain))
+    }
+}
+
+fn normalize_domain(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    domain.strip_prefix("www.").map(str::to_string).unwrap_or(domain)
+}
+
+/// Defaults used when a download does not specify them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralSettings {
+    pub default_output_folder: Option<String>,
+    pub default_download_type: String,
+    pub default_quality: String,
+    pub notifications: bool,
+    /// Downloads the queue runs at the same time
+    pub max_concurrent_downloads: usize,
+    /// Extra slots reserved for fast-lane downloads (audio, unless overridden), so small
+    /// downloads are not stuck behind large videos; 0 turns the fast lane off
+    pub fast_lane_slots: usize,
+    /// Fragments yt-dlp fetches in parallel for HLS/DASH streams (`-N`)
+    pub concurrent_fragments: u32,
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            default_output_folder: None,
+            default_download_type: "mp4".to_string(),
+            default_quality: "best".to_string(),
+            notifications: true,
+            max_concurrent_downloads: 3,
+            fast_lane_slots: 1,
+            concurrent_fragments: 1,
+        }
+    }
+}
+
+impl GeneralSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !DOWNLOAD_TYPES.contains(&self.default_download_type.as_str()) {
+            return Err(format!("Unsupported default download type: {}", self.default_download_type));
+        }
+        if !QUALITIES.contains(&self.default_quality.as_str()) {
+            return Err(format!("Unsupported default quality: {}", self.default_quality));
+        }
+        if !(1..=10).contains(&self.max_concurrent_downloads) {
+            return Err(format!(
+                "Concurrent downloads must be between 1 and 10 (got {})",
+                self.max_concurrent_downloads
+            ));
+        }
+        if self.fast_lane_slots > 5 {
+            return Err(format!("Fast lane slots must be between 0 and 5 (got {})", self.fast_lane_slots));
+        }
+        if !(1..=32).contains(&self.concurrent_fragments) {
+            return Err(format!("Concurrent fragments must be between 1 and 32 (got {})", self.concurrent_fragments));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub codec_preference: CodecPreference,
+    pub container: Container,
+    /// Re-encode finished video downloads with this preset
+    pub transcode_preset: Option<crate::ffmpeg::TranscodePreset>,
+    /// Write a Kodi/Jellyfin `.nfo` file next to each video
+    pub write_nfo: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Bitrate such as `192K`, or a VBR level from 0 (best) to 10, for lossy formats
+    pub quality: String,
+    /// Run two-pass EBU R128 loudness normalization after each download
+    pub normalize_loudness: bool,
+    /// Integrated loudness target in LUFS (-16 suits podcasts, -14 streaming music)
+    pub loudness_target: f64,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { quality: "192K".to_string(), normalize_loudness: false, loudness_target: -16.0 }
+    }
+}
+
+impl AudioSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        crate::formats::validate_audio_quality(&self.quality)?;
+        if !(-70.0..=-5.0).contains(&self.loudness_target) {
+            return Err(format!("Loudness target must be between -70 and -5 LUFS (got {})", self.loudness_target));
+        }
+        Ok(())
+    }
+}
+
+/// `IE_KEY:ARG=VALUE[;ARG=VALUE]` as taken by `--extractor-args`, e.g. `youtube:player_client=android`
+pub fn validate_extractor_args(value: &str) -> Result<(), String> {
+    let valid = regex::Regex::new(r"^[A-Za-z0-9_]+:[^=\s;]+=[^\s;]*(;[^=\s;]+=[^\s;]*)*$").unwrap();
+    if valid.is_match(value.trim()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid extractor args \"{}\"; use extractor:key=value, e.g. youtube:player_client=android", value))
+    }
+}
+
+/// Options for power users that are passed straight to yt-dlp
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdvancedSettings {
+    /// `--extractor-args` for every download, e.g. `youtube:player_client=android`. The extractor
+    /// name limits each entry to its site, which is how these work around throttling and bot checks.
+    pub extractor_args: Vec<String>,
+    /// Pass a yt-dlp config file with `--config-location`
+    pub use_yt_dlp_config: bool,
+    /// The user's own config file; the app-managed `yt-dlp.conf` in app data when unset
+    pub yt_dlp_config_path: Option<String>,
+    /// Let yt-dlp and aria2c use the `HTTP_PROXY`/`HTTPS_PROXY` environment; otherwise only a
+    /// download's own proxy is used
+    pub use_system_proxy: bool,
+}
+
+impl AdvancedSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.extractor_args.iter().try_for_each(|args| validate_extractor_args(args))
+    }
+}
+
+/// Which kind of library folder a download belongs in
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaCategory {
+    Music,
+    Videos,
+}
+
+impl MediaCategory {
+    pub fn of(download_type: &str) -> Self {
+        if crate::formats::is_audio(download_type) {
+            MediaCategory::Music
+        } else {
+            MediaCategory::Videos
+        }
+    }
+}
+
+/// Sends downloads carrying `tag` to `folder`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagFolder {
+    pub tag: String,
+    pub folder: String,
+}
+
+/// Per-category output folders
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderSettings {
+    /// Send downloads that do not name a folder to the folder for their category
+    pub route_by_category: bool,
+    pub music: Option<String>,
+    pub videos: Option<String>,
+    /// Folders for tagged downloads, over any other folder choice; the first matching tag wins
+    pub by_tag: Vec<TagFolder>,
+}
+
+impl FolderSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for route in &self.by_tag {
+            crate::tags::normalize(&route.tag)?;
+            if !std::path::Path::new(route.folder.trim()).is_absolute() {
+                return Err(format!("The folder for tag \"{}\" must be an absolute path", route.tag));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What to do with downloads while the OS reports a metered connection (tethering, capped plans)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MeteredAction {
+    Ignore,
+    #[default]
+    Pause,
+    Throttle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeteredSettings {
+    pub action: MeteredAction,
+    /// Bandwidth cap for `throttle`, in bytes per second
+    pub throttle_bytes_per_sec: u64,
+}
+
+impl Default for MeteredSettings {
+    fn default() -> Self {
+        Self { action: MeteredAction::default(), throttle_bytes_per_sec: 256 * 1024 }
+    }
+}
+
+impl MeteredSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.action == MeteredAction::Throttle && self.throttle_bytes_per_sec < 10 * 1024 {
+            return Err("The metered speed limit must be at least 10 KB/s".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Which download events raise a desktop notification; `general.notifications` turns them all off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub on_start: bool,
+    pub on_complete: bool,
+    pub on_error: bool,
+    /// One summary when every download in a batch (playlist, import) has finished
+    pub on_batch_complete: bool,
+    /// Show the video thumbnail as the notification icon
+    pub show_thumbnail: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self { on_start: false, on_complete: true, on_error: true, on_batch_complete: true, show_thumbnail: true }
+    }
+}
+
+/// yt-dlp release line the updater follows
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum YtDlpChannel {
+    #[default]
+    Stable,
+    /// Daily builds, which pick up fixes for site changes (YouTube breakages) days before stable
+    Nightly,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YtDlpSettings {
+    pub channel: YtDlpChannel,
+    /// Stay on this release, e.g. `2024.08.06` (or `2024.08.06.232909` on nightly), instead of the latest
+    pub pinned_version: Option<String>,
+}
+
+impl YtDlpSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(version) = &self.pinned_version {
+            let valid = regex::Regex::new(r"^\d{4}\.\d{2}\.\d{2}(\.\d+)?$").unwrap();
+            if !valid.is_match(version.trim()) {
+                return Err(format!("Invalid yt-dlp version \"{}\"; use a release tag such as 2024.08.06", version));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Global shortcut that downloads the URL on the clipboard (desktop only)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    pub enabled: bool,
+    /// Accelerator such as `CommandOrControl+Shift+D`
+    pub shortcut: String,
+    /// Preset to download with; the site preset or general defaults when unset
+    pub preset: Option<String>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self { enabled: true, shortcut: "CommandOrControl+Shift+D".to_string(), preset: None }
+    }
+}
+
+impl HotkeySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.shortcut.trim().is_empty() {
+            return Err("The download shortcut cannot be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Which YouTube app yt-dlp poses as, and whether failed downloads try the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YouTubeSettings {
+    /// `youtube:player_client`, e.g. `tv` or `ios,web_safari`; yt-dlp's own choice when unset
+    pub player_client: Option<String>,
+    /// Retry downloads that get HTTP 403 or time out with the next client
+    pub rotate_clients: bool,
+}
+
+impl Default for YouTubeSettings {
+    fn default() -> Self {
+        Self { player_client: None, rotate_clients: true }
+    }
+}
+
+impl YouTubeSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.player_client {
+            Some(client) => crate::youtube::validate_player_client(client),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Metadata fields output templates usually build file names from
+pub const NAME_FIELDS: &str = "title,uploader,channel,playlist_title,album,artist,track";
+/// Astral-plane symbols (most emoji), the Misc Symbols/Dingbats blocks and the joiners and
+/// variation selector emoji sequences are built from, in Python `re` syntax
+const EMOJI_PATTERN: &str = r"[\U00010000-\U0010FFFF\u2600-\u27BF\uFE0F\u200D]";
+const MIN_FILENAME_LENGTH: u32 = 32;
+
+/// How yt-dlp turns titles into file names. Exotic titles break some filesystems and cloud-sync
+/// clients; these trade readable names for ones that work everywhere.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilenameSettings {
+    /// ASCII only, no spaces or `&` (`--restrict-filenames`)
+    pub restrict: bool,
+    /// Leave out characters Windows forbids on every platform, for synced folders (`--windows-filenames`)
+    pub windows_safe: bool,
+    /// Longest file name in characters, not counting the extension (`--trim-filenames`); no
+    /// limit when unset. Windows may cut names further to stay under MAX_PATH.
+    pub max_length: Option<u32>,
+    /// Remove emoji from the title and other fields used in names. This edits the metadata, so
+    /// embedded tags lose them too.
+    pub strip_emoji: bool,
+    /// Rename finished downloads to ASCII, e.g. `Москва.mp4` → `Moskva.mp4`. Titles in metadata
+    /// and history are left as they are.
+    pub transliterate: bool,
+}
+
+impl FilenameSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(length) = self.max_length {
+            if !(MIN_FILENAME_LENGTH..=255).contains(&length) {
+                return Err(format!("Maximum file name length must be between {} and 255", MIN_FILENAME_LENGTH));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.restrict {
+            args.push("--restrict-filenames".to_string());
+        }
+        if self.windows_safe {
+            args.push("--windows-filenames".to_string());
+        }
+        if let Some(length) = self.max_length {
+            args.push("--trim-filenames".to_string());
+            args.push(length.to_string());
+        }
+        if self.strip_emoji {
+            args.push("--replace-in-metadata".to_string());
+            args.push(NAME_FIELDS.to_string());
+            args.push(EMOJI_PATTERN.to_string());
+            args.push(String::new());
+        }
+        args
+    }
+}
+
+/// Downloader yt-dlp hands a protocol to (`--downloader PROTO:NAME`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Downloader {
+    /// yt-dlp's own downloader
+    Native,
+    Aria2c,
+    Ffmpeg,
+}
+
+impl Downloader {
+    fn name(self) -> &'static str {
+        match self {
+            Downloader::Native => "native",
+            Downloader::Aria2c => "aria2c",
+            Downloader::Ffmpeg => "ffmpeg",
+        }
+    }
+}
+
+/// Downloader per protocol. aria2c speeds up plain files but breaks some HLS/DASH streams the
+/// native downloader handles, so streams default to native.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloaderSettings {
+    /// Plain HTTP(S) and FTP files
+    pub http: Downloader,
+    /// HLS (`.m3u8`) streams
+    pub m3u8: Downloader,
+    /// DASH segments
+    pub dash: Downloader,
+}
+
+impl Default for DownloaderSettings {
+    fn default() -> Self {
+        Self { http: Downloader::Aria2c, m3u8: Downloader::Native, dash: Downloader::Native }
+    }
+}
+
+impl DownloaderSettings {
+    pub fn uses_aria2c(&self) -> bool {
+        [self.http, self.m3u8, self.dash].contains(&Downloader::Aria2c)
+    }
+
+    /// `--downloader` arguments, with `aria2_args` passed to aria2c when it is used
+    pub fn yt_dlp_args(&self, aria2_args: &str) -> Vec<String> {
+        // The downloader without a protocol covers http and ftp
+        let mut args = vec![
+            "--downloader".to_string(),
+            self.http.name().to_string(),
+            "--downloader".to_string(),
+            format!("m3u8:{}", self.m3u8.name()),
+            "--downloader".to_string(),
+            format!("dash:{}", self.dash.name()),
+        ];
+        if self.uses_aria2c() {
+            args.push("--downloader-args".to_string());
+            args.push(format!("aria2c:{}", aria2_args));
+        }
+        args
+    }
+}
+
+/// Switch downloads queued without a chosen format to audio when the video is music
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassifySettings {
+    pub enabled: bool,
+    /// Format used for videos detected as music
+    pub audio_format: String,
+}
+
+impl Default for ClassifySettings {
+    fn default() -> Self {
+        Self { enabled: false, audio_format: "mp3".to_string() }
+    }
+}
+
+impl ClassifySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !crate::formats::is_audio(&self.audio_format) {
+            return Err(format!("Not an audio format: {}", self.audio_format));
+        }
+        Ok(())
+    }
+}
+
+/// Limits that stop a download which hangs; 0 turns a limit off
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutSettings {
+    /// Longest a whole download may take, in minutes
+    pub overall_minutes: u32,
+    /// Longest a download may go without receiving any data, in seconds
+    pub stall_seconds: u32,
+    /// How many times a timed-out download is queued again
+    pub retries: u32,
+}
+
+pub const MAX_TIMEOUT_RETRIES: u32 = 5;
+
+impl TimeoutSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.stall_seconds != 0 && self.stall_seconds < 30 {
+            return Err("The no-progress timeout must be at least 30 seconds".to_string());
+        }
+        if self.retries > MAX_TIMEOUT_RETRIES {
+            return Err(format!("Timeout retries cannot exceed {}", MAX_TIMEOUT_RETRIES));
+        }
+        Ok(())
+    }
+}
+
+/// Local HTTP/WebSocket remote-control server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on all interfaces so other devices on the LAN can connect, not just localhost.
+    /// The API is plain HTTP: anyone who can watch the network sees the token and can use it,
+    /// so only turn this on for a trusted network.
+    pub allow_lan: bool,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: 47821, allow_lan: false }
+    }
+}
+
+impl ApiSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port < 1024 {
+            return Err(format!("API port must be 1024 or higher (got {})", self.port));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub general: GeneralSettings,
+    pub aria2: Aria2Settings,
+    pub site_presets: Vec<SitePreset>,
+    pub api: ApiSettings,
+    pub video: VideoSettings,
+    pub audio: AudioSettings,
+    pub advanced: AdvancedSettings,
+    pub folders: FolderSettings,
+    pub timeouts: TimeoutSettings,
+    pub metered: MeteredSettings,
+    pub notifications: NotificationSettings,
+    pub yt_dlp: YtDlpSettings,
+    pub hotkey: HotkeySettings,
+    pub classify: ClassifySettings,
+    pub downloaders: DownloaderSettings,
+    pub youtube: YouTubeSettings,
+    pub filenames: FilenameSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            general: GeneralSettings::default(),
+            aria2: Aria2Settings::default(),
+            site_presets: Vec::new(),
+            api: ApiSettings::default(),
+            video: VideoSettings::default(),
+            audio: AudioSettings::default(),
+            advanced: AdvancedSettings::default(),
+            folders: FolderSettings::default(),
+            timeouts: TimeoutSettings::default(),
+            metered: MeteredSettings::default(),
+            notifications: NotificationSettings::default(),
+            yt_dlp: YtDlpSettings::default(),
+            hotkey: HotkeySettings::default(),
+            classify: ClassifySettings::default(),
+            downloaders: DownloaderSettings::default(),
+            youtube: YouTubeSettings::default(),
+            filenames: FilenameSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.general.validate()?;
+        self.aria2.validate()?;
+        self.api.validate()?;
+        self.audio.validate()?;
+        self.advanced.validate()?;
+        self.timeouts.validate()?;
+        self.metered.validate()?;
+        self.yt_dlp.validate()?;
+        self.hotkey.validate()?;
+        self.classify.validate()?;
+        self.youtube.validate()?;
+        self.filenames.validate()?;
+        self.folders.validate()?;
+        for preset in &self.site_presets {
+            preset.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Download type and quality for a URL: explicit values win, then the site preset,
+    /// then the general defaults
+    pub fn resolve_format(&self, url: &str, download_type: Option<String>, quality: Option<String>) -> (String, String) {
+        let preset = self.site_preset_for(url);
+        if let Some(preset) = preset {
+            eprintln!("🎯 Using site preset for {}: {} / {}", preset.domain, preset.download_type, preset.quality);
+        }
+        let download_type = download_type
+            .or_else(|| preset.map(|p| p.download_type.clone()))
+            .unwrap_or_else(|| self.general.default_download_type.clone());
+        let quality = quality
+            .or_else(|| preset.map(|p| p.quality.clone()))
+            .unwrap_or_else(|| self.general.default_quality.clone());
+        (download_type, quality)
+    }
+
+    /// Where a download goes: an explicit folder wins, then the category folder when routing is
+    /// on, then the default folder. `category` overrides the one implied by the download type.
+    pub fn resolve_output_folder(
+        &self,
+        explicit: Option<String>,
+        download_type: &str,
+        category: Option<MediaCategory>,
+    ) -> Result<String, String> {
+        let non_empty = |folder: &Option<String>| folder.clone().filter(|f| !f.trim().is_empty());
+        if let Some(folder) = non_empty(&explicit) {
+            return Ok(folder);
+        }
+        if self.folders.route_by_category {
+            let routed = match category.unwrap_or_else(|| MediaCategory::of(download_type)) {
+                MediaCategory::Music => non_empty(&self.folders.music),
+                MediaCategory::Videos => non_empty(&self.folders.videos),
+            };
+            if let Some(folder) = routed {
+                return Ok(folder);
+            }
+        }
+        non_empty(&self.general.default_output_folder)
+            .ok_or_else(|| "No output folder given and no default folder is set".to_string())
+    }
+
+    /// The preset for the URL's host; the most specific domain wins
+    pub fn site_preset_for(&self, url: &str) -> Option<&SitePreset> {
+        let host = url::Url::parse(url).ok()?.host_str()?.to_lowercase();
+        self.site_presets
+            .iter()
+            .filter(|preset| preset.matches(&host))
+            .max_by_key(|preset| normalize_domain(&preset.domain).len())
+    }
+}
+
+pub type SettingsState = Arc<Mutex<Settings>>;
+
+/// Upgrade a settings document written by an older version, one schema step at a time
+pub fn migrate(mut value: serde_json::Value) -> Result<Settings, String> {
+    if !value.is_object() {
+        return Err("Settings must be a JSON object".to_string());
+    }
+    let mut version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings version {} is newer than this app supports ({})",
+            version, SETTINGS_VERSION
+        ));
+    }
+
+    while version < SETTINGS_VERSION {
+        match version {
+            // v0 had only `aria2` and `site_presets`; `general` falls back to its defaults
+            0 => {}
+            v => return Err(format!("Unknown settings version {}", v)),
+        }
+        version += 1;
+        value["version"] = serde_json::json!(version);
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+/// Load settings from disk, falling back to defaults for a missing or invalid file
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    let path = match storage::app_data_file(app, SETTINGS_FILE) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("⚠️  {}", e);
+            return Settings::default();
+        }
+    };
+
+    let stored = storage::read_json::<serde_json::Value>(&path).and_then(|value| {
+        value
+            .map(|value| {
+                let old_version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+                migrate(value).map(|settings| (settings, old_version))
+            })
+            .transpose()
+    });
+
+    match stored {
+        Ok(Some((mut settings, old_version))) => {
+            if let Err(e) = settings.general.validate() {
+                eprintln!("⚠️  Ignoring invalid general settings: {}", e);
+                settings.general = GeneralSettings::default();
+            }
+            if let Err(e) = settings.audio.validate() {
+                eprintln!("⚠️  Ignoring invalid audio settings: {}", e);
+                settings.audio = AudioSettings::default();
+            }
+            if let Err(e) = settings.advanced.validate() {
+                eprintln!("⚠️  Ignoring invalid advanced settings: {}", e);
+                settings.advanced = AdvancedSettings::default();
+            }
+            if let Err(e) = settings.timeouts.validate() {
+                eprintln!("⚠️  Ignoring invalid timeout settings: {}", e);
+                settings.timeouts = TimeoutSettings::default();
+            }
+            if let Err(e) = settings.metered.validate() {
+                eprintln!("⚠️  Ignoring invalid metered connection settings: {}", e);
+                settings.metered = MeteredSettings::default();
+            }
+            if let Err(e) = settings.yt_dlp.validate() {
+                eprintln!("⚠️  Ignoring invalid yt-dlp settings: {}", e);
+                settings.yt_dlp = YtDlpSettings::default();
+            }
+            if let Err(e) = settings.folders.validate() {
+                eprintln!("⚠️  Ignoring invalid folder settings: {}", e);
+                settings.folders = FolderSettings::default();
+            }
+            if let Err(e) = settings.hotkey.validate() {
+                eprintln!("⚠️  Ignoring invalid shortcut settings: {}", e);
+                settings.hotkey = HotkeySettings::default();
+            }
+            if let Err(e) = settings.classify.validate() {
+                eprintln!("⚠️  Ignoring invalid music detection settings: {}", e);
+                settings.classify = ClassifySettings::default();
+            }
+            if let Err(e) = settings.youtube.validate() {
+                eprintln!("⚠️  Ignoring invalid YouTube settings: {}", e);
+                settings.youtube = YouTubeSettings::default();
+            }
+            if let Err(e) = settings.filenames.validate() {
+                eprintln!("⚠️  Ignoring invalid file name settings: {}", e);
+                settings.filenames = FilenameSettings::default();
+            }
+            if let Err(e) = settings.api.validate() {
+                eprintln!("⚠️  Ignoring invalid API settings: {}", e);
+                settings.api = ApiSettings::default();
+            }
+            if let Err(e) = settings.aria2.validate() {
+                eprintln!("⚠️  Ignoring invalid aria2 settings: {}", e);
+                settings.aria2 = Aria2Settings::default();
+            }
+            settings.site_presets.retain(|preset| match preset.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("⚠️  Ignoring invalid site preset: {}", e);
+                    false
+                }
+            });
+            if old_version < SETTINGS_VERSION as u64 {
+                eprintln!("🔧 Migrated settings from version {} to {}", old_version, SETTINGS_VERSION);
+                if let Err(e) = save(app, &settings) {
+                    eprintln!("⚠️  {}", e);
+                }
+            }
+            settings
+        }
+        Ok(None) => Settings::default(),
+        Err(e) => {
+            eprintln!("⚠️  {}", e);
+            // The next save would write defaults over the user's file; set it aside first
+            let kept = path.with_extension("json.unreadable");
+            match std::fs::rename(&path, &kept) {
+                Ok(()) => eprintln!("📦 Kept the unreadable settings file as {}", kept.display()),
+                Err(e) => eprintln!("⚠️  Failed to set aside {}: {}", path.display(), e),
+            }
+            Settings::default()
+        }
+    }
+}
+
+pub fn save<R: Runtime>(app: &AppHandle<R>, settings: &Settings) -> Result<(), String> {
+    let path = storage::app_data_file(app, SETTINGS_FILE)?;
+    storage::write_json(&path, settings)
+}
+
+/// Snapshot of the current settings
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    app.state::<SettingsState>().lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub async fn get_aria2_settings<R: Runtime>(app_handle: AppHandle<R>) -> Result<Aria2Settings, String> {
+    Ok(current(&app_handle).aria2)
+}
+
+#[tauri::command]
+pub async fn set_aria2_settings<R: Runtime>(
+    app_handle: AppHandle<R>,
+    aria2: Aria2Settings,
+) -> Result<Aria2Settings, String> {
+    aria2.validate()?;
+    let updated = {
+        let state = app_handle.state::<SettingsState>();
+        let mut settings = state.lock().unwrap();
+        settings.aria2 = aria2;
+        settings.clone()
+    };
+    save(&app_handle, &updated)?;
+    Ok(updated.aria2)
+}
+
+#[tauri::command]
+pub async fn get_site_presets<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<SitePreset>, String> {
+    Ok(current(&app_handle).site_presets)
+}
+
+#[tauri::command]
+pub async fn set_site_presets<R: Runtime>(
+    app_handle: AppHandle<R>,
+    presets: Vec<SitePreset>,
+) -> Result<Vec<SitePreset>, String> {
+    for preset in &presets {
+        preset.validate()?;
+    }
+    let updated = {
+        let state = app_handle.state::<SettingsState>();
+        let mut settings = state.lock().unwrap();
+        settings.site_presets = presets;
+        settings.clone()
+    };
+    save(&app_handle, &updated)?;
+    Ok(updated.site_presets)
+}
+
+/// Everything user-configurable, as written by `export_settings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at_ms: u64,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+}
+
+/// Bring an exported file of any known version up to the current bundle format
+fn migrate_bundle(value: serde_json::Value) -> Result<SettingsBundle, String> {
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    match version {
+        // Unversioned files are a bare settings.json copied out of the app data folder
+        0 => Ok(SettingsBundle {
+            version: BUNDLE_VERSION,
+            exported_at_ms: 0,
+            settings: migrate(value)?,
+            presets: Vec::new(),
+        }),
+        1 => {
+            let mut value = value;
+            let settings = value
+                .as_object_mut()
+                .and_then(|bundle| bundle.remove("settings"))
+                .unwrap_or_else(|| serde_json::json!({}));
+            let settings = migrate(settings)?;
+            let mut bundle: SettingsBundle =
+                serde_json::from_value(value).map_err(|e| format!("Invalid settings file: {}", e))?;
+            bundle.settings = settings;
+            Ok(bundle)
+        }
+        v => Err(format!(
+            "Settings file version {} is newer than this app supports ({}); please update U-Download",
+            v, BUNDLE_VERSION
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn export_settings<R: Runtime>(app_handle: AppHandle<R>, path: String) -> Result<(), String> {
+    let bundle = SettingsBundle {
+        version: BUNDLE_VERSION,
+        exported_at_ms: crate::download_manager::now_millis(),
+        settings: current(&app_handle),
+        presets: presets::load_all(&app_handle)?,
+    };
+    storage::write_json(std::path::Path::new(&path), &bundle)?;
+    eprintln!("📤 Exported settings to {}", path);
+    Ok(())
+}
+
+/// Replace the current settings and presets with the contents of an exported file.
+/// Nothing is changed unless the whole file validates.
+#[tauri::command]
+pub async fn import_settings<R: Runtime>(app_handle: AppHandle<R>, path: String) -> Result<SettingsBundle, String> {
+    let value: serde_json::Value = storage::read_json(std::path::Path::new(&path))?
+        .ok_or_else(|| format!("Settings file not found: {}", path))?;
+    let bundle = migrate_bundle(value)?;
+
+    bundle.settings.validate()?;
+    for preset in &bundle.presets {
+        preset.validate()?;
+    }
+
+    save(&app_handle, &bundle.settings)?;
+    presets::save_all(&app_handle, &bundle.presets)?;
+    *app_handle.state::<SettingsState>().lock().unwrap() = bundle.settings.clone();
+
+    eprintln!("📥 Imported settings from {} ({} preset(s))", path, bundle.presets.len());
+    Ok(bundle)
+}
+
+#[tauri::command]
+pub async fn get_settings<R: Runtime>(app_handle: AppHandle<R>) -> Result<Settings, String> {
+    Ok(current(&app_handle))
+}
+
+#[tauri::command]
+pub async fn update_settings<R: Runtime>(app_handle: AppHandle<R>, settings: Settings) -> Result<Settings, String> {
+    let settings = Settings { version: SETTINGS_VERSION, ..settings };
+    settings.validate()?;
+    #[cfg(not(target_os = "android"))]
+    crate::hotkey::parse(&settings.hotkey.shortcut)?;
+    crate::crash::breadcrumb("settings updated");
+    save(&app_handle, &settings)?;
+    let previous = std::mem::replace(&mut *app_handle.state::<SettingsState>().lock().unwrap(), settings.clone());
+    crate::binary_manager::configure_child_env(&app_handle);
+    // A higher concurrency limit lets queued downloads start right away
+    if settings.general.max_concurrent_downloads > previous.general.max_concurrent_downloads
+        || settings.general.fast_lane_slots > previous.general.fast_lane_slots
+    {
+        crate::download_manager::schedule(&app_handle);
+    }
+    #[cfg(not(target_os = "android"))]
+    if previous.api != settings.api {
+        crate::api::restart(&app_handle).await?;
+    }
+    #[cfg(not(target_os = "android"))]
+    if previous.hotkey != settings.hotkey {
+        crate::hotkey::register(&app_handle)?;
+    }
+    #[cfg(target_os = "android")]
+    let _ = previous;
+    Ok(settings)
+}
@@ -0,0 +1,332 @@
+// Opt-in OS integrations that launch the app with a link: the "Download with U-Download" context
+// menu entry for link files (`--download <target>`) and the web/`udownload:` link handler
+// (`--open-link <url>`). A second launch hands its arguments to the running instance through the
+// single-instance plugin, so either way they land in `handle_args`.
+use std::path::Path;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+use crate::{download_manager, link_files, notifications};
+
+pub const DOWNLOAD_ARG: &str = "--download";
+pub const OPEN_LINK_ARG: &str = "--open-link";
+/// `udownload://download?url=<encoded url>` or `udownload:<url>`, for pages and bookmarklets that
+/// want to hand a link over without making U-Download the browser
+pub const LINK_SCHEME: &str = "udownload";
+
+/// Arguments following `flag` in a command line, up to the next flag
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args.iter()
+        .skip_while(|arg| *arg != flag)
+        .skip(1)
+        .take_while(|arg| !arg.starts_with("--"))
+        .map(String::as_str)
+        .collect()
+}
+
+/// The web URL a handled link points to
+fn unwrap_link(link: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(link).map_err(|e| format!("Invalid link {}: {}", link, e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(link.to_string()),
+        scheme if scheme == LINK_SCHEME => {
+            let inner = parsed
+                .query_pairs()
+                .find(|(key, _)| key == "url")
+                .map(|(_, value)| value.into_owned())
+                .or_else(|| link.split_once(':').map(|(_, rest)| rest.trim_start_matches('/').to_string()))
+                .unwrap_or_default();
+            match url::Url::parse(&inner) {
+                Ok(target) if matches!(target.scheme(), "http" | "https") => Ok(inner),
+                _ => Err(format!("{} does not carry a web link", link)),
+            }
+        }
+        other => Err(format!("Unsupported link scheme: {}", other)),
+    }
+}
+
+/// A web URL as is, or the URL inside a link file
+fn resolve_target(target: &str) -> Result<String, String> {
+    match url::Url::parse(target) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(target.to_string()),
+        _ => link_files::parse_link_file(Path::new(target)),
+    }
+}
+
+fn queue_url<R: Runtime>(app: &AppHandle<R>, url: Result<String, String>, source: &str) {
+    let result = url.and_then(|url| {
+        let request = download_manager::default_request(app, url.clone(), None)?;
+        download_manager::submit(app, request);
+        eprintln!("📎 Queued {} from {}", url, source);
+        Ok(url)
+    });
+    notifications::notify_queued(app, result);
+}
+
+/// Queue everything passed with `--download` or `--open-link`, from this launch or a later one
+pub fn handle_args<R: Runtime>(app: &AppHandle<R>, args: &[String]) {
+    for target in flag_values(args, DOWNLOAD_ARG) {
+        queue_url(app, resolve_target(target), "the context menu");
+    }
+    for link in flag_values(args, OPEN_LINK_ARG) {
+        queue_url(app, unwrap_link(link), "a link");
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Cannot locate the U-Download executable: {}", e))
+}
+
+/// Registry key for the verb on Internet Shortcut (`.url`) files
+#[cfg(target_os = "windows")]
+const VERB_KEY: &str = r"HKCU\Software\Classes\InternetShortcut\shell\UDownload";
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn is_registered() -> bool {
+    reg(&["query", VERB_KEY]).is_ok()
+}
+
+#[cfg(target_os = "windows")]
+fn register() -> Result<(), String> {
+    let exe = current_exe()?.to_string_lossy().to_string();
+    let command = format!("\"{}\" {} \"%1\"", exe, DOWNLOAD_ARG);
+    reg(&["add", VERB_KEY, "/ve", "/d", "Download with U-Download", "/f"])?;
+    reg(&["add", VERB_KEY, "/v", "Icon", "/d", &exe, "/f"])?;
+    reg(&["add", &format!(r"{}\command", VERB_KEY), "/ve", "/d", &command, "/f"])
+}
+
+#[cfg(target_os = "windows")]
+fn unregister() -> Result<(), String> {
+    if is_registered() {
+        reg(&["delete", VERB_KEY, "/f"])?;
+    }
+    Ok(())
+}
+
+/// File managers list desktop entries for the MIME types they declare under "Open With"
+#[cfg(target_os = "linux")]
+fn desktop_entry() -> Result<PathBuf, String> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or("Cannot find the applications directory")?;
+    Ok(data_home.join("applications").join("u-download-download.desktop"))
+}
+
+#[cfg(target_os = "linux")]
+fn refresh_desktop_database(entry: &Path) {
+    if let Some(dir) = entry.parent() {
+        // Optional: file managers also rescan on their own
+        let _ = std::process::Command::new("update-desktop-database").arg(dir).output();
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_registered() -> bool {
+    desktop_entry().is_ok_and(|entry| entry.exists())
+}
+
+#[cfg(target_os = "linux")]
+fn register() -> Result<(), String> {
+    let exe = current_exe()?;
+    let entry = desktop_entry()?;
+    let contents = format!(
+        "[Desktop Entry]\nType=Application\nName=Download with U-Download\nIcon=u-download\nExec=\"{}\" {} %u\nMimeType=application/x-mswinurl;application/x-desktop;text/x-uri;\nNoDisplay=true\n",
+        exe.display(),
+        DOWNLOAD_ARG
+    );
+    if let Some(dir) = entry.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&entry, contents).map_err(|e| format!("Failed to write {}: {}", entry.display(), e))?;
+    refresh_desktop_database(&entry);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn unregister() -> Result<(), String> {
+    let entry = desktop_entry()?;
+    if entry.exists() {
+        std::fs::remove_file(&entry).map_err(|e| format!("Failed to remove {}: {}", entry.display(), e))?;
+        refresh_desktop_database(&entry);
+    }
+    Ok(())
+}
+
+/// macOS services are declared in the bundle's Info.plist and answered by an Objective-C
+/// provider, neither of which can be added at runtime
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_registered() -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn register() -> Result<(), String> {
+    Err("unsupported".into())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn unregister() -> Result<(), String> {
+    Ok(())
+}
+
+/// ProgID the http(s) association points at; the user picks it in the "Open with" prompt
+#[cfg(target_os = "windows")]
+const LINK_PROG_ID: &str = "UDownload.Link";
+#[cfg(target_os = "windows")]
+const CAPABILITIES_KEY: &str = r"Software\U-Download\Capabilities";
+
+#[cfg(target_os = "windows")]
+fn is_handler_registered() -> bool {
+    reg(&["query", &format!(r"HKCU\Software\Classes\{}", LINK_SCHEME)]).is_ok()
+}
+
+/// Registers the `udownload:` scheme and offers the app for http(s) through Default Apps without
+/// taking over the browser
+#[cfg(target_os = "windows")]
+fn register_handler() -> Result<(), String> {
+    let exe = current_exe()?.to_string_lossy().to_string();
+    let command = format!("\"{}\" {} \"%1\"", exe, OPEN_LINK_ARG);
+    let scheme_key = format!(r"HKCU\Software\Classes\{}", LINK_SCHEME);
+    reg(&["add", &scheme_key, "/ve", "/d", "URL:U-Download link", "/f"])?;
+    reg(&["add", &scheme_key, "/v", "URL Protocol", "/d", "", "/f"])?;
+    reg(&["add", &format!(r"{}\shell\open\command", scheme_key), "/ve", "/d", &command, "/f"])?;
+
+    let prog_id_key = format!(r"HKCU\Software\Classes\{}", LINK_PROG_ID);
+    reg(&["add", &prog_id_key, "/ve", "/d", "Open in U-Download", "/f"])?;
+    reg(&["add", &format!(r"{}\shell\open\command", prog_id_key), "/ve", "/d", &command, "/f"])?;
+
+    let capabilities = format!(r"HKCU\{}", CAPABILITIES_KEY);
+    reg(&["add", &capabilities, "/v", "ApplicationName", "/d", "U-Download", "/f"])?;
+    reg(&["add", &capabilities, "/v", "ApplicationDescription", "/d", "Download videos from links", "/f"])?;
+    for scheme in ["http", "https"] {
+        reg(&["add", &format!(r"{}\URLAssociations", capabilities), "/v", scheme, "/d", LINK_PROG_ID, "/f"])?;
+    }
+    reg(&["add", r"HKCU\Software\RegisteredApplications", "/v", "U-Download", "/d", CAPABILITIES_KEY, "/f"])
+}
+
+#[cfg(target_os = "windows")]
+fn unregister_handler() -> Result<(), String> {
+    let keys = [
+        format!(r"HKCU\Software\Classes\{}", LINK_SCHEME),
+        format!(r"HKCU\Software\Classes\{}", LINK_PROG_ID),
+        r"HKCU\Software\U-Download".to_string(),
+    ];
+    for key in keys.iter().filter(|key| reg(&["query", key]).is_ok()) {
+        reg(&["delete", key, "/f"])?;
+    }
+    let _ = reg(&["delete", r"HKCU\Software\RegisteredApplications", "/v", "U-Download", "/f"]);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn handler_entry() -> Result<PathBuf, String> {
+    Ok(desktop_entry()?.with_file_name("u-download-links.desktop"))
+}
+
+#[cfg(target_os = "linux")]
+fn is_handler_registered() -> bool {
+    handler_entry().is_ok_and(|entry| entry.exists())
+}
+
+/// Default handler for `udownload:`; for http(s) the entry is only offered as an "Open with"
+/// choice, the browser stays the default
+#[cfg(target_os = "linux")]
+fn register_handler() -> Result<(), String> {
+    let exe = current_exe()?;
+    let entry = handler_entry()?;
+    let contents = format!(
+        "[Desktop Entry]\nType=Application\nName=Open in U-Download\nIcon=u-download\nExec=\"{}\" {} %u\nMimeType=x-scheme-handler/{};x-scheme-handler/http;x-scheme-handler/https;\nNoDisplay=true\n",
+        exe.display(),
+        OPEN_LINK_ARG,
+        LINK_SCHEME
+    );
+    if let Some(dir) = entry.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&entry, contents).map_err(|e| format!("Failed to write {}: {}", entry.display(), e))?;
+    refresh_desktop_database(&entry);
+    let file_name = entry.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let _ = std::process::Command::new("xdg-mime")
+        .args(["default", &file_name, &format!("x-scheme-handler/{}", LINK_SCHEME)])
+        .output();
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn unregister_handler() -> Result<(), String> {
+    let entry = handler_entry()?;
+    if entry.exists() {
+        std::fs::remove_file(&entry).map_err(|e| format!("Failed to remove {}: {}", entry.display(), e))?;
+        refresh_desktop_database(&entry);
+    }
+    Ok(())
+}
+
+/// URL schemes on macOS come from CFBundleURLTypes in the bundle's Info.plist
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_handler_registered() -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn register_handler() -> Result<(), String> {
+    Err("unsupported".into())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn unregister_handler() -> Result<(), String> {
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_context_menu_integration() -> Result<bool, String> {
+    Ok(is_registered())
+}
+
+/// Add or remove the context menu entry. Returns whether it is now registered.
+#[tauri::command]
+pub async fn set_context_menu_integration(enabled: bool) -> Result<bool, String> {
+    if enabled {
+        register()?;
+        eprintln!("📎 Added the \"Download with U-Download\" context menu entry");
+    } else {
+        unregister()?;
+        eprintln!("📎 Removed the context menu entry");
+    }
+    Ok(is_registered())
+}
+
+#[tauri::command]
+pub async fn get_link_handler() -> Result<bool, String> {
+    Ok(is_handler_registered())
+}
+
+/// Offer U-Download as a handler for web links and `udownload:` links, or withdraw it.
+/// Returns whether it is now registered.
+#[tauri::command]
+pub async fn set_link_handler(enabled: bool) -> Result<bool, String> {
+    if enabled {
+        register_handler()?;
+        eprintln!("📎 Registered U-Download as a link handler");
+    } else {
+        unregister_handler()?;
+        eprintln!("📎 Removed the link handler");
+    }
+    Ok(is_handler_registered())
+}
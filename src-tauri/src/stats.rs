@@ -0,0 +1,217 @@
+// Aggregates over the download history for the dashboard view. Days and weeks are UTC.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Runtime};
+
+use crate::download_manager::JobStatus;
+use crate::history::{self, HistoryEntry};
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SiteStats {
+    pub site: String,
+    pub downloads: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PeriodStats {
+    /// `2025-03-14` for days, `2025-W11` (ISO week) for weeks
+    pub period: String,
+    pub downloads: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DownloadStats {
+    pub total_downloads: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub total_bytes: u64,
+    /// Mean of the per-download average speeds of completed downloads
+    pub average_speed_bytes_per_sec: u64,
+    /// Busiest sites first
+    pub per_site: Vec<SiteStats>,
+    /// Oldest first
+    pub per_day: Vec<PeriodStats>,
+    pub per_week: Vec<PeriodStats>,
+}
+
+/// Civil date from days since 1970-01-01 (Howard Hinnant's `civil_from_days`)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 for a civil date (inverse of `civil_from_days`)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// `2025-03-14T09:26:53Z`
+pub fn format_timestamp(timestamp_ms: u64) -> String {
+    let ms = timestamp_ms as i64;
+    let (year, month, day) = civil_from_days(ms.div_euclid(MS_PER_DAY));
+    let secs = ms.rem_euclid(MS_PER_DAY) / 1000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// `Thu, 14 Mar 2025 09:26:53 GMT`, as RSS `pubDate` wants
+pub fn format_rfc2822(timestamp_ms: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let ms = timestamp_ms as i64;
+    let days = ms.div_euclid(MS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let secs = ms.rem_euclid(MS_PER_DAY) / 1000;
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        // 1970-01-01 was a Thursday
+        WEEKDAYS[(days + 3).rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+pub fn day_key(timestamp_ms: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp_ms as i64).div_euclid(MS_PER_DAY));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// ISO 8601 week: weeks start on Monday and belong to the year of their Thursday
+pub fn week_key(timestamp_ms: u64) -> String {
+    let days = (timestamp_ms as i64).div_euclid(MS_PER_DAY);
+    // 1970-01-01 was a Thursday
+    let weekday = (days + 3).rem_euclid(7);
+    let thursday = days - weekday + 3;
+    let (year, _, _) = civil_from_days(thursday);
+    let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+    format!("{:04}-W{:02}", year, week)
+}
+
+fn by_period(entries: &[&HistoryEntry], key: fn(u64) -> String) -> Vec<PeriodStats> {
+    let mut periods: BTreeMap<String, PeriodStats> = BTreeMap::new();
+    for entry in entries {
+        let period = key(entry.finished_at_ms);
+        let stats = periods.entry(period.clone()).or_insert_with(|| PeriodStats { period, ..Default::default() });
+        stats.downloads += 1;
+        stats.bytes += entry.bytes;
+    }
+    periods.into_values().collect()
+}
+
+pub fn compute(entries: &[HistoryEntry], since_ms: Option<u64>) -> DownloadStats {
+    let entries: Vec<&HistoryEntry> = entries
+        .iter()
+        .filter(|entry| entry.finished_at_ms >= since_ms.unwrap_or(0))
+        .collect();
+
+    let count = |status: JobStatus| entries.iter().filter(|e| e.status == status).count();
+    let completed: Vec<&&HistoryEntry> = entries.iter().filter(|e| e.status == JobStatus::Completed).collect();
+    let average_speed_bytes_per_sec = if completed.is_empty() {
+        0
+    } else {
+        completed.iter().map(|e| e.average_speed()).sum::<u64>() / completed.len() as u64
+    };
+
+    let mut sites: BTreeMap<&str, SiteStats> = BTreeMap::new();
+    for entry in &entries {
+        let stats = sites
+            .entry(entry.site.as_str())
+            .or_insert_with(|| SiteStats { site: entry.site.clone(), ..Default::default() });
+        stats.downloads += 1;
+        stats.bytes += entry.bytes;
+    }
+    let mut per_site: Vec<SiteStats> = sites.into_values().collect();
+    per_site.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(b.bytes.cmp(&a.bytes)));
+
+    DownloadStats {
+        total_downloads: entries.len(),
+        completed: completed.len(),
+        failed: count(JobStatus::Failed),
+        cancelled: count(JobStatus::Cancelled),
+        total_bytes: entries.iter().map(|e| e.bytes).sum(),
+        average_speed_bytes_per_sec,
+        per_site,
+        per_day: by_period(&entries, day_key),
+        per_week: by_period(&entries, week_key),
+    }
+}
+
+/// Statistics over the whole history, or only downloads finished after `since_ms`
+#[tauri::command]
+pub async fn get_download_stats<R: Runtime>(app_handle: AppHandle<R>, since_ms: Option<u64>) -> Result<DownloadStats, String> {
+    Ok(compute(&history::all(&app_handle), since_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates_around_leap_days_and_month_ends() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        // 1900 and 2100 are not leap years
+        assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        assert_eq!(civil_from_days(20_088), (2024, 12, 31));
+    }
+
+    #[test]
+    fn days_round_trip_through_civil_dates() {
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn timestamps_format_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_741_944_413_000), "2025-03-14T09:26:53Z");
+        assert_eq!(format_rfc2822(1_741_944_413_000), "Fri, 14 Mar 2025 09:26:53 GMT");
+        assert_eq!(day_key(1_741_944_413_000), "2025-03-14");
+    }
+
+    #[test]
+    fn iso_weeks_at_year_boundaries() {
+        // 2021-01-03 is a Sunday in the last week of 2020
+        assert_eq!(week_key(1_609_675_200_000), "2020-W53");
+        // 2024-12-30 is the Monday of 2025's first week
+        assert_eq!(week_key(1_735_560_000_000), "2025-W01");
+        assert_eq!(week_key(1_767_268_800_000), "2026-W01");
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Path of a file in the app data directory, creating the directory if needed
+pub fn app_data_file<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory {}: {}", dir.display(), e))?;
+    Ok(dir.join(name))
+}
+
+/// Read a JSON file, returning `None` if it does not exist yet
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Write a JSON file atomically (write to a temp file, then rename over the target)
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+/// Folders nothing should be downloaded into: the OS and program directories
+fn system_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramW6432", "ProgramData"]
+            .iter()
+            .filter_map(|var| std::env::var_os(var))
+            .map(PathBuf::from)
+            .collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        [
+            "/bin", "/sbin", "/usr", "/etc", "/boot", "/dev", "/proc", "/sys", "/lib", "/lib64", "/var/lib",
+            "/System", "/Library", "/Applications", "/private/etc", "/private/var/db",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect()
+    }
+}
+
+/// `name` with the characters Windows forbids in file names, and control characters, replaced by `_`
+pub fn sanitize_file_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect()
+}
+
+/// Check a file name that came from the webview or an import file. It must be a bare name, so
+/// it cannot leave the output folder. Returns it sanitized.
+pub fn validate_file_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() || trimmed == "." || trimmed == ".." || trimmed.contains(['/', '\\']) {
+        return Err(format!("Invalid file name \"{}\"; use a name without folders", name));
+    }
+    Ok(sanitize_file_name(trimmed))
+}
+
+/// Check a download folder that came from the webview or settings: it must be an absolute path to
+/// an existing, writable directory outside system folders and the app's own data and resources.
+/// Returns the canonical path, so `..` and symlinks cannot point somewhere else later.
+pub fn validate_output_folder<R: Runtime>(app: &AppHandle<R>, folder: &str) -> Result<String, String> {
+    let folder = folder.trim();
+    let path = Path::new(folder);
+    if folder.is_empty() || folder.contains('\0') {
+        return Err("Output folder is empty or invalid".to_string());
+    }
+    if !path.is_absolute() {
+        return Err(format!("Output folder must be an absolute path: {}", folder));
+    }
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Output folder does not exist: {} ({})", folder, e))?;
+    if !canonical.is_dir() {
+        return Err(format!("Output folder is not a directory: {}", canonical.display()));
+    }
+
+    // A filesystem root is not a folder anyone means to fill with videos
+    if canonical.parent().is_none() {
+        return Err(format!("Refusing to download into {}", canonical.display()));
+    }
+    let app_dirs = [app.path().app_data_dir(), app.path().resource_dir(), app.path().app_config_dir()];
+    let forbidden = system_dirs()
+        .into_iter()
+        .chain(app_dirs.into_iter().flatten())
+        .filter_map(|dir| dir.canonicalize().ok());
+    for dir in forbidden {
+        if canonical.starts_with(&dir) {
+            return Err(format!("Refusing to download into {}, which is inside {}", canonical.display(), dir.display()));
+        }
+    }
+
+    let probe = canonical.join(format!(".u-download-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("Output folder is not writable: {} ({})", canonical.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+
+    let canonical = canonical.to_string_lossy().to_string();
+    // canonicalize() gives verbatim `\\?\C:\...` paths on Windows, which ffmpeg and the UI don't expect
+    #[cfg(target_os = "windows")]
+    if let Some(plain) = canonical.strip_prefix(r"\\?\").filter(|rest| !rest.starts_with("UNC\\")) {
+        return Ok(plain.to_string());
+    }
+    Ok(canonical)
+}
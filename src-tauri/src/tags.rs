@@ -0,0 +1,111 @@
+// User-defined tags on downloads ("workout", "lecture"). They are set when queuing through
+// `DownloadOptions::tags`, can be edited later on queued and finished downloads, and can route
+// downloads to a folder per tag.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::{DownloadManagerState, DownloadRequest};
+use crate::{download_manager, history, settings, storage};
+
+const MAX_TAG_CHARS: usize = 32;
+
+/// Tags are compared case-insensitively and stored lowercase
+pub fn normalize(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tags cannot be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_CHARS {
+        return Err(format!("Tags can be at most {} characters: {}", MAX_TAG_CHARS, tag));
+    }
+    if tag.contains(['/', '\\', ',']) {
+        return Err(format!("Tags cannot contain slashes or commas: {}", tag));
+    }
+    Ok(tag)
+}
+
+/// The folder of the first tag with a route, in place of the request's own folder
+pub fn apply_folder_route<R: Runtime>(app: &AppHandle<R>, mut request: DownloadRequest) -> DownloadRequest {
+    let routes = settings::current(app).folders.by_tag;
+    let Some(route) = routes.iter().find(|route| {
+        normalize(&route.tag).is_ok_and(|tag| request.options.tags.contains(&tag))
+    }) else {
+        return request;
+    };
+    match storage::validate_output_folder(app, &route.folder) {
+        Ok(folder) => {
+            eprintln!("🏷️ Routing {} to {} for tag \"{}\"", request.url, folder, route.tag);
+            request.output_folder = folder;
+        }
+        Err(e) => eprintln!("⚠️  Ignoring the folder for tag \"{}\": {}", route.tag, e),
+    }
+    request
+}
+
+/// Apply `change` to the tags of a queued job and of its history entry, whichever exist.
+/// Returns the resulting tags.
+fn edit_tags<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    tags: Vec<String>,
+    change: impl Fn(&mut Vec<String>, &[String]),
+) -> Result<Vec<String>, String> {
+    let tags: Vec<String> = tags.iter().map(|tag| normalize(tag)).collect::<Result<_, _>>()?;
+    let mut result = None;
+
+    let in_queue = {
+        let state = app.state::<DownloadManagerState>();
+        let mut manager = state.lock().unwrap();
+        manager.job_mut(id).map(|job| {
+            change(&mut job.request.options.tags, &tags);
+            result = Some(job.request.options.tags.clone());
+        })
+    };
+    if in_queue.is_some() {
+        download_manager::persist(app);
+    }
+    history::update(app, id, |entry| {
+        change(&mut entry.tags, &tags);
+        result = Some(entry.tags.clone());
+    });
+
+    result.ok_or_else(|| format!("Unknown download id: {}", id))
+}
+
+#[tauri::command]
+pub async fn tag_download<R: Runtime>(app_handle: AppHandle<R>, id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    edit_tags(&app_handle, &id, tags, |current, added| {
+        for tag in added {
+            if !current.contains(tag) {
+                current.push(tag.clone());
+            }
+        }
+    })
+}
+
+#[tauri::command]
+pub async fn untag_download<R: Runtime>(app_handle: AppHandle<R>, id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    edit_tags(&app_handle, &id, tags, |current, removed| current.retain(|tag| !removed.contains(tag)))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TagCount {
+    pub tag: String,
+    /// History entries with the tag
+    pub count: usize,
+}
+
+/// Every tag in history, most used first
+#[tauri::command]
+pub async fn list_tags<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<TagCount>, String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in history::all(&app_handle) {
+        for tag in entry.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags: Vec<TagCount> = counts.into_iter().map(|(tag, count)| TagCount { tag, count }).collect();
+    tags.sort_by_key(|tag| std::cmp::Reverse(tag.count));
+    Ok(tags)
+}
@@ -0,0 +1,26 @@
+// Overall queue progress on the Windows taskbar button, the macOS dock icon and the
+// Unity launcher entry on Linux (where the desktop environment supports it)
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::download_manager::QueueProgress;
+
+pub fn update<R: Runtime>(app: &AppHandle<R>, summary: &QueueProgress) {
+    let Some(window) = app.get_webview_window("main") else { return };
+
+    let state = if summary.active == 0 {
+        ProgressBarState { status: Some(ProgressBarStatus::None), progress: None }
+    } else if summary.total_bytes == 0 {
+        // Downloads are starting up and yt-dlp has not reported a size yet
+        ProgressBarState { status: Some(ProgressBarStatus::Indeterminate), progress: None }
+    } else {
+        ProgressBarState {
+            status: Some(ProgressBarStatus::Normal),
+            progress: Some(summary.percentage.round().clamp(0.0, 100.0) as u64),
+        }
+    };
+
+    if let Err(e) = window.set_progress_bar(state) {
+        eprintln!("⚠️ Failed to update taskbar progress: {}", e);
+    }
+}
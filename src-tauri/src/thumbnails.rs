@@ -0,0 +1,107 @@
+// Video thumbnails downloaded once and kept under the app data directory, so the UI can show
+// previews from local files instead of hotlinking remote images. The webview cannot load
+// filesystem paths, so the UI gets them as `data:` URLs.
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime};
+
+use crate::storage;
+
+const CACHE_DIR: &str = "thumbnails";
+const DEFAULT_WIDTH: u64 = 480;
+
+/// Best thumbnail URL for a target width: the smallest at least that wide, else the largest
+fn pick_thumbnail(metadata: &serde_json::Value, width: u64) -> Option<String> {
+    let mut candidates: Vec<(u64, &str)> = metadata["thumbnails"]
+        .as_array()
+        .map(|thumbs| {
+            thumbs
+                .iter()
+                .filter_map(|t| Some((t["width"].as_u64().unwrap_or(0), t["url"].as_str()?)))
+                // WebP isn't supported by every WebView this app ships on
+                .filter(|(_, url)| !url.contains(".webp"))
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort_by_key(|(w, _)| *w);
+
+    candidates
+        .iter()
+        .find(|(w, _)| *w >= width)
+        .or(candidates.last())
+        .map(|(_, url)| url.to_string())
+        .or_else(|| metadata["thumbnail"].as_str().map(str::to_string))
+}
+
+fn extension_of(content_type: Option<&str>, url: &str) -> &'static str {
+    match content_type.unwrap_or_default() {
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/jpeg" | "image/jpg" => "jpg",
+        _ if url.contains(".png") => "png",
+        _ => "jpg",
+    }
+}
+
+fn cache_key(url: &str, width: u64) -> String {
+    let digest = Sha256::digest(format!("{}@{}", url.trim(), width).as_bytes());
+    format!("{:x}", digest)[..24].to_string()
+}
+
+/// Local path of a video's thumbnail, downloading it on first use
+pub async fn cached_path<R: Runtime>(app_handle: &AppHandle<R>, url: &str, size: Option<u64>) -> Result<PathBuf, String> {
+    let width = size.unwrap_or(DEFAULT_WIDTH).clamp(64, 3840);
+    let dir = storage::app_data_file(app_handle, CACHE_DIR)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create thumbnail cache: {}", e))?;
+
+    let key = cache_key(url, width);
+    for ext in ["jpg", "png", "webp"] {
+        let cached = dir.join(format!("{}.{}", key, ext));
+        if cached.is_file() {
+            return Ok(cached);
+        }
+    }
+
+    let metadata = crate::fetch_metadata_json(app_handle, url).await?;
+    let thumbnail_url = pick_thumbnail(&metadata, width).ok_or("This video has no thumbnail")?;
+
+    let response = reqwest::Client::new()
+        .get(&thumbnail_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download thumbnail: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Thumbnail download failed with status: {}", response.status()));
+    }
+    let ext = extension_of(
+        response.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()),
+        &thumbnail_url,
+    );
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+
+    let path: PathBuf = dir.join(format!("{}.{}", key, ext));
+    let tmp = path.with_extension("part");
+    std::fs::write(&tmp, &bytes).map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+    Ok(path)
+}
+
+fn data_url(path: &Path) -> Result<String, String> {
+    use base64::Engine;
+    let mime = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        _ => "image/jpeg",
+    };
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes)))
+}
+
+/// A video's thumbnail as a `data:` URL for an `<img>`, downloading it on first use
+#[tauri::command]
+pub async fn fetch_thumbnail<R: Runtime>(app_handle: AppHandle<R>, url: String, size: Option<u64>) -> Result<String, String> {
+    data_url(&cached_path(&app_handle, &url, size).await?)
+}
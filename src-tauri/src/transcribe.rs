@@ -0,0 +1,294 @@
+// Offline transcription with whisper.cpp. The release workflow builds `whisper-cli` and bundles it
+// beside the other tools; speech models are large, so each is downloaded on demand into app data
+// and checked against its pinned SHA-256.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::io::AsyncWriteExt;
+
+use crate::{binary_manager, ffmpeg, storage};
+
+const MODELS_DIR: &str = "whisper-models";
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Available models with their approximate download size in MB and the SHA-256 of the file;
+/// bigger is slower and more accurate
+const MODELS: &[(&str, u64, &str)] = &[
+    ("tiny", 75, "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21"),
+    ("base", 142, "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe"),
+    ("small", 466, "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b"),
+    ("medium", 1500, "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208"),
+    ("large-v3-turbo", 1620, "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69"),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Srt,
+    Vtt,
+}
+
+impl TranscriptFormat {
+    fn ext(self) -> &'static str {
+        match self {
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::Vtt => "vtt",
+        }
+    }
+}
+
+/// Per-download transcription request
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TranscriptionOptions {
+    /// Model name from `list_whisper_models`; `base` when empty
+    pub model: String,
+    /// Spoken language code such as `en`; detected automatically when unset
+    pub language: Option<String>,
+    pub format: TranscriptFormat,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WhisperModel {
+    pub name: &'static str,
+    pub size_mb: u64,
+    pub installed: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModelDownloadProgress {
+    pub model: String,
+    pub bytes_downloaded: u64,
+    pub total_bytes: u64,
+}
+
+fn check_model_name(model: &str) -> Result<&'static str, String> {
+    MODELS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .find(|name| *name == model)
+        .ok_or_else(|| format!("Unknown whisper model: {}", model))
+}
+
+pub fn model_path<R: Runtime>(app: &AppHandle<R>, model: &str) -> Result<PathBuf, String> {
+    let name = check_model_name(model)?;
+    let dir = storage::app_data_file(app, MODELS_DIR)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir.join(format!("ggml-{}.bin", name)))
+}
+
+impl TranscriptionOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.model.is_empty() {
+            check_model_name(&self.model)?;
+        }
+        if let Some(language) = &self.language {
+            if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) {
+                return Err(format!("Language must be a code like en or de (got \"{}\")", language));
+            }
+        }
+        Ok(())
+    }
+
+    fn model_name(&self) -> &str {
+        if self.model.is_empty() { "base" } else { &self.model }
+    }
+}
+
+/// Transcribe a media file into a `.srt`/`.vtt` beside it, reporting 0–100 through `on_progress`.
+/// Blocks until whisper finishes.
+pub fn transcribe<R: Runtime>(
+    app: &AppHandle<R>,
+    media: &Path,
+    options: &TranscriptionOptions,
+    mut on_progress: impl FnMut(f64),
+) -> Result<PathBuf, String> {
+    let paths = binary_manager::resolve_paths(app)?;
+    let whisper = binary_manager::optional_binary(&paths, "whisper-cli")
+        .ok_or("Transcription is not available in this build (whisper-cli is missing)")?;
+    let model = model_path(app, options.model_name())?;
+    if !model.is_file() {
+        return Err(format!("The {} speech model is not downloaded yet", options.model_name()));
+    }
+
+    // whisper.cpp only reads 16 kHz mono WAV
+    let wav = ffmpeg::working_path(&media.with_extension("wav"));
+    let mut convert = binary_manager::tool_command(&paths.ffmpeg);
+    convert
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(media)
+        .arg("-vn")
+        .arg("-ar")
+        .arg("16000")
+        .arg("-ac")
+        .arg("1")
+        .arg("-c:a")
+        .arg("pcm_s16le")
+        .arg(&wav);
+    // Conversion is quick next to transcription; count it as the first 5%
+    let converted = ffmpeg::run_with_progress(convert, ffmpeg::probe_duration(&paths.ffmpeg, media), |p| {
+        on_progress(p * 0.05)
+    });
+    if let Err(e) = converted {
+        let _ = std::fs::remove_file(&wav);
+        return Err(e);
+    }
+
+    // whisper appends the extension to -of itself
+    let output_stem = media.with_extension("");
+    let mut cmd = binary_manager::tool_command(&whisper);
+    cmd.arg("-m")
+        .arg(&model)
+        .arg("-f")
+        .arg(&wav)
+        .arg(format!("-o{}", options.format.ext()))
+        .arg("-of")
+        .arg(&output_stem)
+        .arg("-l")
+        .arg(options.language.as_deref().unwrap_or("auto"))
+        .arg("-pp")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    eprintln!("🗣️ Transcribing {} with the {} model", media.display(), options.model_name());
+
+    let result = (|| {
+        let (mut child, _child_guard) = crate::download_manager::spawn_tracked(&mut cmd)
+            .map_err(|e| format!("Failed to run whisper: {}", e))?;
+        let progress_line = regex::Regex::new(r"progress\s*=\s*(\d+)%").unwrap();
+        let mut log = Vec::new();
+        if let Some(stderr) = child.stderr.take() {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                match progress_line.captures(&line).and_then(|c| c[1].parse::<f64>().ok()) {
+                    Some(percent) => on_progress(5.0 + percent * 0.95),
+                    None => log.push(line),
+                }
+            }
+        }
+        let status = child.wait().map_err(|e| format!("Whisper process error: {}", e))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("Transcription failed: {}", log.join("\n").trim()))
+        }
+    })();
+    let _ = std::fs::remove_file(&wav);
+    result?;
+
+    on_progress(100.0);
+    Ok(output_stem.with_extension(options.format.ext()))
+}
+
+#[tauri::command]
+pub async fn list_whisper_models<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<WhisperModel>, String> {
+    MODELS
+        .iter()
+        .map(|(name, size_mb, _)| {
+            Ok(WhisperModel { name, size_mb: *size_mb, installed: model_path(&app_handle, name)?.is_file() })
+        })
+        .collect()
+}
+
+/// Download a speech model, emitting `whisper-model-progress` as it arrives
+#[tauri::command]
+pub async fn download_whisper_model<R: Runtime>(app_handle: AppHandle<R>, model: String) -> Result<(), String> {
+    let path = model_path(&app_handle, &model)?;
+    if path.is_file() {
+        return Ok(());
+    }
+
+    let expected_sha256 = MODELS
+        .iter()
+        .find(|(name, _, _)| *name == model)
+        .map(|(_, _, sha256)| *sha256)
+        .unwrap_or_default();
+    let url = format!("{}/{}", MODEL_BASE_URL, path.file_name().unwrap_or_default().to_string_lossy());
+    let mut response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Failed to download the {} model: {}", model, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Model download failed with status: {}", response.status()));
+    }
+    let total_bytes = response.content_length().unwrap_or(0);
+
+    let part = path.with_extension("bin.part");
+    let mut file = tokio::fs::File::create(&part)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", part.display(), e))?;
+    let mut progress = ModelDownloadProgress { model: model.clone(), bytes_downloaded: 0, total_bytes };
+    let mut last_emit = std::time::Instant::now();
+    let mut hasher = Sha256::new();
+
+    let result: Result<(), String> = async {
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("Model download interrupted: {}", e))? {
+            file.write_all(&chunk).await.map_err(|e| format!("Failed to write model: {}", e))?;
+            hasher.update(&chunk);
+            progress.bytes_downloaded += chunk.len() as u64;
+            if last_emit.elapsed() >= std::time::Duration::from_millis(250) {
+                let _ = app_handle.emit("whisper-model-progress", progress.clone());
+                last_emit = std::time::Instant::now();
+            }
+        }
+        file.flush().await.map_err(|e| format!("Failed to write model: {}", e))?;
+        let actual_sha256 = format!("{:x}", hasher.finalize());
+        if actual_sha256 != expected_sha256 {
+            return Err(format!(
+                "The downloaded {} model is corrupted or was changed (SHA-256 {}, expected {})",
+                model, actual_sha256, expected_sha256
+            ));
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&part, &path)
+        .await
+        .map_err(|e| format!("Failed to save model: {}", e))?;
+    let _ = app_handle.emit("whisper-model-progress", progress);
+    eprintln!("✅ Downloaded whisper model {}", model);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_whisper_model<R: Runtime>(app_handle: AppHandle<R>, model: String) -> Result<(), String> {
+    let path = model_path(&app_handle, &model)?;
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete {}: {}", path.display(), e)),
+    }
+}
+
+/// Transcribe a local file on demand; progress arrives as `tool-progress`
+#[tauri::command]
+pub async fn transcribe_file<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    options: Option<TranscriptionOptions>,
+) -> Result<String, String> {
+    let media = PathBuf::from(&path);
+    if !media.is_file() {
+        return Err(format!("File not found: {}", media.display()));
+    }
+    let options = options.unwrap_or_default();
+    options.validate()?;
+
+    let app = app_handle.clone();
+    tokio::task::spawn_blocking(move || {
+        transcribe(&app, &media, &options, |percentage| {
+            let _ = app.emit("tool-progress", crate::media_tools::ToolProgress { output: path.clone(), percentage });
+        })
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {}", e))?
+    .map(|transcript| transcript.to_string_lossy().to_string())
+}
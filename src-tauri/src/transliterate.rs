@@ -0,0 +1,69 @@
+// ASCII file names for devices and NAS shares that mangle Unicode. Only files on disk are
+// renamed, after post-processing; titles in metadata and history keep their original script.
+use std::path::{Path, PathBuf};
+
+/// Files written next to a download under its name: subtitles, NFO, transcripts, thumbnails
+const SIDECAR_EXTENSIONS: &[&str] = &["srt", "vtt", "ass", "lrc", "nfo", "txt", "json", "jpg", "png", "webp"];
+
+/// ASCII spelling of a file name stem, e.g. `Москва` → `Moskva`, `東京` → `Dong Jing`
+pub fn to_ascii(stem: &str) -> String {
+    let ascii = deunicode::deunicode_with_tofu(stem, "_");
+    // Full-width punctuation transliterates to characters Windows forbids, e.g. `：` → `:`
+    let ascii: String = ascii
+        .chars()
+        .map(|c| if c.is_control() || r#"<>:"/\|?*"#.contains(c) { '_' } else { c })
+        .collect();
+    let ascii = ascii.trim();
+    if ascii.is_empty() {
+        "_".to_string()
+    } else {
+        ascii.to_string()
+    }
+}
+
+/// `dir/<stem><suffix>`, numbered `<stem> (2)<suffix>` and up while the name is taken
+fn free_path(dir: &Path, stem: &str, suffix: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}{}", stem, suffix));
+    let mut copy = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}){}", stem, copy, suffix));
+        copy += 1;
+    }
+    path
+}
+
+/// Give `file` an ASCII name, along with the sidecars that share its stem (`<stem>.en.srt`,
+/// `<stem>.nfo`, transcripts). Returns the new path of `file`.
+pub fn rename(file: &Path) -> Result<PathBuf, String> {
+    let (Some(dir), Some(stem)) = (file.parent(), file.file_stem().and_then(|stem| stem.to_str())) else {
+        return Ok(file.to_path_buf());
+    };
+    if stem.is_ascii() {
+        return Ok(file.to_path_buf());
+    }
+    let extension = file.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let renamed = free_path(dir, &to_ascii(stem), &extension);
+    // The number picked for the file, if any, carries over to its sidecars
+    let new_stem = renamed.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    std::fs::rename(file, &renamed).map_err(|e| format!("Failed to rename {}: {}", file.display(), e))?;
+
+    let sidecar_prefix = format!("{}.", stem);
+    let sidecars = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in sidecars.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(rest) = name.strip_prefix(&sidecar_prefix) else { continue };
+        let is_sidecar = rest.rsplit('.').next().is_some_and(|ext| SIDECAR_EXTENSIONS.contains(&ext));
+        if !is_sidecar {
+            continue;
+        }
+        let target = dir.join(format!("{}.{}", new_stem, rest));
+        if target.exists() {
+            continue;
+        }
+        if let Err(e) = std::fs::rename(entry.path(), &target) {
+            eprintln!("⚠️  Failed to rename {}: {}", entry.path().display(), e);
+        }
+    }
+    eprintln!("🔤 Renamed {} to {}", file.display(), renamed.display());
+    Ok(renamed)
+}
@@ -0,0 +1,305 @@
+// System tray: live status of the queue plus quick actions
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::download_manager::{self, DownloadManagerState, QueueProgress};
+use crate::i18n::{t, tf};
+use crate::{aria2, settings, DownloadProgress};
+
+const TRAY_ID: &str = "main";
+
+/// Menu entries whose text changes while downloads run or when the locale changes
+pub struct TrayItems<R: Runtime> {
+    status: MenuItem<R>,
+    queue: MenuItem<R>,
+    labels: Vec<(MenuItem<R>, &'static str)>,
+    /// App icon the state badges are drawn on
+    base_icon: Image<'static>,
+    icon_state: Mutex<IconState>,
+}
+
+/// What the tray icon shows; the icon is only redrawn when this changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconState {
+    Idle,
+    /// Progress bar filled to `tenths` of the width, red once a download in the run has failed
+    Progress { tenths: u8, failed: bool },
+    /// Sizes are not known yet: a segment sweeps across the bar
+    Indeterminate { frame: u8 },
+    /// Downloads are waiting but none is running after a failure
+    Error,
+}
+
+const SWEEP_FRAMES: u8 = 4;
+const BLUE: [u8; 4] = [59, 130, 246, 255];
+const RED: [u8; 4] = [239, 68, 68, 255];
+const TRACK: [u8; 4] = [31, 41, 55, 230];
+
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub fn build<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let status_item = MenuItem::with_id(app, "status", t("tray-idle"), false, None::<&str>)?;
+    let queue_item = MenuItem::with_id(app, "queue", t("tray-queue-empty"), false, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, "show", t("tray-show"), true, None::<&str>)?;
+    let pause_item = MenuItem::with_id(app, "pause_all", t("tray-pause-all"), true, None::<&str>)?;
+    let cancel_item = MenuItem::with_id(app, "cancel_all", t("tray-cancel-all"), true, None::<&str>)?;
+    let folder_item = MenuItem::with_id(app, "open_folder", t("tray-open-folder"), true, None::<&str>)?;
+    let overlay_item = MenuItem::with_id(app, "overlay", t("tray-overlay"), true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", t("tray-quit"), true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &queue_item,
+            &PredefinedMenuItem::separator(app)?,
+            &show_item,
+            &pause_item,
+            &cancel_item,
+            &folder_item,
+            &overlay_item,
+            &PredefinedMenuItem::separator(app)?,
+            &quit_item,
+        ],
+    )?;
+
+    let base_icon = app.default_window_icon().unwrap().clone().to_owned();
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(base_icon.clone())
+        .menu(&menu)
+        .tooltip("U-Download")
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "show" => show_main_window(app),
+            "pause_all" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = download_manager::pause_all(&app).await {
+                        eprintln!("⚠️ Failed to pause downloads: {}", e);
+                    }
+                });
+            }
+            "cancel_all" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = download_manager::cancel_all(&app).await {
+                        eprintln!("⚠️ Failed to cancel downloads: {}", e);
+                    }
+                });
+            }
+            "open_folder" => open_downloads_folder(app),
+            "overlay" => {
+                if let Err(e) = crate::monitor::toggle_overlay(app) {
+                    eprintln!("⚠️ {}", e);
+                }
+            }
+            "quit" => confirm_quit(app),
+            _ => {}
+        })
+        .build(app)?;
+
+    app.manage(TrayItems {
+        status: status_item,
+        queue: queue_item,
+        labels: vec![
+            (show_item, "tray-show"),
+            (pause_item, "tray-pause-all"),
+            (cancel_item, "tray-cancel-all"),
+            (folder_item, "tray-open-folder"),
+            (overlay_item, "tray-overlay"),
+            (quit_item, "tray-quit"),
+        ],
+        base_icon,
+        icon_state: Mutex::new(IconState::Idle),
+    });
+    Ok(())
+}
+
+/// Re-translate the fixed menu entries after a locale change; the status lines follow on the
+/// next queue update
+pub fn relabel<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(items) = app.try_state::<TrayItems<R>>() {
+        for (item, key) in &items.labels {
+            let _ = item.set_text(t(key));
+        }
+        let _ = items.status.set_text(t("tray-idle"));
+        let _ = items.queue.set_text(t("tray-queue-empty"));
+    }
+}
+
+fn open_downloads_folder<R: Runtime>(app: &AppHandle<R>) {
+    let folder = app
+        .state::<DownloadManagerState>()
+        .lock()
+        .unwrap()
+        .last_output_folder()
+        .or_else(|| settings::current(app).general.default_output_folder);
+    match folder {
+        Some(folder) => {
+            if let Err(e) = app.opener().open_path(&folder, None::<&str>) {
+                eprintln!("⚠️ Failed to open {}: {}", folder, e);
+            }
+        }
+        None => eprintln!("⚠️ No downloads folder chosen yet"),
+    }
+}
+
+fn confirm_quit<R: Runtime>(app: &AppHandle<R>) {
+    let app_handle = app.clone();
+    app.dialog()
+        .message(t("quit-message"))
+        .title(t("quit-title"))
+        .kind(tauri_plugin_dialog::MessageDialogKind::Info)
+        .buttons(tauri_plugin_dialog::MessageDialogButtons::OkCancelCustom(
+            t("yes"),
+            t("no"),
+        ))
+        .show(move |answer| {
+            if answer {
+                std::thread::spawn(move || {
+                    download_manager::kill_children();
+                    aria2::shutdown(&app_handle.state::<aria2::Aria2State>());
+                    app_handle.exit(0);
+                });
+            }
+        });
+}
+
+/// Refresh the tooltip and status entries from the latest queue summary
+pub fn update<R: Runtime>(app: &AppHandle<R>, summary: &QueueProgress, headline: Option<&DownloadProgress>) {
+    let status = match headline {
+        Some(progress) => {
+            let title = if progress.title.is_empty() { t("tray-downloading") } else { progress.title.clone() };
+            tf(
+                "tray-progress",
+                &[
+                    ("title", &truncate(&title, 40)),
+                    ("percent", &format!("{:.0}", progress.percentage)),
+                    ("speed", &progress.speed),
+                ],
+            )
+        }
+        None if summary.active > 0 => t("tray-preparing"),
+        None => t("tray-idle"),
+    };
+    let queue = if summary.active + summary.queued == 0 {
+        t("tray-queue-empty")
+    } else {
+        tf(
+            "tray-queue",
+            &[
+                ("active", &summary.active.to_string()),
+                ("queued", &summary.queued.to_string()),
+                ("percent", &format!("{:.0}", summary.percentage)),
+            ],
+        )
+    };
+
+    if let Some(items) = app.try_state::<TrayItems<R>>() {
+        let _ = items.status.set_text(&status);
+        let _ = items.queue.set_text(&queue);
+        update_icon(app, &items, summary);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let tooltip = if summary.active + summary.queued == 0 {
+            "U-Download".to_string()
+        } else {
+            format!("U-Download\n{}\n{}", status, queue)
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars - 1).collect::<String>())
+    }
+}
+
+fn icon_state(summary: &QueueProgress, previous: IconState) -> IconState {
+    if summary.active > 0 && summary.total_bytes == 0 {
+        let frame = match previous {
+            IconState::Indeterminate { frame } => (frame + 1) % SWEEP_FRAMES,
+            _ => 0,
+        };
+        IconState::Indeterminate { frame }
+    } else if summary.active > 0 {
+        let tenths = (summary.percentage / 10.0).floor().clamp(0.0, 10.0) as u8;
+        IconState::Progress { tenths, failed: summary.failed > 0 }
+    } else if summary.queued > 0 && summary.failed > 0 {
+        IconState::Error
+    } else {
+        IconState::Idle
+    }
+}
+
+fn update_icon<R: Runtime>(app: &AppHandle<R>, items: &TrayItems<R>, summary: &QueueProgress) {
+    let mut current = items.icon_state.lock().unwrap();
+    let next = icon_state(summary, *current);
+    if next == *current {
+        return;
+    }
+    *current = next;
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Err(e) = tray.set_icon(Some(draw_icon(&items.base_icon, next))) {
+            eprintln!("⚠️ Failed to update tray icon: {}", e);
+        }
+    }
+}
+
+/// The app icon with a progress bar along the bottom or an error dot in the corner
+fn draw_icon(base: &Image<'_>, state: IconState) -> Image<'static> {
+    let (width, height) = (base.width() as usize, base.height() as usize);
+    let mut rgba = base.rgba().to_vec();
+    let mut fill = |x0: usize, y0: usize, x1: usize, y1: usize, color: [u8; 4]| {
+        for y in y0..y1.min(height) {
+            for x in x0..x1.min(width) {
+                let i = (y * width + x) * 4;
+                rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    };
+
+    let bar_top = height - height / 4;
+    let bar_bottom = height - height / 16;
+    let inset = width / 16;
+    let bar_width = width - 2 * inset;
+    match state {
+        IconState::Idle => {}
+        IconState::Progress { tenths, failed } => {
+            fill(inset, bar_top, width - inset, bar_bottom, TRACK);
+            let filled = bar_width * tenths as usize / 10;
+            fill(inset, bar_top, inset + filled, bar_bottom, if failed { RED } else { BLUE });
+        }
+        IconState::Indeterminate { frame } => {
+            fill(inset, bar_top, width - inset, bar_bottom, TRACK);
+            let segment = bar_width / SWEEP_FRAMES as usize;
+            let start = inset + segment * frame as usize;
+            fill(start, bar_top, start + segment, bar_bottom, BLUE);
+        }
+        IconState::Error => {
+            let radius = width / 5;
+            let (cx, cy) = (width - radius - 1, height - radius - 1);
+            for y in cy - radius..=cy + radius {
+                for x in cx - radius..=cx + radius {
+                    let (dx, dy) = (x.abs_diff(cx), y.abs_diff(cy));
+                    if dx * dx + dy * dy <= radius * radius {
+                        let i = (y * width + x) * 4;
+                        rgba[i..i + 4].copy_from_slice(&RED);
+                    }
+                }
+            }
+        }
+    }
+    Image::new_owned(rgba, width as u32, height as u32)
+}
@@ -0,0 +1,199 @@
+// Application self-update: check the signed release manifest at startup, download the
+// installer in the background (the updater plugin verifies its signature) and install on request
+#![cfg_attr(target_os = "android", allow(dead_code, unused_imports))]
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AppUpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AppUpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+#[cfg(not(target_os = "android"))]
+#[derive(Default)]
+pub struct PendingUpdate {
+    update: Option<tauri_plugin_updater::Update>,
+    /// Verified installer bytes, once the background download finishes
+    bytes: Option<Vec<u8>>,
+    downloading: bool,
+}
+
+#[cfg(not(target_os = "android"))]
+pub type UpdaterState = Arc<Mutex<PendingUpdate>>;
+
+#[cfg(not(target_os = "android"))]
+fn info(update: &tauri_plugin_updater::Update) -> AppUpdateInfo {
+    AppUpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        date: update.date.map(|d| d.to_string()),
+    }
+}
+
+/// Whether this build carries the public key release manifests are signed with. Until the
+/// signing key is set up in tauri.conf.json and the release workflow, updates are off.
+#[cfg(not(target_os = "android"))]
+fn configured<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater["pubkey"].as_str())
+        .is_some_and(|pubkey| !pubkey.trim().is_empty())
+}
+
+#[cfg(not(target_os = "android"))]
+async fn check<R: Runtime>(app: &AppHandle<R>) -> Result<Option<AppUpdateInfo>, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    if !configured(app) {
+        return Err("Updates are not configured for this build".to_string());
+    }
+    let update = app
+        .updater()
+        .map_err(|e| format!("Updater unavailable: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+
+    let state = app.state::<UpdaterState>();
+    let mut pending = state.lock().unwrap();
+    match update {
+        Some(update) => {
+            let info = info(&update);
+            // A newer release replaces whatever was fetched for an older one
+            if pending.update.as_ref().is_none_or(|u| u.version != update.version) {
+                pending.bytes = None;
+            }
+            pending.update = Some(update);
+            Ok(Some(info))
+        }
+        None => {
+            *pending = PendingUpdate::default();
+            Ok(None)
+        }
+    }
+}
+
+/// Fetch the installer for the pending update, emitting `app-update-progress`
+#[cfg(not(target_os = "android"))]
+async fn download<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let update = {
+        let state = app.state::<UpdaterState>();
+        let mut pending = state.lock().unwrap();
+        if pending.bytes.is_some() || pending.downloading {
+            return Ok(());
+        }
+        let Some(update) = pending.update.clone() else {
+            return Err("No update available".to_string());
+        };
+        pending.downloading = true;
+        update
+    };
+
+    let progress_app = app.clone();
+    let mut downloaded = 0u64;
+    let result = update
+        .download(
+            move |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = progress_app.emit("app-update-progress", AppUpdateProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e));
+
+    let state = app.state::<UpdaterState>();
+    let mut pending = state.lock().unwrap();
+    pending.downloading = false;
+    pending.bytes = Some(result?);
+    drop(pending);
+
+    eprintln!("⬇️ Update {} downloaded and verified", update.version);
+    let _ = app.emit("app-update-ready", info(&update));
+    Ok(())
+}
+
+/// Startup check; a found update is announced and downloaded in the background
+pub fn spawn_startup_check<R: Runtime>(app: AppHandle<R>) {
+    #[cfg(target_os = "android")]
+    let _ = app;
+    #[cfg(not(target_os = "android"))]
+    tauri::async_runtime::spawn(async move {
+        if !configured(&app) {
+            eprintln!("ℹ️ Update checks are off: no updater public key in this build");
+            return;
+        }
+        match check(&app).await {
+            Ok(Some(info)) => {
+                eprintln!("🆕 Update available: {} -> {}", info.current_version, info.version);
+                let _ = app.emit("app-update-available", info);
+                if let Err(e) = download(&app).await {
+                    eprintln!("⚠️ {}", e);
+                    let _ = app.emit("app-update-error", e);
+                }
+            }
+            Ok(None) => eprintln!("✅ U-Download is up to date"),
+            Err(e) => eprintln!("⚠️ {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn check_for_app_update<R: Runtime>(app_handle: AppHandle<R>) -> Result<Option<AppUpdateInfo>, String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = app_handle;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        check(&app_handle).await
+    }
+}
+
+/// Install the pending update (downloading it first if the background fetch has not
+/// finished) and restart into the new version
+#[tauri::command]
+pub async fn install_app_update<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    #[cfg(target_os = "android")]
+    {
+        let _ = app_handle;
+        Err("unsupported".into())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        download(&app_handle).await?;
+        let (update, bytes) = {
+            let state = app_handle.state::<UpdaterState>();
+            let mut pending = state.lock().unwrap();
+            let update = pending.update.take().ok_or("No update available")?;
+            let bytes = pending
+                .bytes
+                .take()
+                .ok_or("The update is still downloading; try again when it finishes")?;
+            (update, bytes)
+        };
+
+        eprintln!("📦 Installing update {}", update.version);
+        update
+            .install(bytes)
+            .map_err(|e| format!("Failed to install update: {}", e))?;
+
+        crate::aria2::shutdown(&app_handle.state::<crate::aria2::Aria2State>());
+        app_handle.restart();
+    }
+}
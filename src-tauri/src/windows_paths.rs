@@ -0,0 +1,54 @@
+// Windows path limits for downloads: file names are cut short enough for the whole path to fit
+// in MAX_PATH, and names that are reserved device names (CON, AUX, ...) get a leading underscore.
+// Verbatim `\\?\` paths would lift the limit, but ffmpeg and the UI don't handle them.
+
+/// Longest path most Windows programs accept, in UTF-16 units
+pub const MAX_PATH: usize = 260;
+/// Room for the extension and the suffixes yt-dlp adds while downloading (`.f137.webm.part`),
+/// which `--trim-filenames` does not count
+const SUFFIX_ROOM: usize = 24;
+/// Assumed length of each folder level an output template adds, e.g. `%(uploader)s/`
+const TEMPLATE_FOLDER_ROOM: usize = 48;
+/// Shortest name a download is cut to, however deep its folder
+const MIN_NAME_LENGTH: usize = 16;
+const RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+    "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+/// The same names in Python `re` syntax. Windows ignores everything from the first dot, so
+/// `nul.tar.gz` is reserved too.
+const RESERVED_PATTERN: &str = r"(?i)^((?:CON|PRN|AUX|NUL|COM[1-9]|LPT[1-9])\s*(?:\..*)?)$";
+
+/// Whether Windows refuses `name` as a file name
+pub fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    RESERVED.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// `name`, with a leading underscore if it is reserved
+pub fn safe_name(name: &str) -> String {
+    if is_reserved(name) {
+        format!("_{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Longest file name, without extension, that keeps `pattern` (the output folder followed by the
+/// output template) under MAX_PATH
+pub fn name_budget(output_folder: &str, pattern: &str) -> u32 {
+    let template = pattern.strip_prefix(output_folder).unwrap_or(pattern);
+    let levels = template.trim_start_matches(['/', '\\']).matches(['/', '\\']).count();
+    let used = output_folder.encode_utf16().count() + 1 + levels * TEMPLATE_FOLDER_ROOM + SUFFIX_ROOM;
+    MAX_PATH.saturating_sub(used).max(MIN_NAME_LENGTH) as u32
+}
+
+/// yt-dlp arguments that prefix reserved names in the metadata fields file names are made from
+pub fn reserved_name_args() -> Vec<String> {
+    vec![
+        "--replace-in-metadata".to_string(),
+        crate::settings::NAME_FIELDS.to_string(),
+        RESERVED_PATTERN.to_string(),
+        r"_\1".to_string(),
+    ]
+}
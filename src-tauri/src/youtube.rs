@@ -0,0 +1,55 @@
+// YouTube player clients: which of YouTube's apps yt-dlp pretends to be. Switching client is the
+// usual fix when YouTube starts answering 403 or throttling one of them, so failed downloads
+// move on to the next client by themselves.
+use tauri::{AppHandle, Runtime};
+
+use crate::download_manager::DownloadRequest;
+use crate::settings;
+
+/// Values yt-dlp accepts for `youtube:player_client`
+pub const PLAYER_CLIENTS: &[&str] =
+    &["default", "web", "web_safari", "web_embedded", "mweb", "android", "android_vr", "ios", "tv", "tv_embedded"];
+/// Order clients are tried in after a failure, most often working first
+const ROTATION: &[&str] = &["tv", "ios", "web_safari", "android_vr", "mweb", "web_embedded"];
+
+pub fn is_youtube(url: &str) -> bool {
+    let site = crate::history::site_of(url);
+    site == "youtu.be" || site == "youtube.com" || site.ends_with(".youtube.com")
+}
+
+/// Check a comma-separated client list such as `tv,ios`
+pub fn validate_player_client(value: &str) -> Result<(), String> {
+    for client in value.split(',').map(str::trim) {
+        if !PLAYER_CLIENTS.contains(&client) {
+            return Err(format!("Unknown YouTube player client: {} (expected one of {})", client, PLAYER_CLIENTS.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+/// The client a download uses: its own, else the one from settings
+fn client_for<R: Runtime>(app: &AppHandle<R>, request: &DownloadRequest) -> Option<String> {
+    request.options.player_client.clone().or(settings::current(app).youtube.player_client)
+}
+
+/// `--extractor-args` value selecting the player client, if one is chosen
+pub fn extractor_args<R: Runtime>(app: &AppHandle<R>, request: &DownloadRequest) -> Option<String> {
+    client_for(app, request).map(|client| format!("youtube:player_client={}", client))
+}
+
+/// The client to retry a failed YouTube download with, or `None` once the rotation has been
+/// used up or is turned off
+pub fn next_client<R: Runtime>(app: &AppHandle<R>, request: &DownloadRequest) -> Option<String> {
+    if !is_youtube(&request.url) || !settings::current(app).youtube.rotate_clients {
+        return None;
+    }
+    let next = match client_for(app, request) {
+        Some(current) => match ROTATION.iter().position(|client| *client == current) {
+            Some(index) => ROTATION.get(index + 1).copied(),
+            // A client outside the rotation was chosen by hand; start the rotation after it
+            None => ROTATION.first().copied(),
+        },
+        None => ROTATION.first().copied(),
+    }?;
+    Some(next.to_string())
+}
@@ -0,0 +1,84 @@
+// A yt-dlp config file passed with `--config-location`, so advanced users can keep persistent
+// defaults. The app manages one in its data directory, or uses a file the user points at.
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+use crate::{settings, storage};
+
+const MANAGED_CONFIG_FILE: &str = "yt-dlp.conf";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct YtDlpConfig {
+    pub enabled: bool,
+    pub path: String,
+    /// Whether `path` is the app-managed file (editable from the UI) or the user's own
+    pub managed: bool,
+    pub contents: String,
+}
+
+fn managed_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    storage::app_data_file(app, MANAGED_CONFIG_FILE)
+}
+
+/// Config file to pass to yt-dlp, if enabled and present
+pub fn config_location<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    let advanced = settings::current(app).advanced;
+    if !advanced.use_yt_dlp_config {
+        return None;
+    }
+    let path = match advanced.yt_dlp_config_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => managed_path(app).ok()?,
+    };
+    if path.is_file() {
+        Some(path)
+    } else {
+        eprintln!("⚠️  yt-dlp config file {} not found; ignoring it", path.display());
+        None
+    }
+}
+
+/// Each non-comment line of the managed file goes through the same checks as extra arguments
+fn validate_contents(contents: &str) -> Result<(), String> {
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        crate::extra_args::parse(line).map_err(|e| format!("Line {}: {}", number + 1, e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_yt_dlp_config<R: Runtime>(app_handle: AppHandle<R>) -> Result<YtDlpConfig, String> {
+    let advanced = settings::current(&app_handle).advanced;
+    let (path, managed) = match advanced.yt_dlp_config_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => (PathBuf::from(path), false),
+        None => (managed_path(&app_handle)?, true),
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    Ok(YtDlpConfig {
+        enabled: advanced.use_yt_dlp_config,
+        path: path.to_string_lossy().to_string(),
+        managed,
+        contents,
+    })
+}
+
+/// Replace the contents of the app-managed config file
+#[tauri::command]
+pub async fn save_yt_dlp_config<R: Runtime>(app_handle: AppHandle<R>, contents: String) -> Result<(), String> {
+    validate_contents(&contents)?;
+    let path = managed_path(&app_handle)?;
+    let tmp = path.with_extension("conf.tmp");
+    std::fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+    eprintln!("📝 Saved yt-dlp config to {}", path.display());
+    Ok(())
+}
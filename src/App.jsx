@@ -3,7 +3,7 @@ import { getVersion } from "@tauri-apps/api/app";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { downloadDir, videoDir, join, dirname } from "@tauri-apps/api/path";
-import { isPermissionGranted as notifGranted, requestPermission as notifRequest, sendNotification } from "@tauri-apps/plugin-notification";
+import { isPermissionGranted as notifGranted, requestPermission as notifRequest, registerActionTypes, onAction } from "@tauri-apps/plugin-notification";
 import VideoPreview from "./VideoPreview";
 import soundNotifications from "./SoundNotifications";
 import "./App.css";
@@ -13,6 +13,9 @@ function App() {
   const [url, setUrl] = useState("");
   const [downloadType, setDownloadType] = useState("mp4");
   const [quality, setQuality] = useState("best");
+  // Only choices the user made themselves override the per-site presets
+  const [formatChosen, setFormatChosen] = useState(false);
+  const [qualityChosen, setQualityChosen] = useState(false);
   const [outputFolder, setOutputFolder] = useState("");
   const [progress, setProgress] = useState(0);
   const [speed, setSpeed] = useState("");
@@ -30,6 +33,7 @@ function App() {
   const [trimStartTime, setTrimStartTime] = useState(null);
   const [trimEndTime, setTrimEndTime] = useState(null);
   const [isTrimMode, setIsTrimMode] = useState(false);
+  const [isOnline, setIsOnline] = useState(true);
 
   useEffect(() => {
     localStorage.setItem("isDarkMode", JSON.stringify(isDarkMode));
@@ -77,19 +81,100 @@ function App() {
           localStorage.setItem("outputFolder", vdir);
         }
 
-        // Android Share intent via native bridge (file-based)
+        // Android Share intent via native bridge (file-based): queue every accepted link
         try {
-          const shared = await invoke('get_shared_url');
-          if (shared && isValidYouTubeUrl(shared)) {
-            setUrl(shared);
+          const shared = await invoke('get_shared_urls');
+          const skipped = [...shared.rejected];
+          for (const link of shared.accepted) {
+            try {
+              await invoke('start_download', {
+                url: link.url,
+                downloadType: null,
+                quality: null,
+                outputFolder: vdir || outputFolder || null,
+              });
+            } catch (error) {
+              skipped.push(`${link.url} (${error})`);
+            }
+          }
+          if (skipped.length) {
+            alert(`These shared links could not be downloaded:\n\n${skipped.join("\n")}`);
+          }
+        } catch {}
+
+        // Offer to review or report crashes from earlier runs
+        try {
+          const crashes = await invoke('get_crash_reports');
+          for (const report of crashes) {
+            if (window.confirm(`U-Download closed unexpectedly last time.\n\n${report.summary}\n\nOpen the crash report?`)) {
+              await invoke('open_crash_report', { path: report.path });
+            }
+            if (window.confirm('Report this crash on GitHub? You can review the report before sending it.')) {
+              await invoke('submit_crash_report', { path: report.path });
+            }
+            await invoke('dismiss_crash_report', { path: report.path });
+          }
+        } catch {}
+
+        // Offer to continue downloads interrupted by Android killing the process
+        try {
+          const interrupted = await invoke('get_interrupted_downloads');
+          for (const item of interrupted) {
+            if (window.confirm(`Resume interrupted download "${item.title}"?`)) {
+              await invoke('resume_interrupted_download', { id: item.id });
+            } else {
+              await invoke('discard_interrupted_download', { id: item.id });
+            }
           }
         } catch {}
 
+        // Offer to pick up downloads that were still queued or running when the app last closed
+        try {
+          const restored = await invoke('get_restored_downloads');
+          for (const item of restored) {
+            if (window.confirm(`Resume unfinished download "${item.request.url}"?`)) {
+              await invoke('resume_restored_download', { id: item.id });
+            } else {
+              await invoke('discard_restored_download', { id: item.id });
+            }
+          }
+        } catch {}
+
+        // Backend notifications, tray labels and error hints follow the UI language
+        try {
+          await invoke('set_locale', { locale: navigator.language || 'en' });
+        } catch {}
+
         // Notification permission
         try {
           const granted = await notifGranted();
           if (!granted) await notifRequest();
         } catch {}
+
+        // Buttons on download notifications. Only Android and iOS show them; desktop
+        // notifications have no buttons and don't report clicks.
+        try {
+          const labels = await invoke('get_translations', {
+            keys: ['action-open-file', 'action-show-in-folder', 'action-retry'],
+          });
+          await registerActionTypes([
+            { id: 'download-complete', actions: [
+              { id: 'open-file', title: labels['action-open-file'], foreground: true },
+              { id: 'show-in-folder', title: labels['action-show-in-folder'], foreground: true },
+            ] },
+            { id: 'download-failed', actions: [{ id: 'retry', title: labels['action-retry'] }] },
+          ]);
+          await onAction((event) => {
+            const extra = (event.notification ?? event).extra ?? {};
+            if (event.actionId === 'open-file' && extra.id) {
+              invoke('open_downloaded_file', { id: extra.id }).catch(console.error);
+            } else if (event.actionId === 'show-in-folder' && extra.id) {
+              invoke('show_in_folder', { id: extra.id }).catch(console.error);
+            } else if (event.actionId === 'retry' && extra.id) {
+              invoke('retry_download', { id: extra.id }).catch(console.error);
+            }
+          });
+        } catch {}
       } catch {}
     })();
     // eslint-disable-next-line react-hooks/exhaustive-deps
@@ -101,15 +186,21 @@ function App() {
     const savedQuality = localStorage.getItem("quality");
     
     if (savedFolder) setOutputFolder(savedFolder);
-    if (savedType) setDownloadType(savedType);
-    if (savedQuality) setQuality(savedQuality);
+    if (savedType && (!isAndroid || ["mp4", "mp3"].includes(savedType))) {
+      setDownloadType(savedType);
+      setFormatChosen(true);
+    }
+    if (savedQuality) {
+      setQuality(savedQuality);
+      setQualityChosen(true);
+    }
   }, []);
 
   useEffect(() => {
     localStorage.setItem("outputFolder", outputFolder);
-    localStorage.setItem("downloadType", downloadType);
-    localStorage.setItem("quality", quality);
-  }, [outputFolder, downloadType, quality]);
+    if (formatChosen) localStorage.setItem("downloadType", downloadType);
+    if (qualityChosen) localStorage.setItem("quality", quality);
+  }, [outputFolder, downloadType, quality, formatChosen, qualityChosen]);
 
   useEffect(() => {
     const setupListeners = async () => {
@@ -122,13 +213,55 @@ function App() {
       });
 
       const errorUnlisten = await listen("download-error", (event) => {
-        console.error("Download error:", event.payload);
-        alert(`Download Error: ${event.payload}`);
+        const { id, code, message, details } = event.payload;
+        console.error("Download error:", code, details);
+        if (code === 'geo_restricted') {
+          // Offer the region retry right from the error instead of a bare alert
+          if (window.confirm(`${message}\n\nRetry from another region?`)) {
+            const country = window.prompt("Two-letter country code to download from (leave empty to let yt-dlp choose):", "");
+            if (country !== null) {
+              invoke("retry_with_region", { id, country: country.trim() || null, proxy: null })
+                .catch((error) => alert(`Could not retry the download:\n\n${error}`));
+            }
+          }
+        } else if (code !== 'cancelled') {
+          alert(message);
+        }
         setStatus("error");
         setProgress(0);
         setSpeed("");
         setEta("");
-        try { sendNotification({ title: 'Download Failed', body: String(event.payload) }); } catch {}
+      });
+
+      try {
+        const network = await invoke("get_network_status");
+        setIsOnline(network.online);
+      } catch {}
+      const networkUnlisten = await listen("network-status", (event) => {
+        setIsOnline(event.payload.online);
+      });
+
+      const integrityUnlisten = await listen("binary-integrity", async (event) => {
+        const names = event.payload.map((binary) => `${binary.name} (${binary.status})`).join(", ");
+        if (window.confirm(`Some bundled tools are damaged: ${names}.\n\nTry to repair them now?`)) {
+          try {
+            await invoke("repair_binaries");
+            alert("Repair complete.");
+          } catch (error) {
+            alert(`Repair failed:\n\n${error}`);
+          }
+        }
+      });
+
+      const countdownUnlisten = await listen("premiere-countdown", (event) => {
+        const { seconds_remaining, phase } = event.payload;
+        setStatus("scheduled");
+        if (phase === "checking" || seconds_remaining == null) {
+          setEta("Waiting for the stream to start…");
+        } else {
+          const minutes = Math.ceil(seconds_remaining / 60);
+          setEta(minutes >= 60 ? `Starts in ${Math.floor(minutes / 60)}h ${minutes % 60}m` : `Starts in ${minutes}m`);
+        }
       });
 
       const completeUnlisten = await listen("download-complete", (event) => {
@@ -136,13 +269,15 @@ function App() {
         
         // Play completion sound only
         soundNotifications.playDownloadComplete();
-        try { sendNotification({ title: 'Download Complete', body: String(event.payload) }); } catch {}
       });
 
       return () => {
         progressUnlisten();
         errorUnlisten();
         completeUnlisten();
+        networkUnlisten();
+        integrityUnlisten();
+        countdownUnlisten();
       };
     };
 
@@ -242,6 +377,27 @@ function App() {
       }
     }
 
+    // Premieres and streams that have not started can wait in the queue and record when live
+    const info = await invoke("get_video_metadata", { url }).catch(() => null);
+    if (info?.live_status === "is_upcoming") {
+      const starts = info.release_timestamp ? ` at ${new Date(info.release_timestamp * 1000).toLocaleString()}` : "";
+      if (!window.confirm(`This premiere or stream has not started yet${starts}.\n\nWait and record it when it goes live?`)) {
+        return;
+      }
+      try {
+        await invoke("queue_upcoming_stream", {
+          url,
+          downloadType: formatChosen ? downloadType : null,
+          quality: qualityChosen ? quality : null,
+          outputFolder,
+        });
+        setStatus("scheduled");
+      } catch (error) {
+        alert(`Could not schedule the recording:\n\n${error}`);
+      }
+      return;
+    }
+
     setStatus("downloading");
     setProgress(0);
     setSpeed("");
@@ -250,8 +406,8 @@ function App() {
     try {
       await invoke("start_download", {
         url,
-        downloadType,
-        quality,
+        downloadType: formatChosen ? downloadType : null,
+        quality: qualityChosen ? quality : null,
         outputFolder,
         startTime: trimStartTime,
         endTime: trimEndTime
@@ -292,12 +448,42 @@ function App() {
         ffmpegResult = `\n\n❌ FFmpeg: Bundled binary not found (${error})`;
       }
 
-      alert(`Dependencies Check:\n\n${result}${ffmpegResult}`);
+      let updatesResult = "";
+      try {
+        const updates = await invoke("check_binary_updates");
+        const available = updates.filter((u) => u.update_available);
+        updatesResult = available.length
+          ? `\n\nUpdates available:\n${available.map((u) => `⬆️ ${u.name}: ${u.installed} → ${u.latest}`).join("\n")}`
+          : "\n\nAll bundled tools are up to date.";
+      } catch (error) {
+        updatesResult = `\n\n⚠️ Could not check for updates (${error})`;
+      }
+
+      alert(`Dependencies Check:\n\n${result}${ffmpegResult}${updatesResult}`);
+
+      if (updatesResult.includes("yt-dlp:") && window.confirm("Update yt-dlp now?")) {
+        try {
+          const update = await invoke("update_yt_dlp");
+          alert(`yt-dlp ${update.version} installed. Use "Roll back yt-dlp" if downloads start failing.`);
+        } catch (error) {
+          alert(`yt-dlp update failed:\n\n${error}`);
+        }
+      }
     } catch (error) {
       alert(`Dependencies Check Failed:\n\n${error}`);
     }
   };
 
+  const rollbackYtDlp = async () => {
+    if (!window.confirm("Switch back to the yt-dlp version used before the last update?")) return;
+    try {
+      const version = await invoke("rollback_yt_dlp");
+      alert(`yt-dlp ${version} is now in use.`);
+    } catch (error) {
+      alert(`Rollback failed:\n\n${error}`);
+    }
+  };
+
   return (
     <div data-theme={isDarkMode ? 'dark' : 'light'} className={`min-h-screen transition-all duration-500 ${
       isDarkMode 
@@ -305,6 +491,11 @@ function App() {
         : 'bg-gradient-to-br from-blue-50 via-white to-purple-50'
     }`}>
       <div className="container mx-auto px-6 py-8 max-w-5xl">
+        {!isOnline && (
+          <div className="mb-6 px-4 py-3 rounded-xl text-sm font-medium bg-amber-500/15 text-amber-600 border border-amber-500/30">
+            You're offline. New downloads are queued and start automatically when the connection returns.
+          </div>
+        )}
         {/* Header */}
         <div className="flex justify-between items-center mb-12">
           <div className="flex items-center gap-4">
@@ -349,6 +540,28 @@ function App() {
               }`}
             >
             </button>
+            {!isAndroid && (
+              <button
+                onClick={() => invoke("open_monitor_window").catch(console.error)}
+                className={`px-3 py-2 rounded-full text-xs font-semibold transition-colors hover:scale-105 ${
+                  isDarkMode 
+                    ? 'bg-blue-900/30 text-blue-400 border border-blue-400/30 hover:bg-blue-800/40' 
+                    : 'bg-blue-100 text-blue-700 border border-blue-200 hover:bg-blue-200'
+                }`}
+              >
+                Monitor
+              </button>
+            )}
+            <button
+              onClick={rollbackYtDlp}
+              className={`px-3 py-2 rounded-full text-xs font-semibold transition-colors hover:scale-105 ${
+                isDarkMode 
+                  ? 'bg-blue-900/30 text-blue-400 border border-blue-400/30 hover:bg-blue-800/40' 
+                  : 'bg-blue-100 text-blue-700 border border-blue-200 hover:bg-blue-200'
+              }`}
+            >
+              Roll back yt-dlp
+            </button>
             <div className={`px-3 py-2 rounded-full text-xs font-semibold ${
               isDarkMode 
                 ? 'bg-green-900/30 text-green-400 border border-green-400/30' 
@@ -469,7 +682,10 @@ function App() {
               <div className="relative group">
                 <select
                   value={downloadType}
-                  onChange={(e) => setDownloadType(e.target.value)}
+                  onChange={(e) => {
+                    setDownloadType(e.target.value);
+                    setFormatChosen(true);
+                  }}
                   className={`w-full px-4 py-4 pr-10 rounded-2xl border-2 focus:outline-none transition-all duration-300 text-lg cursor-pointer appearance-none ${
                     isDarkMode 
                       ? 'bg-gray-700/50 border-gray-600/50 text-white focus:border-blue-500/50 focus:bg-gray-700' 
@@ -478,6 +694,15 @@ function App() {
                 >
                   <option value="mp4">🎥 MP4 (Video)</option>
                   <option value="mp3">🎵 MP3 (Audio Only)</option>
+                  {/* Android saves streams as fetched and cannot convert to other audio formats */}
+                  {!isAndroid && (
+                    <>
+                      <option value="m4a">🎵 M4A (Audio Only)</option>
+                      <option value="opus">🎵 Opus (Audio Only)</option>
+                      <option value="flac">🎼 FLAC (Lossless Audio)</option>
+                      <option value="wav">🎼 WAV (Uncompressed Audio)</option>
+                    </>
+                  )}
                 </select>
                 <div className="absolute right-4 top-1/2 -translate-y-1/2 text-gray-400 pointer-events-none">
                   <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
@@ -496,7 +721,10 @@ function App() {
               <div className="relative group">
                 <select
                   value={quality}
-                  onChange={(e) => setQuality(e.target.value)}
+                  onChange={(e) => {
+                    setQuality(e.target.value);
+                    setQualityChosen(true);
+                  }}
                   className={`w-full px-4 py-4 pr-10 rounded-2xl border-2 focus:outline-none transition-all duration-300 text-lg cursor-pointer appearance-none ${
                     isDarkMode 
                       ? 'bg-gray-700/50 border-gray-600/50 text-white focus:border-green-500/50 focus:bg-gray-700' 
@@ -507,6 +735,9 @@ function App() {
                   <option value="480">💻 480p (Standard)</option>
                   <option value="720">🖥️ 720p (HD)</option>
                   <option value="1080">🎯 1080p (Full HD)</option>
+                  <option value="1440">🔷 1440p (QHD)</option>
+                  <option value="2160">🌟 2160p (4K)</option>
+                  <option value="4320">💎 4320p (8K)</option>
                   <option value="best">✨ Best Available</option>
                 </select>
                 <div className="absolute right-4 top-1/2 -translate-y-1/2 text-gray-400 pointer-events-none">
@@ -0,0 +1,65 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// Compact list of running downloads for the detachable monitor window
+function Monitor() {
+  const [downloads, setDownloads] = useState({});
+  const [queue, setQueue] = useState(null);
+
+  useEffect(() => {
+    let unlisteners = [];
+    (async () => {
+      try {
+        setQueue(await invoke("get_queue_progress"));
+        const jobs = await invoke("list_downloads");
+        setDownloads(Object.fromEntries(
+          jobs.filter((job) => job.status === "active").map((job) => [job.id, job.progress]),
+        ));
+      } catch {}
+
+      unlisteners = await Promise.all([
+        listen("download-progress", (event) => {
+          const progress = event.payload;
+          setDownloads((current) => {
+            const next = { ...current };
+            if (progress.status === "completed" || progress.status === "error") {
+              delete next[progress.id];
+            } else {
+              next[progress.id] = progress;
+            }
+            return next;
+          });
+        }),
+        listen("queue-progress", (event) => setQueue(event.payload)),
+      ]);
+    })();
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  }, []);
+
+  const active = Object.values(downloads);
+
+  return (
+    <div className="min-h-screen p-3 text-sm bg-gray-900 text-gray-100">
+      {queue && (
+        <div className="mb-2 text-xs text-gray-400">
+          {queue.active} active, {queue.queued} queued — {Math.round(queue.percentage)}% · {queue.speed}
+        </div>
+      )}
+      {active.length === 0 && <div className="text-gray-400">No active downloads</div>}
+      {active.map((download) => (
+        <div key={download.id} className="mb-3">
+          <div className="truncate font-medium">{download.title || "Preparing…"}</div>
+          <div className="h-1.5 mt-1 rounded bg-gray-700 overflow-hidden">
+            <div className="h-full bg-blue-500" style={{ width: `${download.percentage}%` }} />
+          </div>
+          <div className="mt-0.5 text-xs text-gray-400">
+            {download.percentage.toFixed(1)}% · {download.speed} · {download.eta}
+          </div>
+        </div>
+      ))}
+    </div>
+  );
+}
+
+export default Monitor;
@@ -0,0 +1,59 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// Frameless always-on-top strip with the most recently updated download. The whole strip is a
+// drag region; double-click closes it.
+function Overlay() {
+  const [current, setCurrent] = useState(null);
+  const [queue, setQueue] = useState(null);
+
+  useEffect(() => {
+    let unlisteners = [];
+    (async () => {
+      try {
+        setQueue(await invoke("get_queue_progress"));
+      } catch {}
+
+      unlisteners = await Promise.all([
+        listen("download-progress", (event) => {
+          const progress = event.payload;
+          setCurrent((shown) => {
+            if (progress.status === "completed" || progress.status === "error") {
+              return shown && shown.id === progress.id ? null : shown;
+            }
+            return progress;
+          });
+        }),
+        listen("queue-progress", (event) => setQueue(event.payload)),
+      ]);
+    })();
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  }, []);
+
+  const percentage = current ? current.percentage : queue?.active ? queue.percentage : 0;
+  const speed = current ? current.speed : queue?.speed;
+
+  return (
+    <div
+      data-tauri-drag-region
+      onDoubleClick={() => invoke("toggle_progress_overlay")}
+      className="h-screen px-3 py-2 text-xs select-none cursor-move bg-gray-900/90 text-gray-100"
+    >
+      <div data-tauri-drag-region className="flex justify-between">
+        <span data-tauri-drag-region className="truncate">
+          {current ? current.title || "Preparing…" : queue?.active ? `${queue.active} active` : "Idle"}
+        </span>
+        {queue?.queued > 0 && <span data-tauri-drag-region className="ml-2 text-gray-400">+{queue.queued}</span>}
+      </div>
+      <div data-tauri-drag-region className="h-1.5 mt-1.5 rounded bg-gray-700 overflow-hidden">
+        <div className="h-full bg-blue-500" style={{ width: `${percentage}%` }} />
+      </div>
+      <div data-tauri-drag-region className="mt-1 text-gray-400">
+        {percentage.toFixed(1)}%{speed ? ` · ${speed}` : ""}
+      </div>
+    </div>
+  );
+}
+
+export default Overlay;
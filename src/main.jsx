@@ -1,10 +1,16 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import Monitor from "./Monitor";
+import Overlay from "./Overlay";
 import "./App.css";
 
+// Secondary windows load the same bundle with ?view=...
+const view = new URLSearchParams(window.location.search).get("view");
+const Root = { monitor: Monitor, overlay: Overlay }[view] || App;
+
 ReactDOM.createRoot(document.getElementById("root")).render(
   <React.StrictMode>
-    <App />
+    <Root />
   </React.StrictMode>,
 );